@@ -1,4 +1,4 @@
-use gdrust_utils::fuzzy::{FuzzyRule, FuzzySet, FuzzySystem};
+use gdrust_utils::fuzzy::{FuzzyRule, FuzzySet, FuzzySystem, PartitionShape};
 use std::collections::HashMap;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -32,26 +32,16 @@ impl GameDifficulty {
     pub fn new() -> Self {
         let mut system = FuzzySystem::new();
 
-        // Define fuzzy sets
-        system.add_input_set(FuzzySet::new(
+        // Define fuzzy sets, peaking at 3, 5, 7 and 9 and summing to 1 everywhere
+        let levels = [
             DifficultyLevel::Easy,
-            vec![(0.0, 1.0), (3.0, 1.0), (5.0, 0.0)],
-        ));
-
-        system.add_input_set(FuzzySet::new(
             DifficultyLevel::Normal,
-            vec![(3.0, 0.0), (5.0, 1.0), (7.0, 0.0)],
-        ));
-
-        system.add_input_set(FuzzySet::new(
             DifficultyLevel::Hard,
-            vec![(5.0, 0.0), (7.0, 1.0), (9.0, 0.0)],
-        ));
-
-        system.add_input_set(FuzzySet::new(
             DifficultyLevel::Nightmare,
-            vec![(7.0, 0.0), (9.0, 1.0), (10.0, 1.0)],
-        ));
+        ];
+        for set in FuzzySet::partition_at(levels, &[3.0, 5.0, 7.0, 9.0], PartitionShape::Triangle) {
+            system.add_input_set(set);
+        }
 
         // Define rules
         system.add_rule(
@@ -1,51 +1,175 @@
+use std::borrow::Borrow;
 use std::collections::HashMap;
-
-#[derive(Debug, Clone)]
+use std::hash::Hash;
+
+mod analysis;
+mod antecedent;
+mod baked;
+mod clustering;
+mod compiled;
+mod consequent;
+mod coverage;
+mod dsl;
+mod fcl;
+mod fixed;
+mod hedge;
+mod inference;
+#[cfg(any(feature = "json", feature = "ron"))]
+mod io;
+mod operators;
+mod partition;
+mod plot;
+mod selector;
+mod shape;
+mod smoothing;
+mod trace;
+mod training;
+mod type2;
+mod validation;
+mod variable;
+
+pub use analysis::{RuleIssue, Unreachable};
+pub use antecedent::Antecedent;
+pub use baked::{BakeOptions, BakedAxis, BakedSystem, Interpolation};
+pub use clustering::{ClusterMethod, ClusterOptions, ClusterSets, Clusters};
+pub use compiled::CompiledSystem;
+pub use consequent::{Consequent, LinearConsequent};
+pub use coverage::{
+    CoverageGap, CoverageInterval, CoverageLevel, CoverageOptions, CoverageReport, InputCoverage,
+};
+pub use dsl::{ParseError, ParseErrorKind, RuleParser};
+pub use fcl::FclExportError;
+pub use fixed::{Fixed, FixedError, FixedSystem};
+pub use hedge::Hedge;
+pub use inference::{Defuzzifier, Inference};
+#[cfg(any(feature = "json", feature = "ron"))]
+pub use io::DataError;
+pub use operators::{Implication, LogicOperators, Negation, SNorm, TNorm};
+pub use partition::PartitionShape;
+pub use plot::PlotOptions;
+pub use selector::{BehaviorSelector, Selection, SelectionChange, SelectorOptions};
+pub use shape::{CustomShape, MembershipFunction, Shape};
+pub use smoothing::{OutputSmoother, SmoothedSystem, Smoothing};
+pub use trace::{Aggregation, Contribution, EvaluationTrace, OutputTrace, RuleTrace, TermTrace};
+pub use training::{FitReport, OutputFit, TrainingOptions, TrainingSample};
+pub use type2::{IntervalSet, IntervalSystem, TypeReduction};
+pub use validation::{Diagnostic, FuzzySystemBuilder, SetOwner, Severity};
+pub use variable::{FuzzyVariable, OutOfRange};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FuzzySet<T> {
     pub category: T,
-    pub points: Vec<(f32, f32)>, // (input_value, membership_degree)
+    pub shape: Shape,
 }
 
 impl<T> FuzzySet<T> {
+    /// Creates a piecewise-linear set from `(input_value, membership_degree)` points
     pub fn new(category: T, points: Vec<(f32, f32)>) -> Self {
-        Self { category, points }
+        Self::with_shape(category, Shape::Points(points))
     }
 
-    pub fn membership(&self, input: f32) -> f32 {
-        // Handle edge cases
-        if self.points.is_empty() {
-            return 0.0;
-        }
+    pub fn with_shape(category: T, shape: Shape) -> Self {
+        Self { category, shape }
+    }
 
-        if input <= self.points[0].0 {
-            return self.points[0].1;
-        }
-        if input >= self.points.last().unwrap().0 {
-            return self.points.last().unwrap().1;
-        }
+    pub fn triangle(category: T, left: f32, peak: f32, right: f32) -> Self {
+        Self::with_shape(category, Shape::Triangle { left, peak, right })
+    }
+
+    pub fn trapezoid(category: T, left: f32, left_top: f32, right_top: f32, right: f32) -> Self {
+        Self::with_shape(
+            category,
+            Shape::Trapezoid {
+                left,
+                left_top,
+                right_top,
+                right,
+            },
+        )
+    }
 
-        // Find the two points to interpolate between
-        for i in 0..self.points.len() - 1 {
-            let (x1, y1) = self.points[i];
-            let (x2, y2) = self.points[i + 1];
+    pub fn gaussian(category: T, mean: f32, sigma: f32) -> Self {
+        Self::with_shape(category, Shape::Gaussian { mean, sigma })
+    }
 
-            if input >= x1 && input <= x2 {
-                if x2 == x1 {
-                    return y1;
-                }
-                // Linear interpolation
-                return y1 + (y2 - y1) * (input - x1) / (x2 - x1);
-            }
-        }
+    pub fn bell(category: T, width: f32, slope: f32, center: f32) -> Self {
+        Self::with_shape(
+            category,
+            Shape::Bell {
+                width,
+                slope,
+                center,
+            },
+        )
+    }
+
+    pub fn sigmoid(category: T, slope: f32, center: f32) -> Self {
+        Self::with_shape(category, Shape::Sigmoid { slope, center })
+    }
+
+    pub fn singleton(category: T, value: f32) -> Self {
+        Self::with_shape(category, Shape::Singleton(value))
+    }
 
-        0.0
+    pub fn custom(category: T, function: impl MembershipFunction + 'static) -> Self {
+        Self::with_shape(category, Shape::custom(function))
+    }
+
+    pub fn membership(&self, input: f32) -> f32 {
+        self.shape.membership(input)
+    }
+
+    /// Membership degree with hedges applied in reading order, see [`Hedge::apply_all`]
+    pub fn hedged_membership(&self, input: f32, hedges: &[Hedge]) -> f32 {
+        Hedge::apply_all(hedges, self.membership(input))
+    }
+
+    /// The range of inputs outside which the membership no longer changes
+    pub fn support(&self) -> Option<(f32, f32)> {
+        self.shape.support()
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        deserialize = "InputType: serde::Deserialize<'de>, OutputType: serde::Deserialize<'de> + Eq + Hash"
+    ))
+)]
 pub struct FuzzyRule<InputType, OutputType> {
-    pub condition: InputType,
-    pub consequences: HashMap<OutputType, f32>,
+    pub condition: Antecedent<InputType>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub consequences: HashMap<OutputType, Consequent<InputType>>,
+    /// Certainty of the rule in 0..=1, scaling its firing strength
+    #[cfg_attr(feature = "serde", serde(default = "default_weight"))]
+    pub weight: f32,
+    /// Disabled rules never fire
+    #[cfg_attr(feature = "serde", serde(default = "default_enabled"))]
+    pub enabled: bool,
+    /// Index into [`FuzzySystem::rule_blocks`] of the block whose operators the rule uses,
+    /// or `None` for the operators of the system
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub block: Option<usize>,
+}
+
+/// The entries of `consequences` in the order of their outputs
+fn sorted_by_output<O: Ord, V>(consequences: &HashMap<O, V>) -> Vec<(&O, &V)> {
+    let mut sorted: Vec<(&O, &V)> = consequences.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    sorted
+}
+
+#[cfg(feature = "serde")]
+fn default_weight() -> f32 {
+    1.0
+}
+
+#[cfg(feature = "serde")]
+fn default_enabled() -> bool {
+    true
 }
 
 impl<InputType, OutputType> FuzzyRule<InputType, OutputType>
@@ -53,30 +177,118 @@ where
     InputType: Clone,
     OutputType: Clone + Eq + std::hash::Hash,
 {
-    pub fn new(condition: InputType) -> Self {
+    /// Creates a rule from a single category or a full [`Antecedent`] expression
+    pub fn new(condition: impl Into<Antecedent<InputType>>) -> Self {
         Self {
-            condition,
+            condition: condition.into(),
             consequences: HashMap::new(),
+            weight: 1.0,
+            enabled: true,
+            block: None,
+        }
+    }
+
+    /// Evaluates the rule with the operators of the rule block at `block`
+    pub fn in_block(mut self, block: usize) -> Self {
+        self.block = Some(block);
+        self
+    }
+
+    /// Sets the certainty of the rule, clamped into 0..=1
+    pub fn with_weight(mut self, weight: f32) -> Self {
+        self.weight = weight.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// Firing strength of the rule for a condition with this truth degree
+    pub fn strength(&self, truth: f32) -> f32 {
+        if self.enabled {
+            truth * self.weight
+        } else {
+            0.0
         }
     }
 
     pub fn with_consequence(mut self, output: OutputType, value: f32) -> Self {
-        self.consequences.insert(output, value);
+        self.consequences
+            .insert(output, Consequent::Constant(value));
+        self
+    }
+
+    /// Makes `output` a linear function of the inputs (first-order Takagi-Sugeno)
+    pub fn with_linear_consequence(
+        mut self,
+        output: OutputType,
+        function: LinearConsequent,
+    ) -> Self {
+        self.consequences
+            .insert(output, Consequent::Linear(function));
+        self
+    }
+
+    /// Points `output` to the output set with this category, for Mamdani inference
+    pub fn with_output_term(mut self, output: OutputType, category: InputType) -> Self {
+        self.consequences.insert(output, Consequent::Term(category));
         self
     }
 }
 
+/// A named group of rules evaluated with their own operators instead of those of the system.
+/// Under Mamdani inference the implied sets of the block are aggregated with the block's
+/// aggregation first, then combined with the rest using the system's.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleBlock {
+    pub name: String,
+    pub operators: LogicOperators,
+}
+
+impl RuleBlock {
+    pub fn new(name: impl Into<String>, operators: LogicOperators) -> Self {
+        Self {
+            name: name.into(),
+            operators,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(
+        default,
+        bound(
+            deserialize = "InputType: serde::Deserialize<'de>, OutputType: serde::Deserialize<'de> + Eq + Hash"
+        )
+    )
+)]
 pub struct FuzzySystem<InputType, OutputType> {
+    /// Sets read by unnamed terms, or by named terms without a matching input variable
     pub input_sets: Vec<FuzzySet<InputType>>,
+    pub input_variables: Vec<FuzzyVariable<InputType>>,
     pub rules: Vec<FuzzyRule<InputType, OutputType>>,
+    pub output_variables: Vec<(OutputType, FuzzyVariable<InputType>)>,
+    pub operators: LogicOperators,
+    pub rule_blocks: Vec<RuleBlock>,
+    pub inference: Inference,
 }
 
 impl<InputType, OutputType> Default for FuzzySystem<InputType, OutputType> {
     fn default() -> Self {
         Self {
             input_sets: Vec::new(),
+            input_variables: Vec::new(),
             rules: Vec::new(),
+            output_variables: Vec::new(),
+            operators: LogicOperators::default(),
+            rule_blocks: Vec::new(),
+            inference: Inference::default(),
         }
     }
 }
@@ -89,7 +301,12 @@ where
     pub fn new() -> Self {
         Self {
             input_sets: Vec::new(),
+            input_variables: Vec::new(),
             rules: Vec::new(),
+            output_variables: Vec::new(),
+            operators: LogicOperators::default(),
+            rule_blocks: Vec::new(),
+            inference: Inference::default(),
         }
     }
 
@@ -101,42 +318,321 @@ where
         self.rules.push(rule);
     }
 
+    /// Adds a rule block and returns its index, for [`FuzzyRule::in_block`]
+    pub fn add_rule_block(&mut self, block: RuleBlock) -> usize {
+        self.rule_blocks.push(block);
+        self.rule_blocks.len() - 1
+    }
+
+    /// The operators of the rule's block, or of the system if the rule has no block or its
+    /// block doesn't exist
+    pub fn rule_operators(&self, rule: &FuzzyRule<InputType, OutputType>) -> &LogicOperators {
+        rule.block
+            .and_then(|block| self.rule_blocks.get(block))
+            .map_or(&self.operators, |block| &block.operators)
+    }
+
+    /// Adds a variable that named terms with the same input name read from
+    pub fn add_input_variable(&mut self, variable: FuzzyVariable<InputType>) {
+        self.input_variables.push(variable);
+    }
+
+    /// Adds the variable holding the output sets and universe of `output`, for Mamdani
+    /// inference
+    pub fn add_output_variable(&mut self, output: OutputType, variable: FuzzyVariable<InputType>) {
+        self.output_variables.push((output, variable));
+    }
+
+    /// Adds a set that rules can point `output` to with [`FuzzyRule::with_output_term`].
+    /// The universe of the output variable grows to cover the set.
+    pub fn add_output_set(&mut self, output: OutputType, set: FuzzySet<InputType>) {
+        let (min, max) = set.support().unwrap_or((0.0, 0.0));
+        match self.output_variables.iter_mut().find(|(o, _)| o == &output) {
+            Some((_, variable)) => {
+                variable.min = variable.min.min(min);
+                variable.max = variable.max.max(max);
+                variable.add_term(set);
+            }
+            None => {
+                let variable = FuzzyVariable::new("", min, max).with_term(set);
+                self.output_variables.push((output, variable));
+            }
+        }
+    }
+
+    pub fn input_variable(&self, name: &str) -> Option<&FuzzyVariable<InputType>> {
+        self.input_variables.iter().find(|v| v.name == name)
+    }
+
+    pub fn output_variable(&self, output: &OutputType) -> Option<&FuzzyVariable<InputType>> {
+        self.output_variables
+            .iter()
+            .find(|(o, _)| o == output)
+            .map(|(_, variable)| variable)
+    }
+
+    /// The set of `input IS category`, looked up in the input variable called `input` or in
+    /// [`FuzzySystem::input_sets`] if there is none
+    pub fn term(&self, input: Option<&str>, category: &InputType) -> Option<&FuzzySet<InputType>> {
+        match input.and_then(|name| self.input_variable(name)) {
+            Some(variable) => variable.term(category),
+            None => self.input_sets.iter().find(|set| &set.category == category),
+        }
+    }
+
+    /// Index of the set of `input IS category` in the terms of the input variable or in
+    /// [`FuzzySystem::input_sets`], mirroring [`FuzzySystem::term`]
+    fn term_index(&self, input: Option<&str>, category: &InputType) -> Option<usize> {
+        let sets = match input.and_then(|name| self.input_variable(name)) {
+            Some(variable) => &variable.terms,
+            None => &self.input_sets,
+        };
+        sets.iter().position(|set| &set.category == category)
+    }
+
+    /// Evaluates the system for a single crisp input, which every term reads regardless of
+    /// its input name
     pub fn evaluate(&self, input_value: f32) -> HashMap<OutputType, f32> {
+        self.evaluate_with(&|_| Some(input_value), None)
+    }
+
+    /// Evaluates the system for several named inputs. Terms reading an input that is missing
+    /// from `inputs` (including unnamed terms) have a membership of 0, and linear consequences
+    /// reading a missing input are skipped.
+    pub fn evaluate_inputs<K>(&self, inputs: &HashMap<K, f32>) -> HashMap<OutputType, f32>
+    where
+        K: Borrow<str> + Eq + Hash,
+    {
+        self.evaluate_with(
+            &|input| input.and_then(|name| inputs.get(name).copied()),
+            None,
+        )
+    }
+
+    /// Declared outputs followed by those only rules produce, each once. The outputs of
+    /// each rule are visited in order, so the result doesn't depend on hashing.
+    fn outputs(&self) -> Vec<OutputType>
+    where
+        OutputType: Ord,
+    {
+        let mut outputs: Vec<OutputType> = Vec::new();
+        let declared = self.output_variables.iter().map(|(output, _)| output);
+        let produced = self
+            .rules
+            .iter()
+            .flat_map(|rule| sorted_by_output(&rule.consequences))
+            .map(|(output, _)| output);
+        for output in declared.chain(produced) {
+            if !outputs.contains(output) {
+                outputs.push(output.clone());
+            }
+        }
+        outputs
+    }
+
+    /// Membership degree of `input IS category`, or 0 without an input value or matching set
+    fn term_membership(
+        &self,
+        input: Option<&str>,
+        value: Option<f32>,
+        category: &InputType,
+    ) -> f32 {
+        value
+            .and_then(|value| self.term(input, category).map(|set| set.membership(value)))
+            .unwrap_or(0.0)
+    }
+
+    /// Applies the out of range policy of the input variable called `input`, if any
+    fn resolve_input(&self, input: Option<&str>, value: Option<f32>) -> Option<f32> {
+        match input.and_then(|name| self.input_variable(name)) {
+            Some(variable) => variable.resolve(value?),
+            None => value,
+        }
+    }
+
+    /// Evaluates the rules, recording every step into `trace` if there is one
+    fn evaluate_with(
+        &self,
+        raw_input: &dyn Fn(Option<&str>) -> Option<f32>,
+        mut trace: Option<&mut EvaluationTrace<InputType, OutputType>>,
+    ) -> HashMap<OutputType, f32> {
+        let input = |name: Option<&str>| self.resolve_input(name, raw_input(name));
         let mut weighted_outputs: HashMap<OutputType, Vec<(f32, f32)>> = HashMap::new();
+        let mut fired_terms: HashMap<OutputType, Vec<FiredTerm<InputType>>> = HashMap::new();
 
         // For each rule, calculate its strength and apply consequences
-        for rule in &self.rules {
-            // Find membership degree for this rule's condition
-            let rule_strength = self
-                .input_sets
-                .iter()
-                .find(|set| set.category == rule.condition)
-                .map(|set| set.membership(input_value))
-                .unwrap_or(0.0);
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !rule.enabled {
+                continue;
+            }
+            let truth =
+                rule.condition
+                    .evaluate(self.rule_operators(rule), &mut |name, category| {
+                        let value = input(name);
+                        let membership = self.term_membership(name, value, category);
+                        if let Some(trace) = trace.as_deref_mut() {
+                            trace.record_membership(name, category, value, membership);
+                        }
+                        membership
+                    });
+            let rule_strength = rule.strength(truth);
+            if let Some(trace) = trace.as_deref_mut() {
+                trace.record_rule(index, rule_strength);
+            }
 
             if rule_strength > 0.0 {
-                for (output_param, value) in &rule.consequences {
-                    weighted_outputs
-                        .entry(output_param.clone())
-                        .or_default()
-                        .push((*value, rule_strength));
+                for (output_param, consequent) in &rule.consequences {
+                    match (consequent, &self.inference) {
+                        (Consequent::Constant(value), Inference::WeightedAverage) => {
+                            weighted_outputs
+                                .entry(output_param.clone())
+                                .or_default()
+                                .push((*value, rule_strength));
+                            if let Some(trace) = trace.as_deref_mut() {
+                                trace
+                                    .record_contribution(output_param, Contribution::Crisp(*value));
+                            }
+                        }
+                        (Consequent::Linear(function), Inference::WeightedAverage) => {
+                            if let Some(value) = function.value(|name| input(Some(name))) {
+                                weighted_outputs
+                                    .entry(output_param.clone())
+                                    .or_default()
+                                    .push((value, rule_strength));
+                                if let Some(trace) = trace.as_deref_mut() {
+                                    trace.record_contribution(
+                                        output_param,
+                                        Contribution::Crisp(value),
+                                    );
+                                }
+                            }
+                        }
+                        (Consequent::Term(category), Inference::Mamdani { .. }) => {
+                            fired_terms
+                                .entry(output_param.clone())
+                                .or_default()
+                                .push(FiredTerm {
+                                    category,
+                                    strength: rule_strength,
+                                    block: rule.block,
+                                });
+                            if let Some(trace) = trace.as_deref_mut() {
+                                trace.record_contribution(
+                                    output_param,
+                                    Contribution::Term(category.clone()),
+                                );
+                            }
+                        }
+                        _ => {}
+                    }
                 }
             }
         }
 
-        // Calculate weighted averages
         let mut result = HashMap::new();
+
+        if let Inference::Mamdani {
+            defuzzifier,
+            resolution,
+        } = self.inference
+        {
+            for (param, terms) in fired_terms {
+                let Some(samples) = self.mamdani_samples(&param, &terms, resolution) else {
+                    continue;
+                };
+                let value = defuzzifier.defuzzify(&samples);
+                if let Some(trace) = trace.as_deref_mut() {
+                    trace.record_output(&param, value, Aggregation::Mamdani { samples });
+                }
+                if let Some(value) = value {
+                    result.insert(param, value);
+                }
+            }
+            return result;
+        }
+
+        // Calculate weighted averages
         for (param, weighted_values) in weighted_outputs {
             let total_weight: f32 = weighted_values.iter().map(|(_, w)| w).sum();
             let weighted_sum: f32 = weighted_values.iter().map(|(v, w)| v * w).sum();
 
-            if total_weight > 0.0 {
-                result.insert(param, weighted_sum / total_weight);
+            let value = (total_weight > 0.0).then(|| weighted_sum / total_weight);
+            if let Some(trace) = trace.as_deref_mut() {
+                let aggregation = Aggregation::WeightedAverage {
+                    weighted_sum,
+                    total_weight,
+                };
+                trace.record_output(&param, value, aggregation);
+            }
+            if let Some(value) = value {
+                result.insert(param, value);
             }
         }
 
         result
     }
+
+    /// Aggregates the implied output sets of `output`, sampled across its universe
+    fn mamdani_samples(
+        &self,
+        output: &OutputType,
+        fired_terms: &[FiredTerm<InputType>],
+        resolution: usize,
+    ) -> Option<Vec<(f32, f32)>> {
+        let variable = self.output_variable(output)?;
+
+        let implied: Vec<(&FuzzySet<InputType>, &FiredTerm<InputType>)> = fired_terms
+            .iter()
+            .filter_map(|fired| variable.term(fired.category).map(|set| (set, fired)))
+            .collect();
+
+        let mut blocks = vec![0.0; self.rule_blocks.len()];
+        let samples: Vec<(f32, f32)> =
+            inference::sample_positions(variable.min, variable.max, resolution)
+                .map(|x| {
+                    let implied = implied
+                        .iter()
+                        .map(|(set, fired)| (fired.block, fired.strength, set.membership(x)));
+                    (x, self.aggregate(implied, &mut blocks))
+                })
+                .collect();
+
+        Some(samples)
+    }
+
+    /// Implies and aggregates `(block, strength, membership)` output set memberships at one
+    /// position. Each block aggregates with its own operators and the blocks are then combined
+    /// with the aggregation of the system. `blocks` is scratch space with one entry per block.
+    fn aggregate(
+        &self,
+        implied: impl IntoIterator<Item = (Option<usize>, f32, f32)>,
+        blocks: &mut [f32],
+    ) -> f32 {
+        blocks.fill(0.0);
+        let mut membership = 0.0;
+        for (block, strength, set_membership) in implied {
+            let block = block.filter(|block| *block < blocks.len());
+            let operators = match block {
+                Some(block) => &self.rule_blocks[block].operators,
+                None => &self.operators,
+            };
+            let implied = operators.implication.apply(strength, set_membership);
+            match block {
+                Some(block) => blocks[block] = operators.aggregation.apply(blocks[block], implied),
+                None => membership = operators.aggregation.apply(membership, implied),
+            }
+        }
+        blocks.iter().fold(membership, |acc, block| {
+            self.operators.aggregation.apply(acc, *block)
+        })
+    }
+}
+
+/// An output term consequence of a fired rule, for Mamdani inference
+struct FiredTerm<'a, InputType> {
+    category: &'a InputType,
+    strength: f32,
+    block: Option<usize>,
 }
 
 #[cfg(test)]
@@ -150,7 +646,7 @@ mod tests {
         let set = FuzzySet::new("temperature", points.clone());
 
         assert_eq!(set.category, "temperature");
-        assert_eq!(set.points, points);
+        assert_eq!(set.shape, Shape::Points(points));
     }
 
     #[test]
@@ -233,7 +729,7 @@ mod tests {
     #[test]
     fn test_fuzzy_rule_creation() {
         let rule: FuzzyRule<&'static str, &'static str> = FuzzyRule::new("high_temp");
-        assert_eq!(rule.condition, "high_temp");
+        assert_eq!(rule.condition, Antecedent::from("high_temp"));
         assert!(rule.consequences.is_empty());
     }
 
@@ -243,9 +739,15 @@ mod tests {
             .with_consequence("fan_speed", 0.8)
             .with_consequence("cooling", 0.9);
 
-        assert_eq!(rule.condition, "high_temp");
-        assert_eq!(rule.consequences.get("fan_speed"), Some(&0.8));
-        assert_eq!(rule.consequences.get("cooling"), Some(&0.9));
+        assert_eq!(rule.condition, Antecedent::from("high_temp"));
+        assert_eq!(
+            rule.consequences.get("fan_speed"),
+            Some(&Consequent::Constant(0.8))
+        );
+        assert_eq!(
+            rule.consequences.get("cooling"),
+            Some(&Consequent::Constant(0.9))
+        );
     }
 
     #[test]
@@ -254,7 +756,10 @@ mod tests {
             .with_consequence("output", 0.5)
             .with_consequence("output", 0.8); // overwrite
 
-        assert_eq!(rule.consequences.get("output"), Some(&0.8));
+        assert_eq!(
+            rule.consequences.get("output"),
+            Some(&Consequent::Constant(0.8))
+        );
         assert_eq!(rule.consequences.len(), 1);
     }
 
@@ -290,7 +795,7 @@ mod tests {
 
         system.add_rule(rule);
         assert_eq!(system.rules.len(), 1);
-        assert_eq!(system.rules[0].condition, "low");
+        assert_eq!(system.rules[0].condition, Antecedent::from("low"));
     }
 
     #[test]
@@ -492,4 +997,572 @@ mod tests {
         let result = system.evaluate(50.0);
         assert!(result.is_empty());
     }
+
+    fn enemy_ai() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+
+        system.add_input_set(FuzzySet::new("health_low", vec![(0.0, 1.0), (50.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("near", vec![(0.0, 1.0), (10.0, 0.0)]));
+
+        system.add_rule(
+            FuzzyRule::new(
+                Antecedent::is("health", "health_low").and(Antecedent::is("distance", "near")),
+            )
+            .with_consequence("flee", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(!Antecedent::is("health", "health_low")).with_consequence("flee", 0.0),
+        );
+
+        system
+    }
+
+    #[test]
+    fn test_evaluate_inputs_and() {
+        let system = enemy_ai();
+        let inputs = HashMap::from([("health", 10.0), ("distance", 5.0)]);
+
+        let result = system.evaluate_inputs(&inputs);
+
+        // health_low = 0.8, near = 0.5 -> AND = 0.5; NOT health_low = 0.2
+        // Weighted average: (1.0 * 0.5 + 0.0 * 0.2) / 0.7
+        assert!((result.get("flee").unwrap() - 0.5 / 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_inputs_string_keys() {
+        let system = enemy_ai();
+        let inputs = HashMap::from([("health".to_string(), 0.0), ("distance".to_string(), 0.0)]);
+
+        let result = system.evaluate_inputs(&inputs);
+        assert_eq!(result.get("flee"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_evaluate_inputs_missing_input() {
+        let system = enemy_ai();
+        let inputs = HashMap::from([("health", 10.0)]);
+
+        // "distance IS near" has no value, so only the NOT rule fires
+        let result = system.evaluate_inputs(&inputs);
+        assert_eq!(result.get("flee"), Some(&0.0));
+    }
+
+    #[test]
+    fn test_evaluate_inputs_unnamed_terms_do_not_fire() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("warm", vec![(0.0, 1.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("warm").with_consequence("fan", 0.5));
+
+        let result = system.evaluate_inputs(&HashMap::from([("temperature", 5.0)]));
+        assert!(result.is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_single_value_feeds_all_inputs() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("a", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_input_set(FuzzySet::new("b", vec![(0.0, 1.0), (10.0, 0.0)]));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("x", "a").or(Antecedent::is("y", "b")))
+                .with_consequence("out", 1.0),
+        );
+
+        let result = system.evaluate(2.0);
+        assert_eq!(result.get("out"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_evaluate_inputs_custom_operators() {
+        let mut system = enemy_ai();
+        system.operators = LogicOperators::probabilistic();
+        let inputs = HashMap::from([("health", 10.0), ("distance", 5.0)]);
+
+        let result = system.evaluate_inputs(&inputs);
+
+        // AND = 0.8 * 0.5 = 0.4; NOT health_low = 0.2
+        assert!((result.get("flee").unwrap() - 0.4 / 0.6).abs() < 1e-6);
+    }
+
+    fn fan_controller(defuzzifier: Defuzzifier) -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.inference = Inference::mamdani(defuzzifier);
+
+        system.add_input_set(FuzzySet::new("cold", vec![(0.0, 1.0), (20.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("hot", vec![(10.0, 0.0), (30.0, 1.0)]));
+
+        system.add_output_set("fan", FuzzySet::new("slow", vec![(0.0, 1.0), (50.0, 0.0)]));
+        system.add_output_set(
+            "fan",
+            FuzzySet::new("fast", vec![(50.0, 0.0), (100.0, 1.0)]),
+        );
+
+        system.add_rule(FuzzyRule::new("cold").with_output_term("fan", "slow"));
+        system.add_rule(FuzzyRule::new("hot").with_output_term("fan", "fast"));
+
+        system
+    }
+
+    #[test]
+    fn test_fuzzy_rule_with_output_term() {
+        let rule: FuzzyRule<&str, &str> = FuzzyRule::new("hot").with_output_term("fan", "fast");
+        assert_eq!(
+            rule.consequences.get("fan"),
+            Some(&Consequent::Term("fast"))
+        );
+    }
+
+    #[test]
+    fn test_support() {
+        let set = FuzzySet::new("test", vec![(2.0, 0.0), (5.0, 1.0), (8.0, 0.0)]);
+        assert_eq!(set.support(), Some((2.0, 8.0)));
+        assert_eq!(FuzzySet::new("empty", vec![]).support(), None);
+    }
+
+    #[test]
+    fn test_mamdani_single_rule_centroid() {
+        let system = fan_controller(Defuzzifier::Centroid);
+
+        // Only "cold" fires, fully: centroid of the falling triangle over 0..50
+        let result = system.evaluate(0.0);
+        assert!((result.get("fan").unwrap() - 50.0 / 3.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn test_mamdani_maximum_defuzzifiers() {
+        let system = fan_controller(Defuzzifier::LargestOfMaximum);
+        assert_eq!(system.evaluate(30.0).get("fan"), Some(&100.0));
+
+        let system = fan_controller(Defuzzifier::SmallestOfMaximum);
+        assert_eq!(system.evaluate(0.0).get("fan"), Some(&0.0));
+    }
+
+    #[test]
+    fn test_mamdani_symmetric_rules() {
+        // At 15 both rules fire with 0.25, so the aggregated set is symmetric around 50
+        for defuzzifier in [
+            Defuzzifier::Centroid,
+            Defuzzifier::Bisector,
+            Defuzzifier::MeanOfMaximum,
+        ] {
+            let system = fan_controller(defuzzifier);
+            let value = system.evaluate(15.0)["fan"];
+            assert!((value - 50.0).abs() < 1.0, "{defuzzifier:?} gave {value}");
+        }
+    }
+
+    #[test]
+    fn test_mamdani_clip_and_scale() {
+        let mut system = fan_controller(Defuzzifier::MeanOfMaximum);
+        // Only keep the "hot" rule, which fires with 0.25 at 15
+        system.rules.remove(0);
+
+        // Clipping leaves a plateau from 62.5 to 100
+        let clipped = system.evaluate(15.0);
+        assert!((clipped["fan"] - 81.25).abs() < 1.0);
+
+        // Scaling keeps the peak at 100
+        system.operators.implication = Implication::Product;
+        let scaled = system.evaluate(15.0);
+        assert_eq!(scaled["fan"], 100.0);
+    }
+
+    #[test]
+    fn test_mamdani_ignores_constant_consequences() {
+        let mut system = fan_controller(Defuzzifier::Centroid);
+        system.add_rule(FuzzyRule::new("cold").with_consequence("heater", 1.0));
+
+        let result = system.evaluate(0.0);
+        assert!(result.contains_key("fan"));
+        assert!(!result.contains_key("heater"));
+    }
+
+    #[test]
+    fn test_weighted_average_ignores_output_terms() {
+        let mut system = fan_controller(Defuzzifier::Centroid);
+        system.inference = Inference::WeightedAverage;
+        system.add_rule(FuzzyRule::new("cold").with_consequence("heater", 1.0));
+
+        let result = system.evaluate(0.0);
+        assert!(!result.contains_key("fan"));
+        assert_eq!(result.get("heater"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_mamdani_missing_output_sets() {
+        let mut system = FuzzySystem::new();
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_input_set(FuzzySet::new("cold", vec![(0.0, 1.0), (20.0, 0.0)]));
+        system.add_rule(FuzzyRule::new("cold").with_output_term("fan", "slow"));
+
+        assert!(system.evaluate(0.0).is_empty());
+    }
+
+    fn speed_controller() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+
+        system.add_input_set(FuzzySet::new("near", vec![(0.0, 1.0), (10.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("far", vec![(0.0, 0.0), (10.0, 1.0)]));
+
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("distance", "near")).with_linear_consequence(
+                "speed",
+                LinearConsequent::new(2.0)
+                    .with_coefficient("distance", 0.3)
+                    .with_coefficient("health", 0.1),
+            ),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("distance", "far")).with_consequence("speed", 10.0),
+        );
+
+        system
+    }
+
+    #[test]
+    fn test_fuzzy_rule_with_linear_consequence() {
+        let function = LinearConsequent::new(2.0).with_coefficient("distance", 0.3);
+        let rule: FuzzyRule<&str, &str> =
+            FuzzyRule::new("near").with_linear_consequence("speed", function.clone());
+        assert_eq!(
+            rule.consequences.get("speed"),
+            Some(&Consequent::Linear(function))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_linear_consequence() {
+        let system = speed_controller();
+
+        // Only "near" fires: 2.0 + 0.3 * 0.0 + 0.1 * 20.0
+        let result = system.evaluate_inputs(&HashMap::from([("distance", 0.0), ("health", 20.0)]));
+        assert!((result["speed"] - 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_linear_mixed_with_constant() {
+        let system = speed_controller();
+
+        // near = far = 0.5: (0.5 * (2.0 + 1.5 + 2.0) + 0.5 * 10.0) / 1.0
+        let result = system.evaluate_inputs(&HashMap::from([("distance", 5.0), ("health", 20.0)]));
+        assert!((result["speed"] - 7.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_linear_varies_inside_rule() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("any", vec![(0.0, 1.0), (10.0, 1.0)]));
+        system.add_rule(
+            FuzzyRule::new("any").with_linear_consequence(
+                "out",
+                LinearConsequent::new(1.0).with_coefficient("x", 2.0),
+            ),
+        );
+
+        // A constant consequence would plateau, a linear one follows the input
+        assert_eq!(system.evaluate(1.0)["out"], 3.0);
+        assert_eq!(system.evaluate(4.0)["out"], 9.0);
+    }
+
+    #[test]
+    fn test_evaluate_linear_missing_input_is_skipped() {
+        let system = speed_controller();
+
+        // "health" is missing, so only the "far" rule contributes
+        let result = system.evaluate_inputs(&HashMap::from([("distance", 5.0)]));
+        assert_eq!(result.get("speed"), Some(&10.0));
+    }
+
+    fn thermostat() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+
+        system.add_input_variable(
+            FuzzyVariable::new("temperature", 0.0, 50.0)
+                .with_term(FuzzySet::new("cold", vec![(0.0, 1.0), (20.0, 0.0)]))
+                .with_term(FuzzySet::new("hot", vec![(30.0, 0.0), (50.0, 1.0)])),
+        );
+        system.add_input_variable(
+            FuzzyVariable::new("humidity", 0.0, 100.0)
+                .with_out_of_range(OutOfRange::Ignore)
+                .with_term(FuzzySet::new("cold", vec![(0.0, 0.0), (100.0, 1.0)])),
+        );
+
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("temperature", "cold")).with_consequence("heater", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("temperature", "hot")).with_consequence("heater", 0.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("humidity", "cold")).with_consequence("dryer", 1.0),
+        );
+
+        system
+    }
+
+    #[test]
+    fn test_variable_lookups() {
+        let system = thermostat();
+
+        assert_eq!(system.input_variables.len(), 2);
+        assert!(system.input_variable("temperature").is_some());
+        assert!(system.input_variable("pressure").is_none());
+
+        // The same category resolves to different sets per variable
+        let temperature_cold = system.term(Some("temperature"), &"cold").unwrap();
+        let humidity_cold = system.term(Some("humidity"), &"cold").unwrap();
+        assert_eq!(temperature_cold.membership(0.0), 1.0);
+        assert_eq!(humidity_cold.membership(0.0), 0.0);
+        assert!(system.term(Some("temperature"), &"warm").is_none());
+    }
+
+    #[test]
+    fn test_term_falls_back_to_input_sets() {
+        let mut system = thermostat();
+        system.add_input_set(FuzzySet::new("warm", vec![(0.0, 1.0)]));
+
+        assert!(system.term(None, &"warm").is_some());
+        assert!(system.term(Some("unknown"), &"warm").is_some());
+        assert!(system.term(None, &"cold").is_none());
+    }
+
+    #[test]
+    fn test_evaluate_with_variables() {
+        let system = thermostat();
+        let result =
+            system.evaluate_inputs(&HashMap::from([("temperature", 10.0), ("humidity", 50.0)]));
+
+        assert_eq!(result.get("heater"), Some(&1.0));
+        assert_eq!(result.get("dryer"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_evaluate_clamps_input() {
+        let mut system = thermostat();
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("temperature", "hot")).with_linear_consequence(
+                "fan",
+                LinearConsequent::new(0.0).with_coefficient("temperature", 1.0),
+            ),
+        );
+
+        // 80 is clamped to 50, for terms and linear consequences alike
+        let result = system.evaluate_inputs(&HashMap::from([("temperature", 80.0)]));
+        assert_eq!(result.get("heater"), Some(&0.0));
+        assert_eq!(result.get("fan"), Some(&50.0));
+    }
+
+    #[test]
+    fn test_evaluate_ignores_out_of_range_input() {
+        let system = thermostat();
+        let result = system.evaluate_inputs(&HashMap::from([("humidity", 150.0)]));
+        assert!(!result.contains_key("dryer"));
+    }
+
+    #[test]
+    fn test_add_output_set_creates_variable() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_output_set("fan", FuzzySet::new("slow", vec![(0.0, 1.0), (50.0, 0.0)]));
+        system.add_output_set(
+            "fan",
+            FuzzySet::new("fast", vec![(50.0, 0.0), (100.0, 1.0)]),
+        );
+
+        let fan = system.output_variable(&"fan").unwrap();
+        assert_eq!((fan.min, fan.max), (0.0, 100.0));
+        assert_eq!(fan.terms.len(), 2);
+        assert!(system.output_variable(&"heater").is_none());
+    }
+
+    #[test]
+    fn test_mamdani_uses_output_universe() {
+        let mut system = FuzzySystem::new();
+        system.inference = Inference::mamdani(Defuzzifier::LargestOfMaximum);
+        system.add_input_set(FuzzySet::new("any", vec![(0.0, 1.0)]));
+        system.add_output_variable(
+            "fan",
+            FuzzyVariable::new("fan speed", 0.0, 200.0)
+                .with_term(FuzzySet::new("fast", vec![(50.0, 0.0), (100.0, 1.0)])),
+        );
+        system.add_rule(FuzzyRule::new("any").with_output_term("fan", "fast"));
+
+        // "fast" saturates at 1 up to the end of the universe
+        assert_eq!(system.evaluate(0.0).get("fan"), Some(&200.0));
+    }
+
+    #[test]
+    fn test_parametric_set_constructors() {
+        assert_eq!(
+            FuzzySet::triangle("t", 0.0, 5.0, 10.0).shape,
+            Shape::Triangle {
+                left: 0.0,
+                peak: 5.0,
+                right: 10.0
+            }
+        );
+        assert_eq!(
+            FuzzySet::trapezoid("t", 0.0, 2.0, 8.0, 10.0).membership(1.0),
+            0.5
+        );
+        assert_eq!(FuzzySet::gaussian("g", 5.0, 1.0).membership(5.0), 1.0);
+        assert_eq!(FuzzySet::bell("b", 2.0, 2.0, 5.0).membership(7.0), 0.5);
+        assert_eq!(FuzzySet::sigmoid("s", 1.0, 5.0).membership(5.0), 0.5);
+        assert_eq!(FuzzySet::singleton("s", 5.0).membership(5.0), 1.0);
+        assert_eq!(
+            FuzzySet::custom("c", |x: f32| x / 10.0).membership(5.0),
+            0.5
+        );
+    }
+
+    #[test]
+    fn test_evaluate_with_smooth_sets() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::sigmoid("low", -1.0, 5.0));
+        system.add_input_set(FuzzySet::sigmoid("high", 1.0, 5.0));
+        system.add_rule(FuzzyRule::new("low").with_consequence("out", 0.0));
+        system.add_rule(FuzzyRule::new("high").with_consequence("out", 1.0));
+
+        // Complementary sigmoids make the output follow the "high" curve exactly
+        for x in [0.0, 2.5, 5.0, 7.5, 10.0] {
+            let expected = 1.0 / (1.0 + f32::exp(-(x - 5.0)));
+            assert!((system.evaluate(x)["out"] - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_mamdani_with_gaussian_output() {
+        let mut system = FuzzySystem::new();
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_input_set(FuzzySet::new("any", vec![(0.0, 1.0)]));
+        system.add_output_set("speed", FuzzySet::gaussian("medium", 5.0, 1.0));
+        system.add_rule(FuzzyRule::new("any").with_output_term("speed", "medium"));
+
+        // The output universe follows the support of the gaussian
+        let speed = system.output_variable(&"speed").unwrap();
+        assert!(speed.min < 2.0 && speed.max > 8.0);
+        assert!((system.evaluate(0.0)["speed"] - 5.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_hedged_membership() {
+        let set = FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]);
+
+        assert_eq!(set.hedged_membership(5.0, &[]), 0.5);
+        assert_eq!(set.hedged_membership(5.0, &[Hedge::Very]), 0.25);
+        assert_eq!(set.hedged_membership(5.0, &[Hedge::Not, Hedge::Very]), 0.75);
+    }
+
+    #[test]
+    fn test_evaluate_hedged_rules() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("enemy_count", "high").very())
+                .with_consequence("retreat", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("enemy_count", "high").hedged(Hedge::Not))
+                .with_consequence("retreat", 0.0),
+        );
+
+        // very high = 0.25, not high = 0.5
+        let result = system.evaluate(5.0);
+        assert!((result["retreat"] - 0.25 / 0.75).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fuzzy_rule_weight_is_clamped() {
+        let rule: FuzzyRule<&str, &str> = FuzzyRule::new("hot");
+        assert_eq!((rule.weight, rule.enabled), (1.0, true));
+        assert_eq!(rule.clone().with_weight(1.5).weight, 1.0);
+        assert_eq!(rule.with_weight(-0.5).weight, 0.0);
+    }
+
+    #[test]
+    fn test_evaluate_rule_weights() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(
+            FuzzyRule::new("high")
+                .with_consequence("retreat", 1.0)
+                .with_weight(0.25),
+        );
+        system.add_rule(FuzzyRule::new(!Antecedent::from("high")).with_consequence("retreat", 0.0));
+
+        // 0.5 * 0.25 against 0.5
+        let result = system.evaluate(5.0);
+        assert!((result["retreat"] - 0.125 / 0.625).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_disabled_rules() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("high").with_consequence("retreat", 1.0));
+        system.add_rule(FuzzyRule::new(!Antecedent::from("high")).with_consequence("retreat", 0.0));
+
+        system.rules[1].enabled = false;
+        assert_eq!(system.evaluate(5.0)["retreat"], 1.0);
+
+        system.rules[0].enabled = false;
+        assert!(system.evaluate(5.0).is_empty());
+
+        system.rules[1].enabled = true;
+        assert_eq!(system.evaluate(5.0)["retreat"], 0.0);
+    }
+
+    #[test]
+    fn test_rule_blocks_use_their_operators() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_input_set(FuzzySet::new("low", vec![(0.0, 1.0), (10.0, 0.0)]));
+        let block = system.add_rule_block(RuleBlock::new(
+            "probabilistic",
+            LogicOperators::probabilistic(),
+        ));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::from("high").and(Antecedent::from("low")))
+                .with_consequence("min", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::from("high").and(Antecedent::from("low")))
+                .with_consequence("product", 1.0)
+                .in_block(block),
+        );
+
+        let trace = system.evaluate_traced(5.0);
+        assert_eq!(trace.rules[0].strength, 0.5);
+        assert_eq!(trace.rules[1].strength, 0.25);
+        assert_eq!(system.rule_operators(&system.rules[1]).and, TNorm::Product);
+    }
+
+    #[test]
+    fn test_mamdani_aggregation_per_block() {
+        let mut system = FuzzySystem::new();
+        system.inference = Inference::mamdani(Defuzzifier::LargestOfMaximum);
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_output_set(
+            "speed",
+            FuzzySet::new("slow", vec![(0.0, 1.0), (10.0, 0.0)]),
+        );
+        system.add_output_set(
+            "speed",
+            FuzzySet::new("fast", vec![(0.0, 0.0), (10.0, 1.0)]),
+        );
+        system.add_rule(FuzzyRule::new("high").with_output_term("speed", "slow"));
+        system.add_rule(FuzzyRule::new("high").with_output_term("speed", "fast"));
+
+        // Clipped at 0.5, max aggregation is flat at 0.5 across the universe
+        assert_eq!(system.evaluate(5.0)["speed"], 10.0);
+
+        // A bounded sum of the clipped sets peaks at 1 where both are 0.5
+        let block = system.add_rule_block(RuleBlock::new(
+            "sum",
+            LogicOperators::default().with_aggregation(SNorm::BoundedSum),
+        ));
+        for rule in &mut system.rules {
+            rule.block = Some(block);
+        }
+        assert!((system.evaluate(5.0)["speed"] - 5.0).abs() < 1e-4);
+    }
 }
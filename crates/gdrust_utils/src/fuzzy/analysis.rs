@@ -0,0 +1,581 @@
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use super::{inference, sorted_by_output, Antecedent, FuzzyRule, FuzzySystem, Inference, SNorm};
+
+/// Number of samples across each input universe when looking for rules that never fire
+const RESOLUTION: usize = 101;
+/// Upper bound for the samples of the grid over the inputs a single rule reads
+const MAX_SAMPLES: usize = 100_000;
+
+/// Why a rule never fires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unreachable {
+    Disabled,
+    ZeroWeight,
+    /// The condition is false across the universes of the inputs it reads, like a
+    /// contradiction or a term without a set
+    NeverTrue,
+}
+
+/// A redundancy or inconsistency found by [`FuzzySystem::analyze_rules`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum RuleIssue<OutputType> {
+    /// The rule at index `rule` repeats the condition, consequences and rule block of the
+    /// earlier rule `original`
+    Duplicate { rule: usize, original: usize },
+    /// The rules at index `first` and `second` have the same condition, but different
+    /// consequences for `output`
+    Conflict {
+        first: usize,
+        second: usize,
+        output: OutputType,
+    },
+    /// The rule at index `rule` has the consequences of the more general rule `by` and
+    /// never fires stronger, because its condition adds terms to the condition of `by` (or
+    /// to one alternative of it)
+    Subsumed { rule: usize, by: usize },
+    /// The rule at index `rule` never fires
+    Unreachable { rule: usize, reason: Unreachable },
+}
+
+impl<OutputType: Debug> Display for RuleIssue<OutputType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleIssue::Duplicate { rule, original } => {
+                write!(f, "rule {rule}: duplicate of rule {original}")
+            }
+            RuleIssue::Conflict {
+                first,
+                second,
+                output,
+            } => write!(
+                f,
+                "rules {first} and {second}: same condition, different {output:?}"
+            ),
+            RuleIssue::Subsumed { rule, by } => {
+                write!(f, "rule {rule}: subsumed by rule {by}")
+            }
+            RuleIssue::Unreachable { rule, reason } => match reason {
+                Unreachable::Disabled => write!(f, "rule {rule}: disabled"),
+                Unreachable::ZeroWeight => write!(f, "rule {rule}: weight is 0"),
+                Unreachable::NeverTrue => write!(f, "rule {rule}: condition is never true"),
+            },
+        }
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    /// Finds rules that never fire, duplicates, conflicts and subsumed rules. Conditions are
+    /// compared structurally, regardless of the order of `AND` and `OR` operands. Rules
+    /// that never fire are left out of the other checks.
+    pub fn analyze_rules(&self) -> Vec<RuleIssue<OutputType>> {
+        let mut issues = Vec::new();
+        let mut reachable = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            match self.unreachable(rule) {
+                Some(reason) => issues.push(RuleIssue::Unreachable {
+                    rule: index,
+                    reason,
+                }),
+                None => reachable.push((index, rule)),
+            }
+        }
+
+        for (position, &(second, rule)) in reachable.iter().enumerate() {
+            for &(first, earlier) in &reachable[..position] {
+                if equivalent(&earlier.condition, &rule.condition) {
+                    if earlier.consequences == rule.consequences && earlier.block == rule.block {
+                        issues.push(RuleIssue::Duplicate {
+                            rule: second,
+                            original: first,
+                        });
+                        continue;
+                    }
+                    for (output, consequent) in sorted_by_output(&rule.consequences) {
+                        if earlier
+                            .consequences
+                            .get(output)
+                            .is_some_and(|other| other != consequent)
+                        {
+                            issues.push(RuleIssue::Conflict {
+                                first,
+                                second,
+                                output: output.clone(),
+                            });
+                        }
+                    }
+                    continue;
+                }
+                if subsumes(earlier, rule) {
+                    issues.push(RuleIssue::Subsumed {
+                        rule: second,
+                        by: first,
+                    });
+                } else if subsumes(rule, earlier) {
+                    issues.push(RuleIssue::Subsumed {
+                        rule: first,
+                        by: second,
+                    });
+                }
+            }
+        }
+        issues
+    }
+
+    /// A copy without the rules that never fire, and with duplicate rules merged into the
+    /// first one where that leaves the outputs unchanged: under weighted average inference
+    /// the weights are summed as long as the sum stays at most 1, and under Mamdani inference
+    /// with [`SNorm::Max`] aggregation the largest weight is kept. Other duplicates, and
+    /// subsumed and conflicting rules, are kept.
+    pub fn compacted(&self) -> Self
+    where
+        Self: Clone,
+    {
+        let mut compacted = self.clone();
+        let mut removed = vec![false; self.rules.len()];
+        for issue in self.analyze_rules() {
+            match issue {
+                RuleIssue::Unreachable { rule, .. } => removed[rule] = true,
+                // A rule merged into one original is not merged into another
+                RuleIssue::Duplicate { rule, original } if !removed[rule] && !removed[original] => {
+                    let merged = compacted.rules[original].weight;
+                    let weight = self.rules[rule].weight;
+                    let merged = match self.inference {
+                        // The strengths add up in the average, and so do the weights
+                        Inference::WeightedAverage => {
+                            Some(merged + weight).filter(|sum| *sum <= 1.0)
+                        }
+                        // The implied sets of equal conditions are nested, so the max keeps
+                        // the one with the largest weight
+                        Inference::Mamdani { .. } => matches!(
+                            self.rule_operators(&self.rules[rule]).aggregation,
+                            SNorm::Max
+                        )
+                        .then(|| merged.max(weight)),
+                    };
+                    if let Some(merged) = merged {
+                        removed[rule] = true;
+                        compacted.rules[original].weight = merged;
+                    }
+                }
+                _ => {}
+            }
+        }
+        let mut index = 0;
+        compacted.rules.retain(|_| {
+            index += 1;
+            !removed[index - 1]
+        });
+        compacted
+    }
+
+    fn unreachable(&self, rule: &FuzzyRule<InputType, OutputType>) -> Option<Unreachable> {
+        if !rule.enabled {
+            Some(Unreachable::Disabled)
+        } else if rule.weight <= 0.0 {
+            Some(Unreachable::ZeroWeight)
+        } else if self.never_true(rule) {
+            Some(Unreachable::NeverTrue)
+        } else {
+            None
+        }
+    }
+
+    /// Samples the condition on a grid over the inputs it reads
+    fn never_true(&self, rule: &FuzzyRule<InputType, OutputType>) -> bool {
+        let terms = rule.condition.terms();
+        let mut inputs: Vec<Option<&str>> = Vec::new();
+        for (input, _) in &terms {
+            if !inputs.contains(input) {
+                inputs.push(*input);
+            }
+        }
+
+        let mut resolution = RESOLUTION;
+        while resolution > 2 && resolution.saturating_pow(inputs.len() as u32) > MAX_SAMPLES {
+            resolution -= 1;
+        }
+        let axes: Vec<Vec<f32>> = inputs
+            .iter()
+            .map(|input| {
+                let sets: Vec<_> = terms
+                    .iter()
+                    .filter(|(name, _)| name == input)
+                    .filter_map(|(name, category)| self.term(*name, category))
+                    .collect();
+                let range = match input.and_then(|name| self.input_variable(name)) {
+                    Some(variable) => Some((variable.min, variable.max)),
+                    None => sets
+                        .iter()
+                        .filter_map(|set| set.support())
+                        .reduce(|(a, b), (c, d)| (a.min(c), b.max(d))),
+                };
+                let Some((min, max)) = range else {
+                    return vec![0.0];
+                };
+                // Corners catch singletons and sharp peaks between the samples
+                let corners = sets.iter().flat_map(|set| set.shape.corners());
+                let mut positions: Vec<f32> =
+                    inference::sample_positions(min, max, resolution).collect();
+                positions.extend(corners.filter(|x| (min..=max).contains(x)));
+                positions
+            })
+            .collect();
+
+        let operators = self.rule_operators(rule);
+        let mut indices = vec![0; axes.len()];
+        loop {
+            let truth = rule.condition.evaluate(operators, &mut |name, category| {
+                let axis = inputs.iter().position(|input| *input == name).unwrap_or(0);
+                let value = axes.get(axis).map(|positions| positions[indices[axis]]);
+                self.term_membership(name, self.resolve_input(name, value), category)
+            });
+            if truth > 0.0 {
+                return false;
+            }
+
+            // Advances to the next grid sample, like an odometer
+            let mut axis = 0;
+            loop {
+                let Some(index) = indices.get_mut(axis) else {
+                    return true;
+                };
+                *index += 1;
+                if *index < axes[axis].len() {
+                    break;
+                }
+                *index = 0;
+                axis += 1;
+            }
+        }
+    }
+}
+
+/// Whether `specific` fires only where `general` does, and not stronger, with the same
+/// effect. Holds because t-norms never exceed their operands and s-norms never fall below.
+fn subsumes<InputType, OutputType>(
+    general: &FuzzyRule<InputType, OutputType>,
+    specific: &FuzzyRule<InputType, OutputType>,
+) -> bool
+where
+    InputType: PartialEq,
+    OutputType: Eq + Hash,
+{
+    if general.consequences != specific.consequences
+        || general.block != specific.block
+        || specific.weight > general.weight
+    {
+        return false;
+    }
+    let terms = conjuncts(&specific.condition);
+    disjuncts(&general.condition)
+        .into_iter()
+        .any(|alternative| {
+            conjuncts(alternative)
+                .iter()
+                .all(|term| terms.iter().any(|other| equivalent(term, other)))
+        })
+}
+
+fn conjuncts<InputType>(antecedent: &Antecedent<InputType>) -> Vec<&Antecedent<InputType>> {
+    match antecedent {
+        Antecedent::And(terms) => terms.iter().collect(),
+        antecedent => vec![antecedent],
+    }
+}
+
+fn disjuncts<InputType>(antecedent: &Antecedent<InputType>) -> Vec<&Antecedent<InputType>> {
+    match antecedent {
+        Antecedent::Or(terms) => terms.iter().collect(),
+        antecedent => vec![antecedent],
+    }
+}
+
+/// Structural equality, regardless of the order of `AND` and `OR` operands
+fn equivalent<InputType: PartialEq>(a: &Antecedent<InputType>, b: &Antecedent<InputType>) -> bool {
+    match (a, b) {
+        (Antecedent::And(a), Antecedent::And(b)) | (Antecedent::Or(a), Antecedent::Or(b)) => {
+            let mut unmatched: Vec<&Antecedent<InputType>> = b.iter().collect();
+            a.len() == b.len()
+                && a.iter().all(|term| {
+                    match unmatched.iter().position(|other| equivalent(term, other)) {
+                        Some(index) => {
+                            unmatched.swap_remove(index);
+                            true
+                        }
+                        None => false,
+                    }
+                })
+        }
+        (Antecedent::Not(a), Antecedent::Not(b)) => equivalent(a, b),
+        (Antecedent::Hedged(hedge, a), Antecedent::Hedged(other, b)) => {
+            hedge == other && equivalent(a, b)
+        }
+        (a, b) => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    use crate::fuzzy::{Consequent, FuzzySet, FuzzyVariable, LogicOperators, RuleBlock};
+
+    fn is(input: &str, category: &'static str) -> Antecedent<&'static str> {
+        Antecedent::is(input, category)
+    }
+
+    fn guard() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (40.0, 0.0)]))
+                .with_term(FuzzySet::new("high", vec![(60.0, 0.0), (100.0, 1.0)]))
+                .with_term(FuzzySet::singleton("full", 100.0)),
+        );
+        system.add_input_variable(
+            FuzzyVariable::new("distance", 0.0, 50.0)
+                .with_term(FuzzySet::new("near", vec![(0.0, 1.0), (20.0, 0.0)])),
+        );
+        system
+    }
+
+    #[test]
+    fn test_duplicates() {
+        let mut system = guard();
+        system.add_rule(
+            FuzzyRule::new(is("health", "low").and(is("distance", "near")))
+                .with_consequence("flee", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(is("distance", "near").and(is("health", "low")))
+                .with_consequence("flee", 1.0)
+                .with_weight(0.5),
+        );
+        assert_eq!(
+            system.analyze_rules(),
+            vec![RuleIssue::Duplicate {
+                rule: 1,
+                original: 0
+            }]
+        );
+
+        // Rules of different blocks are combined with different operators
+        let block = system.add_rule_block(RuleBlock::new("b", LogicOperators::probabilistic()));
+        system.rules[1].block = Some(block);
+        assert_eq!(system.analyze_rules(), vec![]);
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let mut system = guard();
+        system.add_rule(
+            FuzzyRule::new(is("health", "low"))
+                .with_consequence("shout", 1.0)
+                .with_consequence("flee", 1.0)
+                .with_consequence("aim", 1.0)
+                .with_consequence("hide", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(is("health", "low"))
+                .with_consequence("shout", 0.0)
+                .with_consequence("flee", 0.0)
+                .with_consequence("aim", 0.0)
+                .with_consequence("hide", 1.0),
+        );
+        let conflict = |output| RuleIssue::Conflict {
+            first: 0,
+            second: 1,
+            output,
+        };
+        assert_eq!(
+            system.analyze_rules(),
+            vec![conflict("aim"), conflict("flee"), conflict("shout")]
+        );
+    }
+
+    #[test]
+    fn test_subsumed() {
+        let mut system = guard();
+        system.add_rule(
+            FuzzyRule::new(is("health", "low").and(is("distance", "near")))
+                .with_consequence("flee", 1.0),
+        );
+        system.add_rule(FuzzyRule::new(is("health", "low")).with_consequence("flee", 1.0));
+        system.add_rule(
+            FuzzyRule::new(is("distance", "near").or(is("health", "high")))
+                .with_consequence("flee", 1.0),
+        );
+        assert_eq!(
+            system.analyze_rules(),
+            vec![
+                RuleIssue::Subsumed { rule: 0, by: 1 },
+                RuleIssue::Subsumed { rule: 0, by: 2 },
+            ]
+        );
+
+        // A stronger weight can make the specific rule fire stronger
+        system.rules[0].weight = 1.0;
+        system.rules[1].weight = 0.5;
+        system.rules.truncate(2);
+        assert_eq!(system.analyze_rules(), vec![]);
+    }
+
+    #[test]
+    fn test_unreachable() {
+        let mut system = guard();
+        system.add_rule(FuzzyRule::new(is("health", "low")).with_consequence("a", 1.0));
+        system.add_rule(
+            FuzzyRule::new(is("health", "low"))
+                .with_consequence("b", 1.0)
+                .with_enabled(false),
+        );
+        system.add_rule(
+            FuzzyRule::new(is("health", "low"))
+                .with_consequence("c", 1.0)
+                .with_weight(0.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(is("health", "low").and(is("health", "high")))
+                .with_consequence("d", 1.0),
+        );
+        system.add_rule(FuzzyRule::new(is("health", "dead")).with_consequence("e", 1.0));
+        system.add_rule(FuzzyRule::new(is("health", "full")).with_consequence("f", 1.0));
+        system.add_rule(FuzzyRule::new(!is("health", "dead")).with_consequence("g", 1.0));
+
+        let unreachable = |rule, reason| RuleIssue::Unreachable { rule, reason };
+        assert_eq!(
+            system.analyze_rules(),
+            vec![
+                unreachable(1, Unreachable::Disabled),
+                unreachable(2, Unreachable::ZeroWeight),
+                unreachable(3, Unreachable::NeverTrue),
+                unreachable(4, Unreachable::NeverTrue),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_compacted() {
+        let mut system = guard();
+        system.add_rule(FuzzyRule::new(is("health", "low")).with_consequence("flee", 1.0));
+        system.add_rule(
+            FuzzyRule::new(is("health", "low"))
+                .with_consequence("flee", 0.5)
+                .with_enabled(false),
+        );
+        system.add_rule(
+            FuzzyRule::new(is("health", "low"))
+                .with_consequence("flee", 1.0)
+                .with_weight(0.5),
+        );
+        system.add_rule(
+            FuzzyRule::new(is("health", "low"))
+                .with_consequence("flee", 1.0)
+                .with_weight(0.4),
+        );
+        system.rules[0].weight = 0.3;
+        system.add_rule(FuzzyRule::new(is("health", "high")).with_consequence("flee", 0.0));
+
+        // The last duplicate would push the summed weight past 1
+        let compacted = system.compacted();
+        assert_eq!(compacted.rules.len(), 3);
+        assert_eq!(compacted.rules[0].weight, 0.8);
+        assert_eq!(compacted.rules[1].weight, 0.4);
+        assert_eq!(system.rules.len(), 5);
+    }
+
+    #[test]
+    fn test_compacted_merges_each_duplicate_once() {
+        let mut system = guard();
+        for weight in [0.5, 0.6, 0.4] {
+            system.add_rule(
+                FuzzyRule::new(is("health", "low"))
+                    .with_consequence("flee", 0.0)
+                    .with_weight(weight),
+            );
+        }
+        system.add_rule(FuzzyRule::new(is("health", "high")).with_consequence("flee", 1.0));
+
+        // The first pair can't merge, so the third rule merges into the first only
+        let compacted = system.compacted();
+        let weights: Vec<f32> = compacted.rules.iter().map(|rule| rule.weight).collect();
+        assert_eq!(weights, [0.9, 0.6, 1.0]);
+        assert_same_outputs(&system);
+    }
+
+    fn assert_same_outputs(system: &FuzzySystem<&'static str, &'static str>) {
+        let compacted = system.compacted();
+        for health in [0.0, 5.0, 20.0, 35.0, 50.0, 70.0, 100.0] {
+            let inputs = HashMap::from([("health", health), ("distance", 10.0)]);
+            let expected = system.evaluate_inputs(&inputs);
+            let actual = compacted.evaluate_inputs(&inputs);
+            for (output, value) in &expected {
+                assert!(
+                    (actual[output] - value).abs() < 1e-5,
+                    "{health}: {actual:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_compacted_keeps_outputs() {
+        let mut system = guard();
+        system.add_rule(FuzzyRule::new(is("health", "low")).with_consequence("flee", 1.0));
+        system.add_rule(FuzzyRule::new(is("health", "low")).with_consequence("flee", 1.0));
+        system.add_rule(FuzzyRule::new(is("health", "high")).with_consequence("flee", 0.0));
+        assert_eq!(system.compacted().rules.len(), 3);
+        assert_same_outputs(&system);
+
+        system.rules[0].weight = 0.25;
+        system.rules[1].weight = 0.5;
+        assert_eq!(system.compacted().rules.len(), 2);
+        assert_same_outputs(&system);
+
+        system.add_output_variable(
+            "flee",
+            FuzzyVariable::new("flee", 0.0, 1.0)
+                .with_term(FuzzySet::new("no", vec![(0.0, 1.0), (0.6, 0.0)]))
+                .with_term(FuzzySet::new("yes", vec![(0.4, 0.0), (1.0, 1.0)])),
+        );
+        for (rule, category) in system.rules.iter_mut().zip(["yes", "yes", "no"]) {
+            rule.consequences.insert("flee", Consequent::Term(category));
+        }
+        system.inference = Inference::Mamdani {
+            defuzzifier: Default::default(),
+            resolution: 101,
+        };
+        assert_eq!(system.compacted().rules.len(), 2);
+        assert_eq!(system.compacted().rules[0].weight, 0.5);
+        assert_same_outputs(&system);
+
+        system.operators = LogicOperators::default().with_aggregation(SNorm::BoundedSum);
+        assert_eq!(system.compacted().rules.len(), 3);
+        assert_same_outputs(&system);
+    }
+
+    #[test]
+    fn test_display() {
+        let issue: RuleIssue<&str> = RuleIssue::Conflict {
+            first: 0,
+            second: 3,
+            output: "flee",
+        };
+        assert_eq!(
+            issue.to_string(),
+            "rules 0 and 3: same condition, different \"flee\""
+        );
+        let issue: RuleIssue<&str> = RuleIssue::Unreachable {
+            rule: 2,
+            reason: Unreachable::NeverTrue,
+        };
+        assert_eq!(issue.to_string(), "rule 2: condition is never true");
+    }
+}
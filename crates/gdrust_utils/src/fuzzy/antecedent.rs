@@ -0,0 +1,242 @@
+use std::ops::Not;
+
+use super::{Hedge, LogicOperators};
+
+/// The `IF` part of a [`FuzzyRule`](super::FuzzyRule), an expression tree over input terms
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Antecedent<InputType> {
+    /// `input IS category`. Terms without an input name read the single crisp value passed
+    /// to [`FuzzySystem::evaluate`](super::FuzzySystem::evaluate)
+    Is {
+        input: Option<String>,
+        category: InputType,
+    },
+    And(Vec<Antecedent<InputType>>),
+    Or(Vec<Antecedent<InputType>>),
+    Not(Box<Antecedent<InputType>>),
+    /// Applies a [`Hedge`] to the truth degree of the inner expression, as in
+    /// `input IS very category`
+    Hedged(Hedge, Box<Antecedent<InputType>>),
+}
+
+impl<InputType> From<InputType> for Antecedent<InputType> {
+    fn from(category: InputType) -> Self {
+        Antecedent::Is {
+            input: None,
+            category,
+        }
+    }
+}
+
+impl<InputType> Antecedent<InputType> {
+    /// `input IS category` for a named input variable
+    pub fn is(input: impl Into<String>, category: InputType) -> Self {
+        Antecedent::Is {
+            input: Some(input.into()),
+            category,
+        }
+    }
+
+    pub fn and(self, other: impl Into<Self>) -> Self {
+        match self {
+            Antecedent::And(mut terms) => {
+                terms.push(other.into());
+                Antecedent::And(terms)
+            }
+            term => Antecedent::And(vec![term, other.into()]),
+        }
+    }
+
+    pub fn or(self, other: impl Into<Self>) -> Self {
+        match self {
+            Antecedent::Or(mut terms) => {
+                terms.push(other.into());
+                Antecedent::Or(terms)
+            }
+            term => Antecedent::Or(vec![term, other.into()]),
+        }
+    }
+
+    pub fn hedged(self, hedge: Hedge) -> Self {
+        Antecedent::Hedged(hedge, Box::new(self))
+    }
+
+    pub fn very(self) -> Self {
+        self.hedged(Hedge::Very)
+    }
+
+    pub fn extremely(self) -> Self {
+        self.hedged(Hedge::Extremely)
+    }
+
+    pub fn somewhat(self) -> Self {
+        self.hedged(Hedge::Somewhat)
+    }
+
+    /// All `input IS category` leaves of the expression, from left to right
+    pub fn terms(&self) -> Vec<(Option<&str>, &InputType)> {
+        match self {
+            Antecedent::Is { input, category } => vec![(input.as_deref(), category)],
+            Antecedent::And(terms) | Antecedent::Or(terms) => {
+                terms.iter().flat_map(Antecedent::terms).collect()
+            }
+            Antecedent::Not(inner) | Antecedent::Hedged(_, inner) => inner.terms(),
+        }
+    }
+
+    /// Computes the truth degree of the expression. `term` returns the membership degree of
+    /// a single `input IS category` leaf.
+    pub fn evaluate<F>(&self, operators: &LogicOperators, term: &mut F) -> f32
+    where
+        F: FnMut(Option<&str>, &InputType) -> f32,
+    {
+        match self {
+            Antecedent::Is { input, category } => term(input.as_deref(), category),
+            // Empty conjunctions are true and empty disjunctions are false
+            Antecedent::And(terms) => terms.iter().fold(1.0, |acc, t| {
+                operators.and.apply(acc, t.evaluate(operators, term))
+            }),
+            Antecedent::Or(terms) => terms.iter().fold(0.0, |acc, t| {
+                operators.or.apply(acc, t.evaluate(operators, term))
+            }),
+            Antecedent::Not(inner) => operators.not.apply(inner.evaluate(operators, term)),
+            Antecedent::Hedged(hedge, inner) => hedge.apply(inner.evaluate(operators, term)),
+        }
+    }
+}
+
+impl<InputType> Not for Antecedent<InputType> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Antecedent::Not(Box::new(self))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{SNorm, TNorm};
+
+    fn lookup(input: Option<&str>, category: &&str) -> f32 {
+        match (input, *category) {
+            (Some("health"), "low") => 0.8,
+            (Some("distance"), "near") => 0.3,
+            (None, "warm") => 0.5,
+            _ => 0.0,
+        }
+    }
+
+    #[test]
+    fn test_from_category_is_unnamed() {
+        let antecedent = Antecedent::from("warm");
+        assert_eq!(
+            antecedent,
+            Antecedent::Is {
+                input: None,
+                category: "warm"
+            }
+        );
+    }
+
+    #[test]
+    fn test_and_or_flatten() {
+        let a = Antecedent::is("health", "low")
+            .and(Antecedent::is("distance", "near"))
+            .and(Antecedent::is("ammo", "empty"));
+        match a {
+            Antecedent::And(terms) => assert_eq!(terms.len(), 3),
+            _ => panic!("expected AND"),
+        }
+
+        let o = Antecedent::is("health", "low")
+            .or(Antecedent::is("distance", "near"))
+            .or("warm");
+        match o {
+            Antecedent::Or(terms) => assert_eq!(terms.len(), 3),
+            _ => panic!("expected OR"),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_zadeh() {
+        let ops = LogicOperators::default();
+        let and = Antecedent::is("health", "low").and(Antecedent::is("distance", "near"));
+        let or = Antecedent::is("health", "low").or(Antecedent::is("distance", "near"));
+        let not = !Antecedent::is("health", "low");
+
+        assert_eq!(and.evaluate(&ops, &mut lookup), 0.3);
+        assert_eq!(or.evaluate(&ops, &mut lookup), 0.8);
+        assert!((not.evaluate(&ops, &mut lookup) - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_probabilistic() {
+        let ops = LogicOperators::probabilistic();
+        let and = Antecedent::is("health", "low").and(Antecedent::is("distance", "near"));
+        let or = Antecedent::is("health", "low").or(Antecedent::is("distance", "near"));
+
+        assert!((and.evaluate(&ops, &mut lookup) - 0.24).abs() < 1e-6);
+        assert!((or.evaluate(&ops, &mut lookup) - 0.86).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_nested() {
+        let ops = LogicOperators::new(TNorm::Min, SNorm::Max, Default::default());
+        // (health IS low AND NOT distance IS near) OR warm
+        let expr = Antecedent::is("health", "low")
+            .and(!Antecedent::is("distance", "near"))
+            .or("warm");
+        assert!((expr.evaluate(&ops, &mut lookup) - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_evaluate_empty_groups() {
+        let ops = LogicOperators::default();
+        let and: Antecedent<&str> = Antecedent::And(vec![]);
+        let or: Antecedent<&str> = Antecedent::Or(vec![]);
+        assert_eq!(and.evaluate(&ops, &mut lookup), 1.0);
+        assert_eq!(or.evaluate(&ops, &mut lookup), 0.0);
+    }
+
+    #[test]
+    fn test_evaluate_hedged() {
+        let ops = LogicOperators::default();
+
+        let very = Antecedent::is("health", "low").very();
+        let somewhat = Antecedent::is("distance", "near").somewhat();
+        let extremely = Antecedent::from("warm").extremely();
+        let indeed = Antecedent::from("warm").hedged(Hedge::Indeed);
+
+        assert!((very.evaluate(&ops, &mut lookup) - 0.64).abs() < 1e-6);
+        assert!((somewhat.evaluate(&ops, &mut lookup) - 0.3f32.sqrt()).abs() < 1e-6);
+        assert_eq!(extremely.evaluate(&ops, &mut lookup), 0.125);
+        assert_eq!(indeed.evaluate(&ops, &mut lookup), 0.5);
+    }
+
+    #[test]
+    fn test_evaluate_hedged_expression() {
+        let ops = LogicOperators::default();
+        // very (health IS low AND NOT distance IS near)
+        let expr = Antecedent::is("health", "low")
+            .and(!Antecedent::is("distance", "near"))
+            .very();
+        assert!((expr.evaluate(&ops, &mut lookup) - 0.49).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_terms() {
+        let expr = Antecedent::is("health", "low")
+            .and(!Antecedent::is("distance", "near").very())
+            .or("warm");
+        assert_eq!(
+            expr.terms(),
+            vec![
+                (Some("health"), &"low"),
+                (Some("distance"), &"near"),
+                (None, &"warm")
+            ]
+        );
+    }
+}
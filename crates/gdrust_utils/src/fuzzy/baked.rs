@@ -0,0 +1,406 @@
+use std::hash::Hash;
+
+use super::{inference, FuzzySystem};
+
+/// Settings for [`FuzzySystem::bake`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BakeOptions {
+    /// Number of samples across each input universe
+    pub resolution: usize,
+    /// Upper bound for the samples of the whole table. The per-input resolution is lowered
+    /// to stay at or below it, but never below 2, so tables over many inputs can exceed it.
+    pub max_samples: usize,
+    pub interpolation: Interpolation,
+}
+
+impl Default for BakeOptions {
+    fn default() -> Self {
+        Self {
+            resolution: 65,
+            max_samples: 1_000_000,
+            interpolation: Interpolation::default(),
+        }
+    }
+}
+
+impl BakeOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples;
+        self
+    }
+
+    pub fn with_interpolation(mut self, interpolation: Interpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+}
+
+/// How a [`BakedSystem`] reads values between its samples
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Interpolation {
+    /// The value of the closest sample
+    Nearest,
+    /// Linear between the two closest samples of every input, so bilinear for two inputs
+    #[default]
+    Linear,
+}
+
+/// An input universe of a [`BakedSystem`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedAxis {
+    /// The input variable, or `None` for [`FuzzySystem::input_sets`]
+    pub input: Option<String>,
+    pub min: f32,
+    pub max: f32,
+    pub resolution: usize,
+}
+
+impl BakedAxis {
+    /// Index of the sample at or below `value` and the position between it and the next
+    /// sample, in 0..=1
+    fn locate(&self, value: f32) -> (usize, f32) {
+        let last = self.resolution - 1;
+        let span = self.max - self.min;
+        if last == 0 || span <= 0.0 {
+            return (0, 0.0);
+        }
+        let position = ((value - self.min) / span).clamp(0.0, 1.0) * last as f32;
+        let index = (position as usize).min(last - 1);
+        (index, position - index as f32)
+    }
+}
+
+/// A [`FuzzySystem`] sampled into a dense table by [`FuzzySystem::bake`]. Queries take one
+/// value per axis, are clamped into the baked universes and don't allocate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BakedSystem<OutputType> {
+    axes: Vec<BakedAxis>,
+    outputs: Vec<OutputType>,
+    /// Offset between neighbouring samples of each axis, in samples
+    strides: Vec<usize>,
+    /// Output values of every sample, NaN where the output is undefined
+    values: Vec<f32>,
+    interpolation: Interpolation,
+}
+
+impl<OutputType: PartialEq> BakedSystem<OutputType> {
+    pub fn axes(&self) -> &[BakedAxis] {
+        &self.axes
+    }
+
+    /// Outputs in the order [`BakedSystem::evaluate_into`] writes them, the outputs of
+    /// [`FuzzySystem::output_variables`] first
+    pub fn outputs(&self) -> &[OutputType] {
+        &self.outputs
+    }
+
+    pub fn output_index(&self, output: &OutputType) -> Option<usize> {
+        self.outputs.iter().position(|o| o == output)
+    }
+
+    /// The value of the output at `output_index` for `inputs`, or `None` if it is undefined
+    /// around them. Undefined samples are left out of the interpolation.
+    ///
+    /// # Panics
+    ///
+    /// If there isn't exactly one input per axis.
+    pub fn value(&self, output_index: usize, inputs: &[f32]) -> Option<f32> {
+        assert_eq!(inputs.len(), self.axes.len(), "one input per axis");
+        let outputs = self.outputs.len();
+
+        if self.interpolation == Interpolation::Nearest {
+            let sample = self.axes.iter().zip(inputs).zip(&self.strides).fold(
+                0,
+                |sample, ((axis, input), stride)| {
+                    let (index, t) = axis.locate(*input);
+                    sample + (index + (t >= 0.5) as usize) * stride
+                },
+            );
+            let value = self.values[sample * outputs + output_index];
+            return (!value.is_nan()).then_some(value);
+        }
+
+        // Blend the 2^n corners of the cell around the inputs, one bit per axis
+        let (mut weighted_sum, mut total_weight) = (0.0, 0.0);
+        for corner in 0..1usize << self.axes.len() {
+            let mut sample = 0;
+            let mut weight = 1.0;
+            for (bit, ((axis, input), stride)) in
+                self.axes.iter().zip(inputs).zip(&self.strides).enumerate()
+            {
+                let (index, t) = axis.locate(*input);
+                if corner & (1 << bit) == 0 {
+                    sample += index * stride;
+                    weight *= 1.0 - t;
+                } else {
+                    sample += (index + 1).min(axis.resolution - 1) * stride;
+                    weight *= t;
+                }
+            }
+            let value = self.values[sample * outputs + output_index];
+            if weight > 0.0 && !value.is_nan() {
+                weighted_sum += weight * value;
+                total_weight += weight;
+            }
+        }
+        (total_weight > 0.0).then(|| weighted_sum / total_weight)
+    }
+
+    /// Writes the value of every output for `inputs` into `outputs`, in the order of
+    /// [`BakedSystem::outputs`]
+    ///
+    /// # Panics
+    ///
+    /// If there isn't exactly one input per axis, or `outputs` is shorter than
+    /// [`BakedSystem::outputs`].
+    pub fn evaluate_into(&self, inputs: &[f32], outputs: &mut [Option<f32>]) {
+        for (index, output) in outputs[..self.outputs.len()].iter_mut().enumerate() {
+            *output = self.value(index, inputs);
+        }
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    /// Samples the system into a lookup table for fast repeated queries.
+    ///
+    /// Systems with input variables get one axis per variable, in order, spanning its
+    /// universe and sampled like [`FuzzySystem::evaluate_inputs`]. Terms that read
+    /// [`FuzzySystem::input_sets`] get no axis then and have a membership of 0 everywhere,
+    /// as they would without their input. Systems without input variables get a single axis
+    /// over the union of the supports of the input sets, sampled like
+    /// [`FuzzySystem::evaluate`].
+    pub fn bake(&self, options: &BakeOptions) -> BakedSystem<OutputType> {
+        let axes: Vec<BakedAxis> = if self.input_variables.is_empty() {
+            let (min, max) = self
+                .input_sets
+                .iter()
+                .filter_map(|set| set.support())
+                .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)))
+                .unwrap_or((0.0, 0.0));
+            vec![BakedAxis {
+                input: None,
+                min,
+                max,
+                resolution: 0,
+            }]
+        } else {
+            self.input_variables
+                .iter()
+                .map(|variable| BakedAxis {
+                    input: Some(variable.name.clone()),
+                    min: variable.min,
+                    max: variable.max,
+                    resolution: 0,
+                })
+                .collect()
+        };
+
+        let dimensions = axes.len() as u32;
+        let mut resolution = options.resolution.max(2);
+        while resolution > 2 && resolution.saturating_pow(dimensions) > options.max_samples {
+            resolution -= 1;
+        }
+        let axes: Vec<BakedAxis> = axes
+            .into_iter()
+            .map(|axis| BakedAxis { resolution, ..axis })
+            .collect();
+        let strides: Vec<usize> = (0..axes.len()).map(|i| resolution.pow(i as u32)).collect();
+
+        let outputs = self.outputs();
+
+        let positions: Vec<Vec<f32>> = axes
+            .iter()
+            .map(|axis| inference::sample_positions(axis.min, axis.max, resolution).collect())
+            .collect();
+        let samples = resolution.pow(dimensions);
+        let mut values = Vec::with_capacity(samples * outputs.len());
+        let mut indices = vec![0; axes.len()];
+        for _ in 0..samples {
+            let result = if self.input_variables.is_empty() {
+                let x = positions[0][indices[0]];
+                self.evaluate_with(&|_| Some(x), None)
+            } else {
+                self.evaluate_with(
+                    &|input| {
+                        let axis = axes
+                            .iter()
+                            .position(|axis| axis.input.as_deref() == input)?;
+                        Some(positions[axis][indices[axis]])
+                    },
+                    None,
+                )
+            };
+            values.extend(
+                outputs
+                    .iter()
+                    .map(|output| result.get(output).copied().unwrap_or(f32::NAN)),
+            );
+
+            // Advance the indices like the digits of a number, the first axis fastest
+            if let Some(axis) = indices.iter().position(|i| i + 1 < resolution) {
+                indices[axis] += 1;
+                indices[..axis].iter_mut().for_each(|i| *i = 0);
+            }
+        }
+
+        BakedSystem {
+            axes,
+            outputs,
+            strides,
+            values,
+            interpolation: options.interpolation,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{Antecedent, FuzzyRule, FuzzySet, FuzzyVariable};
+    use std::collections::HashMap;
+
+    fn single_input() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("low", vec![(0.0, 1.0), (10.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("low").with_consequence("speed", 0.0));
+        system.add_rule(FuzzyRule::new("high").with_consequence("speed", 1.0));
+        system
+    }
+
+    fn two_inputs() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        for name in ["health", "distance"] {
+            system.add_input_variable(
+                FuzzyVariable::new(name, 0.0, 100.0)
+                    .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (100.0, 0.0)]))
+                    .with_term(FuzzySet::new("high", vec![(0.0, 0.0), (100.0, 1.0)])),
+            );
+        }
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").and(Antecedent::is("distance", "low")))
+                .with_consequence("flee", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high").or(Antecedent::is("distance", "high")))
+                .with_consequence("flee", 0.0),
+        );
+        system
+    }
+
+    #[test]
+    fn test_bake_single_input() {
+        let system = single_input();
+        let baked = system.bake(&BakeOptions::new().with_resolution(11));
+
+        assert_eq!(baked.axes()[0].input, None);
+        assert_eq!((baked.axes()[0].min, baked.axes()[0].max), (0.0, 10.0));
+        assert_eq!(baked.outputs(), &["speed"]);
+        for x in [0.0, 2.5, 3.0, 7.25, 10.0] {
+            let value = baked.value(0, &[x]).unwrap();
+            assert!((value - system.evaluate(x)["speed"]).abs() < 1e-5, "{x}");
+        }
+        // Clamped into the universe
+        assert_eq!(baked.value(0, &[-5.0]), Some(0.0));
+        assert_eq!(baked.value(0, &[20.0]), Some(1.0));
+    }
+
+    #[test]
+    fn test_bake_bilinear() {
+        let system = two_inputs();
+        let baked = system.bake(&BakeOptions::new().with_resolution(21));
+
+        let flee = baked.output_index(&"flee").unwrap();
+        for (health, distance) in [(0.0, 0.0), (10.0, 30.0), (25.0, 25.0), (40.0, 90.0)] {
+            let expected = system
+                .evaluate_inputs(&HashMap::from([("health", health), ("distance", distance)]))
+                ["flee"];
+            let value = baked.value(flee, &[health, distance]).unwrap();
+            assert!((value - expected).abs() < 0.02, "{health}, {distance}");
+        }
+    }
+
+    #[test]
+    fn test_bake_nearest() {
+        let baked = single_input().bake(
+            &BakeOptions::new()
+                .with_resolution(3)
+                .with_interpolation(Interpolation::Nearest),
+        );
+        assert_eq!(baked.value(0, &[2.0]), Some(0.0));
+        assert_eq!(baked.value(0, &[3.0]), Some(0.5));
+        assert_eq!(baked.value(0, &[8.0]), Some(1.0));
+    }
+
+    #[test]
+    fn test_bake_undefined_outputs() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("low", vec![(0.0, 1.0), (5.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("high", vec![(5.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("low").with_consequence("speed", 0.0));
+        system.add_rule(FuzzyRule::new("high").with_consequence("speed", 1.0));
+        let baked = system.bake(&BakeOptions::new().with_resolution(11));
+
+        // Nothing fires at 5, so the neighbours fill in
+        assert_eq!(baked.value(0, &[5.0]), None);
+        assert_eq!(baked.value(0, &[4.5]), Some(0.0));
+        assert_eq!(baked.value(0, &[5.5]), Some(1.0));
+
+        let mut outputs = [Some(1.0); 2];
+        baked.evaluate_into(&[5.0], &mut outputs);
+        assert_eq!(outputs, [None, Some(1.0)]);
+    }
+
+    #[test]
+    fn test_bake_output_order() {
+        let mut system = two_inputs();
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high"))
+                .with_consequence("turn", 1.0)
+                .with_consequence("aim", 1.0)
+                .with_consequence("flee", 1.0),
+        );
+        let baked = system.bake(&BakeOptions::new().with_resolution(3));
+
+        // Outputs only rules produce come after the earlier ones, in order
+        assert_eq!(baked.outputs(), ["flee", "aim", "turn"]);
+    }
+
+    #[test]
+    fn test_bake_input_sets_next_to_variables() {
+        let mut system = two_inputs();
+        system.add_input_set(FuzzySet::new("always", vec![(0.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("always").with_consequence("patrol", 1.0));
+        let baked = system.bake(&BakeOptions::new().with_resolution(5));
+
+        // The input set has no axis, so its rule never fires, like in evaluate_inputs
+        assert_eq!(baked.axes().len(), 2);
+        let inputs = HashMap::from([("health", 50.0), ("distance", 50.0)]);
+        assert_eq!(system.evaluate_inputs(&inputs).get("patrol"), None);
+        let patrol = baked.output_index(&"patrol").unwrap();
+        assert_eq!(baked.value(patrol, &[50.0, 50.0]), None);
+    }
+
+    #[test]
+    fn test_bake_resolution_is_capped() {
+        let baked = two_inputs().bake(&BakeOptions::new().with_max_samples(30));
+        assert!(baked.axes().iter().all(|axis| axis.resolution == 5));
+
+        // Two samples per input are the least a table can have
+        let baked = two_inputs().bake(&BakeOptions::new().with_max_samples(1));
+        assert!(baked.axes().iter().all(|axis| axis.resolution == 2));
+    }
+}
@@ -0,0 +1,569 @@
+use super::{FuzzySet, PartitionShape, Shape};
+
+/// Clustering algorithm of [`Clusters::fit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClusterMethod {
+    /// Fuzzy c-means, finding round clusters of similar size
+    #[default]
+    FuzzyCMeans,
+    /// Gustafson-Kessel, adapting the distance of each cluster to its covariance so it can
+    /// find elongated clusters. Same as fuzzy c-means for one dimensional data.
+    GustafsonKessel,
+}
+
+/// How [`Clusters::sets`] turns clusters into sets
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClusterSets {
+    /// A partition through the cluster centers, see [`FuzzySet::partition_at`]
+    Partition(PartitionShape),
+    /// A Gaussian per cluster, as wide as the spread of its cluster. The memberships don't
+    /// sum to 1 and fall off beyond the outer clusters.
+    Gaussian,
+}
+
+impl Default for ClusterSets {
+    fn default() -> Self {
+        ClusterSets::Partition(PartitionShape::default())
+    }
+}
+
+/// Settings for [`Clusters::fit`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClusterOptions {
+    pub method: ClusterMethod,
+    /// Exponent `m` > 1 weighting the memberships. Values close to 1 give almost crisp
+    /// clusters, larger values softer ones.
+    pub fuzziness: f32,
+    pub max_iterations: usize,
+    /// Stops once no membership changes by more than this in an iteration
+    pub tolerance: f32,
+}
+
+impl Default for ClusterOptions {
+    fn default() -> Self {
+        Self {
+            method: ClusterMethod::default(),
+            fuzziness: 2.0,
+            max_iterations: 100,
+            tolerance: 1e-5,
+        }
+    }
+}
+
+impl ClusterOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_method(mut self, method: ClusterMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    pub fn with_fuzziness(mut self, fuzziness: f32) -> Self {
+        self.fuzziness = fuzziness;
+        self
+    }
+
+    pub fn with_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: f32) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+}
+
+/// Fuzzy clusters of recorded points
+#[derive(Debug, Clone, PartialEq)]
+pub struct Clusters {
+    /// Center of each cluster
+    pub centers: Vec<Vec<f32>>,
+    /// Membership weighted standard deviation of each cluster along each dimension
+    pub spreads: Vec<Vec<f32>>,
+    /// Membership of each point in each cluster, summing to 1 per point
+    pub memberships: Vec<Vec<f32>>,
+    pub iterations: usize,
+    /// Whether the memberships settled within the tolerance before `max_iterations`
+    pub converged: bool,
+}
+
+/// Smallest membership change counted as movement, and distances counted as a hit
+const EPSILON: f64 = 1e-12;
+/// Ridge added to the cluster covariances of Gustafson-Kessel, relative to their trace
+const REGULARIZATION: f64 = 1e-4;
+
+impl Clusters {
+    /// Groups `points` into `clusters` fuzzy clusters. Starts deterministically from equal
+    /// slices of the points ordered along the dimension with the largest variance.
+    ///
+    /// Returns `None` without clusters, with fewer points than clusters or if the points
+    /// have different or no dimensions.
+    pub fn fit<P: AsRef<[f32]>>(
+        points: &[P],
+        clusters: usize,
+        options: &ClusterOptions,
+    ) -> Option<Self> {
+        let points: Vec<Vec<f64>> = points
+            .iter()
+            .map(|point| point.as_ref().iter().map(|x| *x as f64).collect())
+            .collect();
+        let dimensions = points.first()?.len();
+        if clusters == 0
+            || points.len() < clusters
+            || dimensions == 0
+            || points.iter().any(|point| point.len() != dimensions)
+        {
+            return None;
+        }
+
+        let fuzziness = (options.fuzziness as f64).max(1.0 + 1e-3);
+        let mut centers = initial_centers(&points, clusters);
+        let mut memberships = vec![vec![0.0; clusters]; points.len()];
+        let mut norms = vec![None; clusters];
+        let (mut iterations, mut converged) = (0, false);
+        while iterations < options.max_iterations && !converged {
+            iterations += 1;
+            let mut change: f64 = 0.0;
+            for (point, row) in points.iter().zip(&mut memberships) {
+                let distances: Vec<f64> = centers
+                    .iter()
+                    .zip(&norms)
+                    .map(|(center, norm)| distance(point, center, norm.as_ref()))
+                    .collect();
+                for (old, new) in row.iter_mut().zip(memberships_of(&distances, fuzziness)) {
+                    change = change.max((new - *old).abs());
+                    *old = new;
+                }
+            }
+            centers = weighted_centers(&points, &memberships, fuzziness, clusters);
+            if options.method == ClusterMethod::GustafsonKessel && dimensions > 1 {
+                norms = (0..clusters)
+                    .map(|i| {
+                        let covariance =
+                            covariance(&points, &memberships, fuzziness, i, &centers[i]);
+                        norm_matrix(covariance)
+                    })
+                    .collect();
+            }
+            converged = change <= (options.tolerance as f64).max(EPSILON);
+        }
+
+        let spreads = (0..clusters)
+            .map(|i| {
+                let covariance = covariance(&points, &memberships, fuzziness, i, &centers[i]);
+                (0..dimensions)
+                    .map(|d| covariance[d][d].max(0.0).sqrt() as f32)
+                    .collect()
+            })
+            .collect();
+        let to_f32 = |rows: Vec<Vec<f64>>| -> Vec<Vec<f32>> {
+            rows.into_iter()
+                .map(|row| row.into_iter().map(|x| x as f32).collect())
+                .collect()
+        };
+        Some(Self {
+            centers: to_f32(centers),
+            spreads,
+            memberships: to_f32(memberships),
+            iterations,
+            converged,
+        })
+    }
+
+    /// Sets for `categories` along one `dimension` of the points, assigned to the clusters
+    /// in ascending order of their centers, so `["low", "medium", "high"]` reads naturally.
+    /// Extra categories or clusters are ignored.
+    pub fn sets<T>(
+        &self,
+        dimension: usize,
+        categories: impl IntoIterator<Item = T>,
+        sets: ClusterSets,
+    ) -> Vec<FuzzySet<T>> {
+        let mut clusters: Vec<(f32, f32)> = self
+            .centers
+            .iter()
+            .zip(&self.spreads)
+            .filter_map(|(center, spread)| Some((*center.get(dimension)?, *spread.get(dimension)?)))
+            .collect();
+        clusters.sort_by(|a, b| a.0.total_cmp(&b.0));
+        match sets {
+            ClusterSets::Partition(shape) => {
+                let centers: Vec<f32> = clusters.iter().map(|(center, _)| *center).collect();
+                FuzzySet::partition_at(categories, &centers, shape)
+            }
+            ClusterSets::Gaussian => categories
+                .into_iter()
+                .zip(clusters)
+                .map(|(category, (mean, sigma))| {
+                    let sigma = sigma.max(f32::EPSILON * mean.abs().max(1.0));
+                    FuzzySet::with_shape(category, Shape::Gaussian { mean, sigma })
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<T> FuzzySet<T> {
+    /// One set per category fitted to clusters of recorded `values`, see [`Clusters::fit`]
+    /// and [`Clusters::sets`]. Empty if there are fewer values than categories, and fewer sets
+    /// for partitions when clusters share a center.
+    pub fn fit(
+        categories: impl IntoIterator<Item = T>,
+        values: &[f32],
+        sets: ClusterSets,
+        options: &ClusterOptions,
+    ) -> Vec<Self> {
+        let categories: Vec<T> = categories.into_iter().collect();
+        let points: Vec<[f32; 1]> = values.iter().map(|value| [*value]).collect();
+        match Clusters::fit(&points, categories.len(), options) {
+            Some(clusters) => clusters.sets(0, categories, sets),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Means of equal slices of the points ordered along the dimension with the largest variance
+fn initial_centers(points: &[Vec<f64>], clusters: usize) -> Vec<Vec<f64>> {
+    let dimensions = points[0].len();
+    let count = points.len() as f64;
+    let variance = |d: usize| {
+        let mean = points.iter().map(|point| point[d]).sum::<f64>() / count;
+        points
+            .iter()
+            .map(|point| (point[d] - mean).powi(2))
+            .sum::<f64>()
+    };
+    let axis = (0..dimensions)
+        .max_by(|a, b| variance(*a).total_cmp(&variance(*b)))
+        .unwrap_or(0);
+    let mut order: Vec<&Vec<f64>> = points.iter().collect();
+    order.sort_by(|a, b| a[axis].total_cmp(&b[axis]));
+    (0..clusters)
+        .map(|i| {
+            let slice = &order[i * order.len() / clusters..(i + 1) * order.len() / clusters];
+            (0..dimensions)
+                .map(|d| slice.iter().map(|point| point[d]).sum::<f64>() / slice.len() as f64)
+                .collect()
+        })
+        .collect()
+}
+
+/// Squared distance of `point` from `center`, Euclidean or induced by a norm matrix
+fn distance(point: &[f64], center: &[f64], norm: Option<&Vec<Vec<f64>>>) -> f64 {
+    let offset: Vec<f64> = point.iter().zip(center).map(|(x, c)| x - c).collect();
+    match norm {
+        Some(norm) => norm
+            .iter()
+            .zip(&offset)
+            .map(|(row, a)| a * row.iter().zip(&offset).map(|(m, b)| m * b).sum::<f64>())
+            .sum::<f64>()
+            .max(0.0),
+        None => offset.iter().map(|a| a * a).sum(),
+    }
+}
+
+/// Memberships of a point in each cluster from its squared distances to the centers. A
+/// point on one or more centers belongs to those alone.
+fn memberships_of(distances: &[f64], fuzziness: f64) -> Vec<f64> {
+    let hits = distances.iter().filter(|d| **d < EPSILON).count();
+    if hits > 0 {
+        return distances
+            .iter()
+            .map(|d| if *d < EPSILON { 1.0 / hits as f64 } else { 0.0 })
+            .collect();
+    }
+    let exponent = 1.0 / (fuzziness - 1.0);
+    distances
+        .iter()
+        .map(|d| {
+            let sum: f64 = distances
+                .iter()
+                .map(|other| (d / other).powf(exponent))
+                .sum();
+            1.0 / sum
+        })
+        .collect()
+}
+
+fn weighted_centers(
+    points: &[Vec<f64>],
+    memberships: &[Vec<f64>],
+    fuzziness: f64,
+    clusters: usize,
+) -> Vec<Vec<f64>> {
+    (0..clusters)
+        .map(|i| {
+            let mut center = vec![0.0; points[0].len()];
+            let mut total = 0.0;
+            for (point, row) in points.iter().zip(memberships) {
+                let weight = row[i].powf(fuzziness);
+                total += weight;
+                for (c, x) in center.iter_mut().zip(point) {
+                    *c += weight * x;
+                }
+            }
+            center
+                .iter()
+                .map(|c| c / total.max(f64::MIN_POSITIVE))
+                .collect()
+        })
+        .collect()
+}
+
+/// Fuzzy covariance matrix of cluster `i`
+fn covariance(
+    points: &[Vec<f64>],
+    memberships: &[Vec<f64>],
+    fuzziness: f64,
+    i: usize,
+    center: &[f64],
+) -> Vec<Vec<f64>> {
+    let dimensions = center.len();
+    let mut matrix = vec![vec![0.0; dimensions]; dimensions];
+    let mut total = 0.0;
+    for (point, row) in points.iter().zip(memberships) {
+        let weight = row[i].powf(fuzziness);
+        total += weight;
+        for a in 0..dimensions {
+            for b in 0..dimensions {
+                matrix[a][b] += weight * (point[a] - center[a]) * (point[b] - center[b]);
+            }
+        }
+    }
+    let total = total.max(f64::MIN_POSITIVE);
+    matrix
+        .into_iter()
+        .map(|row| row.into_iter().map(|x| x / total).collect())
+        .collect()
+}
+
+/// The Gustafson-Kessel norm `det(F)^(1/n) * F^-1` of a cluster with covariance `F`, so all
+/// clusters keep the same volume. `None` for Euclidean distances if `F` vanishes.
+fn norm_matrix(mut covariance: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let dimensions = covariance.len();
+    let trace: f64 = (0..dimensions).map(|d| covariance[d][d]).sum();
+    if trace < EPSILON {
+        return None;
+    }
+    for (d, row) in covariance.iter_mut().enumerate() {
+        row[d] += REGULARIZATION * trace / dimensions as f64;
+    }
+    let (inverse, determinant) = invert(covariance)?;
+    let scale = determinant.max(0.0).powf(1.0 / dimensions as f64);
+    Some(
+        inverse
+            .into_iter()
+            .map(|row| row.into_iter().map(|x| x * scale).collect())
+            .collect(),
+    )
+}
+
+/// Inverse and determinant by Gauss-Jordan elimination with partial pivoting
+fn invert(mut matrix: Vec<Vec<f64>>) -> Option<(Vec<Vec<f64>>, f64)> {
+    let n = matrix.len();
+    let mut inverse: Vec<Vec<f64>> = (0..n)
+        .map(|row| {
+            (0..n)
+                .map(|column| if row == column { 1.0 } else { 0.0 })
+                .collect()
+        })
+        .collect();
+    let mut determinant = 1.0;
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        if matrix[pivot][column].abs() < EPSILON {
+            return None;
+        }
+        if pivot != column {
+            matrix.swap(column, pivot);
+            inverse.swap(column, pivot);
+            determinant = -determinant;
+        }
+        let value = matrix[column][column];
+        determinant *= value;
+        for k in 0..n {
+            matrix[column][k] /= value;
+            inverse[column][k] /= value;
+        }
+        for row in 0..n {
+            let factor = matrix[row][column];
+            if row == column || factor == 0.0 {
+                continue;
+            }
+            for k in 0..n {
+                matrix[row][k] -= factor * matrix[column][k];
+                inverse[row][k] -= factor * inverse[column][k];
+            }
+        }
+    }
+    Some((inverse, determinant))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Three blobs of values around 10, 50 and 90
+    fn values() -> Vec<f32> {
+        let mut values = Vec::new();
+        for center in [90.0, 10.0, 50.0] {
+            for offset in [-4.0, -2.0, -1.0, 0.0, 1.0, 2.0, 4.0] {
+                values.push(center + offset);
+            }
+        }
+        values
+    }
+
+    #[test]
+    fn test_fuzzy_c_means_finds_blobs() {
+        let points: Vec<[f32; 1]> = values().into_iter().map(|value| [value]).collect();
+        let clusters = Clusters::fit(&points, 3, &ClusterOptions::default()).unwrap();
+        assert!(clusters.converged);
+
+        let mut centers: Vec<f32> = clusters.centers.iter().map(|center| center[0]).collect();
+        centers.sort_by(f32::total_cmp);
+        for (center, expected) in centers.iter().zip([10.0, 50.0, 90.0]) {
+            assert!((center - expected).abs() < 0.1, "{center}");
+        }
+        for row in &clusters.memberships {
+            assert!((row.iter().sum::<f32>() - 1.0).abs() < 1e-5);
+            assert!(row.iter().any(|membership| *membership > 0.95));
+        }
+        for spread in &clusters.spreads {
+            assert!(spread[0] > 1.0 && spread[0] < 3.5, "{spread:?}");
+        }
+    }
+
+    #[test]
+    fn test_fit_partition() {
+        let sets = FuzzySet::fit(
+            ["low", "medium", "high"],
+            &values(),
+            ClusterSets::Partition(PartitionShape::Triangle),
+            &ClusterOptions::default(),
+        );
+        assert_eq!(sets.len(), 3);
+        assert_eq!(sets[0].category, "low");
+        assert!(sets[0].membership(10.0) > 0.99);
+        assert!(sets[1].membership(50.0) > 0.99);
+        assert!(sets[2].membership(90.0) > 0.99);
+        assert!((sets[0].membership(30.0) - 0.5).abs() < 0.01);
+        assert_eq!(sets[2].membership(200.0), 1.0);
+    }
+
+    #[test]
+    fn test_fit_partition_to_constant_values() {
+        let sets = FuzzySet::fit(
+            ["low", "high"],
+            &[5.0; 8],
+            ClusterSets::Partition(PartitionShape::Triangle),
+            &ClusterOptions::default(),
+        );
+        // Both clusters sit at 5, so a single set covers everything
+        assert_eq!(sets.len(), 1);
+        for x in [0.0, 5.0, 10.0] {
+            assert_eq!(sets[0].membership(x), 1.0);
+        }
+    }
+
+    #[test]
+    fn test_fit_gaussians() {
+        let sets = FuzzySet::fit(
+            ["low", "medium", "high"],
+            &values(),
+            ClusterSets::Gaussian,
+            &ClusterOptions::default(),
+        );
+        match sets[1].shape {
+            Shape::Gaussian { mean, sigma } => {
+                assert!((mean - 50.0).abs() < 0.1);
+                assert!(sigma > 1.0 && sigma < 3.5);
+            }
+            ref shape => panic!("{shape:?}"),
+        }
+        assert!(sets[0].membership(30.0) < 0.01);
+    }
+
+    #[test]
+    fn test_gustafson_kessel_finds_elongated_clusters() {
+        // A horizontal bar ending next to the middle of a vertical one
+        let mut points = Vec::new();
+        for i in 0..=20 {
+            let t = i as f32 / 2.0 - 5.0;
+            points.push([t, 0.0]);
+            points.push([8.0, t]);
+        }
+        let end = points
+            .iter()
+            .position(|point| *point == [4.5, 0.0])
+            .unwrap();
+        let options = ClusterOptions::new().with_method(ClusterMethod::GustafsonKessel);
+        let clusters = Clusters::fit(&points, 2, &options).unwrap();
+        assert!(clusters.converged);
+
+        let horizontal = usize::from(clusters.spreads[0][0] < clusters.spreads[0][1]);
+        for (point, row) in points.iter().zip(&clusters.memberships) {
+            let cluster = if point[0] == 8.0 {
+                1 - horizontal
+            } else {
+                horizontal
+            };
+            if point[1] != 0.0 || point[0] != 8.0 {
+                assert!(row[cluster] > 0.5, "{point:?} {row:?}");
+            }
+        }
+        let sets = clusters.sets(0, ["bar", "post"], ClusterSets::Gaussian);
+        assert!(sets[0].membership(3.0) > 0.5);
+        assert!(sets[1].membership(8.0) == 1.0 && sets[1].membership(7.5) < 0.01);
+
+        // Round clusters give the end of the bar to the closer vertical one
+        let clusters = Clusters::fit(&points, 2, &ClusterOptions::default()).unwrap();
+        let vertical = usize::from(clusters.centers[1][0] > clusters.centers[0][0]);
+        assert!(clusters.memberships[end][vertical] > 0.5);
+    }
+
+    #[test]
+    fn test_fuzziness() {
+        let points: Vec<[f32; 1]> = values().into_iter().map(|value| [value]).collect();
+        let crisp = ClusterOptions::new().with_fuzziness(1.1);
+        let soft = ClusterOptions::new().with_fuzziness(4.0);
+        let crisp = Clusters::fit(&points, 3, &crisp).unwrap();
+        let soft = Clusters::fit(&points, 3, &soft).unwrap();
+        let largest =
+            |clusters: &Clusters| clusters.memberships[0].iter().copied().fold(0.0, f32::max);
+        assert!(largest(&crisp) > 0.9999);
+        assert!(largest(&soft) < largest(&crisp));
+    }
+
+    #[test]
+    fn test_degenerate_data() {
+        let options = ClusterOptions::default();
+        assert_eq!(Clusters::fit::<[f32; 1]>(&[], 2, &options), None);
+        assert_eq!(Clusters::fit(&[[1.0]], 2, &options), None);
+        assert_eq!(Clusters::fit(&[[1.0], [2.0]], 0, &options), None);
+        assert_eq!(
+            Clusters::fit(&[vec![1.0], vec![2.0, 3.0]], 1, &options),
+            None
+        );
+        assert!(FuzzySet::fit(["a", "b"], &[1.0], ClusterSets::default(), &options).is_empty());
+
+        // Identical values still give usable sets
+        let sets = FuzzySet::fit(["a", "b"], &[3.0; 4], ClusterSets::Gaussian, &options);
+        assert_eq!(sets.len(), 2);
+        assert_eq!(sets[0].membership(3.0), 1.0);
+
+        let clusters = Clusters::fit(&[[0.0, 0.0], [0.0, 0.0], [4.0, 4.0]], 2, &options).unwrap();
+        assert!(clusters.converged);
+        assert_eq!(clusters.spreads.len(), 2);
+    }
+}
@@ -0,0 +1,675 @@
+use std::hash::Hash;
+
+use super::{
+    inference, sorted_by_output, Antecedent, Consequent, Defuzzifier, FuzzySystem, FuzzyVariable,
+    Hedge, Inference, LogicOperators, Shape,
+};
+
+/// A [`FuzzySystem`] with its inputs, terms and outputs resolved to indices by
+/// [`FuzzySystem::compile`], for evaluation without hashing or allocation.
+///
+/// Inputs are passed as a slice in the order of [`CompiledSystem::inputs`] and outputs are
+/// written in the order of [`CompiledSystem::outputs`], with NaN for undefined outputs. The
+/// compiled system is a snapshot, so changes to the source system need a new compile.
+#[derive(Debug, Clone)]
+pub struct CompiledSystem<OutputType> {
+    inputs: Vec<CompiledInput>,
+    outputs: Vec<OutputType>,
+    terms: Vec<CompiledTerm>,
+    rules: Vec<CompiledRule>,
+    operators: LogicOperators,
+    /// Universes of the outputs and the defuzzification settings under Mamdani inference
+    mamdani: Option<Mamdani>,
+    scratch: Scratch,
+}
+
+#[derive(Debug, Clone)]
+struct CompiledInput {
+    name: Option<String>,
+    /// The input variable, for its out of range policy
+    variable: Option<FuzzyVariable<()>>,
+}
+
+/// A set read by rule conditions, together with the input it reads
+#[derive(Debug, Clone)]
+struct CompiledTerm {
+    input: Option<usize>,
+    shape: Option<Shape>,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Condition {
+    /// Index into the terms
+    Term(usize),
+    And(Vec<Condition>),
+    Or(Vec<Condition>),
+    Not(Box<Condition>),
+    Hedged(Hedge, Box<Condition>),
+}
+
+impl Condition {
+    /// Mirrors [`Antecedent::evaluate`] on precomputed term memberships
+    fn evaluate(&self, operators: &LogicOperators, memberships: &[f32]) -> f32 {
+        match self {
+            Condition::Term(term) => memberships[*term],
+            Condition::And(terms) => terms.iter().fold(1.0, |acc, t| {
+                operators.and.apply(acc, t.evaluate(operators, memberships))
+            }),
+            Condition::Or(terms) => terms.iter().fold(0.0, |acc, t| {
+                operators.or.apply(acc, t.evaluate(operators, memberships))
+            }),
+            Condition::Not(inner) => operators.not.apply(inner.evaluate(operators, memberships)),
+            Condition::Hedged(hedge, inner) => hedge.apply(inner.evaluate(operators, memberships)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    condition: Condition,
+    weight: f32,
+    operators: LogicOperators,
+    /// Index of an existing rule block
+    block: Option<usize>,
+    /// Consequences the inference mode uses, by output index
+    consequences: Vec<(usize, CompiledConsequent)>,
+}
+
+#[derive(Debug, Clone)]
+enum CompiledConsequent {
+    Constant(f32),
+    /// `constant + sum(coefficient * input)`, with `None` for inputs that are never given
+    Linear {
+        constant: f32,
+        coefficients: Vec<(Option<usize>, f32)>,
+    },
+    /// The output set, if it exists
+    Term(Option<Shape>),
+}
+
+#[derive(Debug, Clone)]
+struct Mamdani {
+    defuzzifier: Defuzzifier,
+    resolution: usize,
+    /// Universe of each output, `None` without an output variable
+    universes: Vec<Option<(f32, f32)>>,
+}
+
+/// Buffers reused by every evaluation
+#[derive(Debug, Clone, Default)]
+struct Scratch {
+    inputs: Vec<Option<f32>>,
+    memberships: Vec<f32>,
+    strengths: Vec<f32>,
+    /// Weighted sum and total weight of each output
+    sums: Vec<(f32, f32)>,
+    /// Aggregated membership of each rule block at the current sample
+    blocks: Vec<f32>,
+    samples: Vec<(f32, f32)>,
+}
+
+impl<OutputType: PartialEq> CompiledSystem<OutputType> {
+    /// Input names in the order [`CompiledSystem::evaluate_into`] reads them. A single `None`
+    /// input is read by every term, like [`FuzzySystem::evaluate`].
+    pub fn inputs(&self) -> impl Iterator<Item = Option<&str>> {
+        self.inputs.iter().map(|input| input.name.as_deref())
+    }
+
+    pub fn input_index(&self, name: &str) -> Option<usize> {
+        self.inputs
+            .iter()
+            .position(|input| input.name.as_deref() == Some(name))
+    }
+
+    /// Outputs in the order [`CompiledSystem::evaluate_into`] writes them, the outputs of
+    /// [`FuzzySystem::output_variables`] first
+    pub fn outputs(&self) -> &[OutputType] {
+        &self.outputs
+    }
+
+    pub fn output_index(&self, output: &OutputType) -> Option<usize> {
+        self.outputs.iter().position(|o| o == output)
+    }
+
+    /// Evaluates the system for one value per input, writing one value per output into
+    /// `outputs`, or NaN where the output is undefined.
+    ///
+    /// # Panics
+    ///
+    /// If `inputs` or `outputs` are shorter than [`CompiledSystem::inputs`] or
+    /// [`CompiledSystem::outputs`].
+    pub fn evaluate_into(&mut self, inputs: &[f32], outputs: &mut [f32]) {
+        let Self {
+            inputs: compiled_inputs,
+            terms,
+            rules,
+            operators,
+            mamdani,
+            scratch,
+            ..
+        } = self;
+        assert!(inputs.len() >= compiled_inputs.len(), "one value per input");
+        let outputs = &mut outputs[..scratch.sums.len()];
+
+        for ((resolved, input), value) in scratch
+            .inputs
+            .iter_mut()
+            .zip(compiled_inputs.iter())
+            .zip(inputs)
+        {
+            *resolved = match &input.variable {
+                Some(variable) => variable.resolve(*value),
+                None => Some(*value),
+            };
+        }
+
+        for (membership, term) in scratch.memberships.iter_mut().zip(terms.iter()) {
+            *membership = match (term.input.and_then(|i| scratch.inputs[i]), &term.shape) {
+                (Some(value), Some(shape)) => shape.membership(value),
+                _ => 0.0,
+            };
+        }
+        for (strength, rule) in scratch.strengths.iter_mut().zip(rules.iter()) {
+            *strength = rule.weight
+                * rule
+                    .condition
+                    .evaluate(&rule.operators, &scratch.memberships);
+        }
+
+        let Some(mamdani) = mamdani else {
+            scratch.sums.fill((0.0, 0.0));
+            for (rule, strength) in rules.iter().zip(&scratch.strengths) {
+                if *strength <= 0.0 {
+                    continue;
+                }
+                for (output, consequent) in &rule.consequences {
+                    let value = match consequent {
+                        CompiledConsequent::Constant(value) => Some(*value),
+                        CompiledConsequent::Linear {
+                            constant,
+                            coefficients,
+                        } => {
+                            coefficients
+                                .iter()
+                                .try_fold(*constant, |acc, (input, coefficient)| {
+                                    input
+                                        .and_then(|input| scratch.inputs[input])
+                                        .map(|value| acc + coefficient * value)
+                                })
+                        }
+                        CompiledConsequent::Term(_) => None,
+                    };
+                    if let Some(value) = value {
+                        let (weighted_sum, total_weight) = &mut scratch.sums[*output];
+                        *weighted_sum += value * strength;
+                        *total_weight += strength;
+                    }
+                }
+            }
+            for (value, (weighted_sum, total_weight)) in outputs.iter_mut().zip(&scratch.sums) {
+                *value = if *total_weight > 0.0 {
+                    weighted_sum / total_weight
+                } else {
+                    f32::NAN
+                };
+            }
+            return;
+        };
+
+        for (output, value) in outputs.iter_mut().enumerate() {
+            *value = f32::NAN;
+            let Some((min, max)) = mamdani.universes[output] else {
+                continue;
+            };
+            let fired = rules
+                .iter()
+                .zip(&scratch.strengths)
+                .any(|(rule, strength)| {
+                    *strength > 0.0 && rule.consequences.iter().any(|(o, _)| *o == output)
+                });
+            if !fired {
+                continue;
+            }
+
+            let positions = inference::sample_positions(min, max, mamdani.resolution);
+            for (sample, x) in scratch.samples.iter_mut().zip(positions) {
+                scratch.blocks.fill(0.0);
+                let mut membership = 0.0;
+                for (rule, strength) in rules.iter().zip(&scratch.strengths) {
+                    if *strength <= 0.0 {
+                        continue;
+                    }
+                    for (_, consequent) in rule.consequences.iter().filter(|(o, _)| *o == output) {
+                        let CompiledConsequent::Term(Some(shape)) = consequent else {
+                            continue;
+                        };
+                        let implied = rule
+                            .operators
+                            .implication
+                            .apply(*strength, shape.membership(x));
+                        match rule.block {
+                            Some(block) => {
+                                scratch.blocks[block] = rule
+                                    .operators
+                                    .aggregation
+                                    .apply(scratch.blocks[block], implied)
+                            }
+                            None => {
+                                membership = rule.operators.aggregation.apply(membership, implied)
+                            }
+                        }
+                    }
+                }
+                let membership = scratch.blocks.iter().fold(membership, |acc, block| {
+                    operators.aggregation.apply(acc, *block)
+                });
+                *sample = (x, membership);
+            }
+            if let Some(result) = mamdani.defuzzifier.defuzzify(&scratch.samples) {
+                *value = result;
+            }
+        }
+    }
+
+    /// Evaluates many input rows at once, like [`CompiledSystem::evaluate_into`] for each.
+    /// `inputs` holds one row of [`CompiledSystem::inputs`] values per agent and `outputs`
+    /// receives one row of [`CompiledSystem::outputs`] values per agent.
+    ///
+    /// # Panics
+    ///
+    /// If `inputs` doesn't hold whole rows, or `outputs` has fewer rows than `inputs`.
+    pub fn evaluate_batch(&mut self, inputs: &[f32], outputs: &mut [f32]) {
+        let (input_count, output_count) = (self.inputs.len(), self.outputs.len());
+        assert_eq!(inputs.len() % input_count, 0, "whole input rows");
+        let rows = inputs.len() / input_count;
+        assert!(
+            outputs.len() >= rows * output_count,
+            "one output row per input row"
+        );
+
+        if output_count == 0 {
+            return;
+        }
+        for (inputs, outputs) in inputs
+            .chunks_exact(input_count)
+            .zip(outputs.chunks_exact_mut(output_count))
+        {
+            self.evaluate_into(inputs, outputs);
+        }
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    /// Resolves inputs, terms and outputs to indices for [`CompiledSystem::evaluate_into`].
+    ///
+    /// The inputs are the input variables followed by the other inputs rules read, which
+    /// evaluates like [`FuzzySystem::evaluate_inputs`]. Systems whose rules name no input at
+    /// all get a single input read by every term, like [`FuzzySystem::evaluate`]. The outputs
+    /// are the declared ones followed by those rules produce. Within a rule, consequences are
+    /// visited in the order of their outputs. Disabled rules are left out.
+    pub fn compile(&self) -> CompiledSystem<OutputType> {
+        let names = self.input_names();
+        let inputs: Vec<CompiledInput> = if names.is_empty() {
+            vec![CompiledInput {
+                name: None,
+                variable: None,
+            }]
+        } else {
+            names
+                .into_iter()
+                .map(|name| CompiledInput {
+                    variable: self.input_variable(&name).map(|variable| FuzzyVariable {
+                        name: variable.name.clone(),
+                        min: variable.min,
+                        max: variable.max,
+                        terms: Vec::new(),
+                        out_of_range: variable.out_of_range,
+                    }),
+                    name: Some(name),
+                })
+                .collect()
+        };
+        let input_index = |name: Option<&str>| match inputs.as_slice() {
+            [CompiledInput { name: None, .. }] => Some(0),
+            _ => inputs
+                .iter()
+                .position(|input| input.name.as_deref() == name),
+        };
+
+        let outputs = self.outputs();
+        let mut terms = Vec::new();
+        // Input and set index of the sets already in `terms`, to share memberships between
+        // rules
+        let mut term_keys: Vec<(Option<usize>, Option<usize>)> = Vec::new();
+        let mut rules = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.enabled) {
+            let condition = self.compile_condition(&rule.condition, &mut |name, category| {
+                let input = input_index(name);
+                let set = self.term(name, category);
+                let key = (input, self.term_index(name, category));
+                match term_keys.iter().position(|k| *k == key) {
+                    Some(term) => term,
+                    None => {
+                        term_keys.push(key);
+                        terms.push(CompiledTerm {
+                            input,
+                            shape: set.map(|set| set.shape.clone()),
+                        });
+                        terms.len() - 1
+                    }
+                }
+            });
+
+            let mut consequences = Vec::new();
+            for (output, consequent) in sorted_by_output(&rule.consequences) {
+                let index = outputs.iter().position(|o| o == output).expect("listed");
+                let compiled = match (consequent, &self.inference) {
+                    (Consequent::Constant(value), Inference::WeightedAverage) => {
+                        CompiledConsequent::Constant(*value)
+                    }
+                    (Consequent::Linear(function), Inference::WeightedAverage) => {
+                        CompiledConsequent::Linear {
+                            constant: function.constant,
+                            coefficients: function
+                                .coefficients
+                                .iter()
+                                .map(|(name, coefficient)| (input_index(Some(name)), *coefficient))
+                                .collect(),
+                        }
+                    }
+                    (Consequent::Term(category), Inference::Mamdani { .. }) => {
+                        CompiledConsequent::Term(
+                            self.output_variable(output)
+                                .and_then(|variable| variable.term(category))
+                                .map(|set| set.shape.clone()),
+                        )
+                    }
+                    _ => continue,
+                };
+                consequences.push((index, compiled));
+            }
+
+            rules.push(CompiledRule {
+                condition,
+                weight: rule.weight,
+                operators: *self.rule_operators(rule),
+                block: rule.block.filter(|block| *block < self.rule_blocks.len()),
+                consequences,
+            });
+        }
+
+        let mamdani = match self.inference {
+            Inference::WeightedAverage => None,
+            Inference::Mamdani {
+                defuzzifier,
+                resolution,
+            } => Some(Mamdani {
+                defuzzifier,
+                resolution,
+                universes: outputs
+                    .iter()
+                    .map(|output| {
+                        self.output_variable(output)
+                            .map(|variable| (variable.min, variable.max))
+                    })
+                    .collect(),
+            }),
+        };
+
+        let scratch = Scratch {
+            inputs: vec![None; inputs.len()],
+            memberships: vec![0.0; terms.len()],
+            strengths: vec![0.0; rules.len()],
+            sums: vec![(0.0, 0.0); outputs.len()],
+            blocks: vec![0.0; self.rule_blocks.len()],
+            samples: match &mamdani {
+                Some(mamdani) => vec![(0.0, 0.0); mamdani.resolution.max(2)],
+                None => Vec::new(),
+            },
+        };
+
+        CompiledSystem {
+            inputs,
+            outputs,
+            terms,
+            rules,
+            operators: self.operators,
+            mamdani,
+            scratch,
+        }
+    }
+
+    pub(super) fn compile_condition(
+        &self,
+        antecedent: &Antecedent<InputType>,
+        term: &mut impl FnMut(Option<&str>, &InputType) -> usize,
+    ) -> Condition {
+        match antecedent {
+            Antecedent::Is { input, category } => Condition::Term(term(input.as_deref(), category)),
+            Antecedent::And(terms) => Condition::And(
+                terms
+                    .iter()
+                    .map(|t| self.compile_condition(t, term))
+                    .collect(),
+            ),
+            Antecedent::Or(terms) => Condition::Or(
+                terms
+                    .iter()
+                    .map(|t| self.compile_condition(t, term))
+                    .collect(),
+            ),
+            Antecedent::Not(inner) => Condition::Not(Box::new(self.compile_condition(inner, term))),
+            Antecedent::Hedged(hedge, inner) => {
+                Condition::Hedged(*hedge, Box::new(self.compile_condition(inner, term)))
+            }
+        }
+    }
+
+    /// The input variables followed by the other inputs rules read, each once
+    pub(super) fn input_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .input_variables
+            .iter()
+            .map(|variable| variable.name.clone())
+            .collect();
+        for rule in &self.rules {
+            let read = rule
+                .condition
+                .terms()
+                .into_iter()
+                .filter_map(|(name, _)| name);
+            let linear =
+                sorted_by_output(&rule.consequences)
+                    .into_iter()
+                    .flat_map(|(_, consequent)| match consequent {
+                        Consequent::Linear(function) => function
+                            .coefficients
+                            .iter()
+                            .map(|(name, _)| name.as_str())
+                            .collect(),
+                        _ => Vec::new(),
+                    });
+            for name in read.chain(linear) {
+                if !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+            }
+        }
+        names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{FuzzyRule, FuzzySet, LinearConsequent, OutOfRange, RuleBlock, SNorm};
+    use std::collections::HashMap;
+
+    fn agent() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (100.0, 0.0)]))
+                .with_term(FuzzySet::new("high", vec![(0.0, 0.0), (100.0, 1.0)])),
+        );
+        system.add_input_variable(
+            FuzzyVariable::new("distance", 0.0, 50.0)
+                .with_term(FuzzySet::triangle("near", 0.0, 0.0, 30.0))
+                .with_term(FuzzySet::triangle("far", 20.0, 50.0, 50.0))
+                .with_out_of_range(OutOfRange::Ignore),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").and(Antecedent::is("distance", "near")))
+                .with_consequence("flee", 1.0)
+                .with_linear_consequence(
+                    "speed",
+                    LinearConsequent::new(2.0).with_coefficient("distance", 0.1),
+                ),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high").or(!Antecedent::is("distance", "far")))
+                .with_consequence("flee", 0.0)
+                .with_consequence("speed", 1.0)
+                .with_weight(0.5),
+        );
+        system
+    }
+
+    fn assert_matches(
+        system: &FuzzySystem<&'static str, &'static str>,
+        compiled: &mut CompiledSystem<&'static str>,
+        inputs: &[f32],
+    ) {
+        let named: HashMap<&str, f32> = compiled
+            .inputs()
+            .map(|name| name.unwrap())
+            .zip(inputs.iter().copied())
+            .collect();
+        let expected = system.evaluate_inputs(&named);
+        let mut outputs = vec![0.0; compiled.outputs().len()];
+        compiled.evaluate_into(inputs, &mut outputs);
+        for (output, value) in compiled.outputs().iter().zip(&outputs) {
+            match expected.get(output) {
+                Some(expected) => assert!((value - expected).abs() < 1e-5, "{output} {inputs:?}"),
+                None => assert!(value.is_nan(), "{output} {inputs:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_compile_matches_evaluate_inputs() {
+        let system = agent();
+        let mut compiled = system.compile();
+
+        assert_eq!(
+            compiled.inputs().collect::<Vec<_>>(),
+            vec![Some("health"), Some("distance")]
+        );
+        assert_eq!(compiled.outputs().len(), 2);
+        assert!(compiled.output_index(&"speed").is_some());
+        for inputs in [
+            [10.0, 5.0],
+            [50.0, 25.0],
+            [90.0, 45.0],
+            [150.0, 10.0],
+            [10.0, 80.0],
+        ] {
+            assert_matches(&system, &mut compiled, &inputs);
+        }
+    }
+
+    #[test]
+    fn test_compile_single_input() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("low", vec![(0.0, 1.0), (10.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("low").with_consequence("speed", 0.0));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::from("high").very()).with_consequence("speed", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new("high")
+                .with_consequence("jump", 1.0)
+                .with_enabled(false),
+        );
+        let mut compiled = system.compile();
+
+        assert_eq!(compiled.inputs().collect::<Vec<_>>(), vec![None]);
+        let mut outputs = [0.0; 2];
+        for x in [0.0, 2.5, 5.0, 10.0] {
+            compiled.evaluate_into(&[x], &mut outputs);
+            assert!((outputs[0] - system.evaluate(x)["speed"]).abs() < 1e-6);
+            assert!(outputs[1].is_nan());
+        }
+    }
+
+    #[test]
+    fn test_compile_order() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("near", vec![(0.0, 1.0), (10.0, 0.0)]));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("distance", "near"))
+                .with_linear_consequence("d", LinearConsequent::new(0.0).with_coefficient("z", 1.0))
+                .with_linear_consequence("b", LinearConsequent::new(0.0).with_coefficient("y", 1.0))
+                .with_consequence("c", 1.0)
+                .with_linear_consequence(
+                    "a",
+                    LinearConsequent::new(0.0).with_coefficient("x", 1.0),
+                ),
+        );
+        let compiled = system.compile();
+
+        assert_eq!(
+            compiled.inputs().collect::<Vec<_>>(),
+            [Some("distance"), Some("x"), Some("y"), Some("z")]
+        );
+        assert_eq!(compiled.outputs(), ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_compile_mamdani() {
+        let mut system = agent();
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_output_set("flee", FuzzySet::triangle("no", 0.0, 0.0, 0.6));
+        system.add_output_set("flee", FuzzySet::triangle("yes", 0.4, 1.0, 1.0));
+        let block = system.add_rule_block(RuleBlock::new(
+            "sum",
+            LogicOperators::probabilistic().with_aggregation(SNorm::BoundedSum),
+        ));
+        system.rules[0] = system.rules[0].clone().with_output_term("flee", "yes");
+        system.rules[1] = system.rules[1]
+            .clone()
+            .with_output_term("flee", "no")
+            .in_block(block);
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high"))
+                .with_output_term("flee", "no")
+                .in_block(block),
+        );
+        let mut compiled = system.compile();
+
+        for inputs in [[10.0, 5.0], [50.0, 25.0], [90.0, 45.0]] {
+            assert_matches(&system, &mut compiled, &inputs);
+        }
+    }
+
+    #[test]
+    fn test_evaluate_batch() {
+        let system = agent();
+        let mut compiled = system.compile();
+
+        let inputs = [10.0, 5.0, 50.0, 25.0, 90.0, 45.0];
+        let mut outputs = [0.0; 6];
+        compiled.evaluate_batch(&inputs, &mut outputs);
+
+        let mut row = [0.0; 2];
+        for (agent, inputs) in inputs.chunks(2).enumerate() {
+            compiled.evaluate_into(inputs, &mut row);
+            assert_eq!(&outputs[agent * 2..agent * 2 + 2], &row);
+        }
+    }
+}
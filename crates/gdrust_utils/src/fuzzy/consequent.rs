@@ -0,0 +1,119 @@
+/// The `THEN` part of a [`FuzzyRule`](super::FuzzyRule) for a single output
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Consequent<InputType> {
+    /// A crisp value, used by [`Inference::WeightedAverage`](super::Inference::WeightedAverage)
+    Constant(f32),
+    /// A linear function of the inputs (first-order Takagi-Sugeno), used by
+    /// [`Inference::WeightedAverage`](super::Inference::WeightedAverage)
+    Linear(LinearConsequent),
+    /// An output set with this category, used by [`Inference::Mamdani`](super::Inference::Mamdani)
+    Term(InputType),
+}
+
+impl<InputType> Consequent<InputType> {
+    /// The crisp value of a constant consequent
+    pub fn constant(&self) -> Option<f32> {
+        match self {
+            Consequent::Constant(value) => Some(*value),
+            Consequent::Linear(_) | Consequent::Term(_) => None,
+        }
+    }
+
+    /// The output set category of a term consequent
+    pub fn term(&self) -> Option<&InputType> {
+        match self {
+            Consequent::Term(category) => Some(category),
+            Consequent::Constant(_) | Consequent::Linear(_) => None,
+        }
+    }
+}
+
+impl<InputType> From<f32> for Consequent<InputType> {
+    fn from(value: f32) -> Self {
+        Consequent::Constant(value)
+    }
+}
+
+impl<InputType> From<LinearConsequent> for Consequent<InputType> {
+    fn from(function: LinearConsequent) -> Self {
+        Consequent::Linear(function)
+    }
+}
+
+/// `constant + sum(coefficient * input)` over named inputs
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinearConsequent {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub coefficients: Vec<(String, f32)>,
+    pub constant: f32,
+}
+
+impl LinearConsequent {
+    pub fn new(constant: f32) -> Self {
+        Self {
+            coefficients: Vec::new(),
+            constant,
+        }
+    }
+
+    pub fn with_coefficient(mut self, input: impl Into<String>, coefficient: f32) -> Self {
+        self.coefficients.push((input.into(), coefficient));
+        self
+    }
+
+    /// Evaluates the function, or returns `None` if `input` has no value for one of its inputs
+    pub fn value(&self, input: impl Fn(&str) -> Option<f32>) -> Option<f32> {
+        self.coefficients
+            .iter()
+            .try_fold(self.constant, |acc, (name, coefficient)| {
+                input(name).map(|value| acc + coefficient * value)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_value() {
+        let function = LinearConsequent::new(2.0)
+            .with_coefficient("distance", 0.3)
+            .with_coefficient("health", 0.1);
+
+        let value = function
+            .value(|name| match name {
+                "distance" => Some(10.0),
+                "health" => Some(50.0),
+                _ => None,
+            })
+            .unwrap();
+        assert!((value - 10.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_linear_missing_input() {
+        let function = LinearConsequent::new(2.0).with_coefficient("distance", 0.3);
+        assert_eq!(function.value(|_| None), None);
+    }
+
+    #[test]
+    fn test_linear_constant_only() {
+        assert_eq!(LinearConsequent::new(1.5).value(|_| None), Some(1.5));
+    }
+
+    #[test]
+    fn test_accessors() {
+        let constant: Consequent<&str> = Consequent::from(0.5);
+        let linear: Consequent<&str> = Consequent::from(LinearConsequent::new(0.5));
+        let term = Consequent::Term("fast");
+
+        assert_eq!(constant.constant(), Some(0.5));
+        assert_eq!(linear.constant(), None);
+        assert_eq!(term.constant(), None);
+        assert_eq!(term.term(), Some(&"fast"));
+        assert_eq!(linear.term(), None);
+    }
+}
@@ -0,0 +1,465 @@
+use std::hash::Hash;
+
+use super::{inference, FuzzySystem};
+
+/// Settings for [`FuzzySystem::coverage`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CoverageOptions {
+    /// Number of samples across each input universe
+    pub resolution: usize,
+    /// Coverage below this degree counts as weak
+    pub weak_threshold: f32,
+    /// Upper bound for the samples of the combined input grid. The per-input resolution of
+    /// the grid is lowered to stay at or below it, but never below 2, so grids over many
+    /// inputs can exceed it.
+    pub max_samples: usize,
+}
+
+impl Default for CoverageOptions {
+    fn default() -> Self {
+        Self {
+            resolution: 101,
+            weak_threshold: 0.2,
+            max_samples: 100_000,
+        }
+    }
+}
+
+impl CoverageOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution;
+        self
+    }
+
+    pub fn with_weak_threshold(mut self, weak_threshold: f32) -> Self {
+        self.weak_threshold = weak_threshold;
+        self
+    }
+
+    pub fn with_max_samples(mut self, max_samples: usize) -> Self {
+        self.max_samples = max_samples;
+        self
+    }
+}
+
+/// How well a part of an input universe is covered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CoverageLevel {
+    /// Nothing fires
+    Uncovered,
+    /// Something fires, but below [`CoverageOptions::weak_threshold`]
+    Weak,
+    /// Covered well, though some outputs may still be undefined
+    Covered,
+}
+
+/// A run of samples of one input with the same coverage
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageInterval<OutputType> {
+    pub min: f32,
+    pub max: f32,
+    pub level: CoverageLevel,
+    /// Lowest coverage degree in the interval
+    pub strength: f32,
+    /// Outputs [`FuzzySystem::evaluate`] leaves out in the interval
+    pub undefined_outputs: Vec<OutputType>,
+}
+
+/// Problem intervals of a single input universe
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputCoverage<OutputType> {
+    /// The input variable, or `None` for [`FuzzySystem::input_sets`]
+    pub input: Option<String>,
+    pub intervals: Vec<CoverageInterval<OutputType>>,
+}
+
+/// A sample of the combined input grid where rules fire weakly or outputs are undefined
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageGap<OutputType> {
+    pub inputs: Vec<(String, f32)>,
+    pub level: CoverageLevel,
+    /// Strength of the strongest rule
+    pub strength: f32,
+    pub undefined_outputs: Vec<OutputType>,
+}
+
+/// Result of [`FuzzySystem::coverage`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoverageReport<OutputType> {
+    pub inputs: Vec<InputCoverage<OutputType>>,
+    /// Problem samples of the grid over all input variables, for systems with more than one
+    pub gaps: Vec<CoverageGap<OutputType>>,
+}
+
+impl<OutputType> CoverageReport<OutputType> {
+    /// Whether every input is covered well and every output always defined
+    pub fn is_complete(&self) -> bool {
+        self.gaps.is_empty() && self.inputs.iter().all(|input| input.intervals.is_empty())
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    /// Sweeps the input universes to find where no rule fires, where rules fire only weakly
+    /// and where outputs are undefined.
+    ///
+    /// Systems reading a single input, through [`FuzzySystem::input_sets`] or one input
+    /// variable, are swept by rule strength. With several input variables each universe is
+    /// swept by the membership of its best term, and the rules are checked on a grid over
+    /// all of them instead. The universe of [`FuzzySystem::input_sets`] is the union of
+    /// their supports.
+    pub fn coverage(&self, options: &CoverageOptions) -> CoverageReport<OutputType> {
+        let threshold = options.weak_threshold;
+        let mut report = CoverageReport {
+            inputs: Vec::new(),
+            gaps: Vec::new(),
+        };
+
+        if let Some((min, max)) = self
+            .input_sets
+            .iter()
+            .filter_map(|set| set.support())
+            .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)))
+        {
+            let intervals = sweep(min, max, options.resolution, threshold, |x| {
+                self.rule_coverage(&|_| Some(x))
+            });
+            report.inputs.push(InputCoverage {
+                input: None,
+                intervals,
+            });
+        }
+
+        match self.input_variables.as_slice() {
+            [variable] => {
+                let name = variable.name.as_str();
+                let intervals = sweep(
+                    variable.min,
+                    variable.max,
+                    options.resolution,
+                    threshold,
+                    |x| self.rule_coverage(&|input| (input == Some(name)).then_some(x)),
+                );
+                report.inputs.push(InputCoverage {
+                    input: Some(variable.name.clone()),
+                    intervals,
+                });
+            }
+            variables => {
+                for variable in variables {
+                    let intervals = sweep(
+                        variable.min,
+                        variable.max,
+                        options.resolution,
+                        threshold,
+                        |x| {
+                            let strength = variable
+                                .memberships(x)
+                                .into_iter()
+                                .fold(0.0, |acc: f32, (_, mu)| acc.max(mu));
+                            (strength, Vec::new())
+                        },
+                    );
+                    report.inputs.push(InputCoverage {
+                        input: Some(variable.name.clone()),
+                        intervals,
+                    });
+                }
+                if variables.len() > 1 {
+                    report.gaps = self.grid_gaps(options);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Samples the grid over all input variables
+    fn grid_gaps(&self, options: &CoverageOptions) -> Vec<CoverageGap<OutputType>> {
+        let dimensions = self.input_variables.len() as u32;
+        let mut resolution = options.resolution.max(2);
+        while resolution > 2 && resolution.saturating_pow(dimensions) > options.max_samples {
+            resolution -= 1;
+        }
+
+        let axes: Vec<Vec<f32>> = self
+            .input_variables
+            .iter()
+            .map(|v| inference::sample_positions(v.min, v.max, resolution).collect())
+            .collect();
+
+        let mut gaps = Vec::new();
+        let mut indices = vec![0; axes.len()];
+        loop {
+            let inputs: Vec<(String, f32)> = self
+                .input_variables
+                .iter()
+                .zip(&axes)
+                .zip(&indices)
+                .map(|((variable, axis), i)| (variable.name.clone(), axis[*i]))
+                .collect();
+            let (strength, undefined_outputs) = self.rule_coverage(&|input| {
+                inputs
+                    .iter()
+                    .find(|(name, _)| Some(name.as_str()) == input)
+                    .map(|(_, value)| *value)
+            });
+            let level = level(strength, options.weak_threshold);
+            if level != CoverageLevel::Covered || !undefined_outputs.is_empty() {
+                gaps.push(CoverageGap {
+                    inputs,
+                    level,
+                    strength,
+                    undefined_outputs,
+                });
+            }
+
+            // Advance the indices like the digits of a number
+            let Some(axis) = indices.iter().position(|i| i + 1 < resolution) else {
+                break;
+            };
+            indices[axis] += 1;
+            indices[..axis].iter_mut().for_each(|i| *i = 0);
+        }
+
+        gaps
+    }
+
+    /// Strength of the strongest rule and the outputs left undefined
+    fn rule_coverage(
+        &self,
+        raw_input: &dyn Fn(Option<&str>) -> Option<f32>,
+    ) -> (f32, Vec<OutputType>) {
+        let input = |name: Option<&str>| self.resolve_input(name, raw_input(name));
+        let strength = self.rules.iter().fold(0.0, |acc: f32, rule| {
+            let truth = rule
+                .condition
+                .evaluate(self.rule_operators(rule), &mut |name, category| {
+                    self.term_membership(name, input(name), category)
+                });
+            acc.max(rule.strength(truth))
+        });
+
+        let outputs = self.evaluate_with(raw_input, None);
+        let undefined = self
+            .outputs()
+            .into_iter()
+            .filter(|output| !outputs.contains_key(output))
+            .collect();
+
+        (strength, undefined)
+    }
+}
+
+/// Samples `min..=max` and merges consecutive problem samples into intervals
+fn sweep<OutputType: PartialEq>(
+    min: f32,
+    max: f32,
+    resolution: usize,
+    threshold: f32,
+    coverage: impl Fn(f32) -> (f32, Vec<OutputType>),
+) -> Vec<CoverageInterval<OutputType>> {
+    let mut intervals: Vec<CoverageInterval<OutputType>> = Vec::new();
+    let mut extend = false;
+
+    for x in inference::sample_positions(min, max, resolution) {
+        let (strength, undefined_outputs) = coverage(x);
+        let level = level(strength, threshold);
+        if level == CoverageLevel::Covered && undefined_outputs.is_empty() {
+            extend = false;
+            continue;
+        }
+
+        match intervals.last_mut() {
+            Some(interval)
+                if extend
+                    && interval.level == level
+                    && interval.undefined_outputs == undefined_outputs =>
+            {
+                interval.max = x;
+                interval.strength = interval.strength.min(strength);
+            }
+            _ => intervals.push(CoverageInterval {
+                min: x,
+                max: x,
+                level,
+                strength,
+                undefined_outputs,
+            }),
+        }
+        extend = true;
+    }
+
+    intervals
+}
+
+fn level(strength: f32, threshold: f32) -> CoverageLevel {
+    if strength <= 0.0 {
+        CoverageLevel::Uncovered
+    } else if strength < threshold {
+        CoverageLevel::Weak
+    } else {
+        CoverageLevel::Covered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{Antecedent, FuzzyRule, FuzzySet, FuzzyVariable};
+
+    #[test]
+    fn test_input_sets_with_gap() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("easy", vec![(0.0, 1.0), (3.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("hard", vec![(5.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("easy").with_consequence("health", 0.5));
+        system.add_rule(FuzzyRule::new("hard").with_consequence("health", 1.5));
+
+        let report = system.coverage(
+            &CoverageOptions::new()
+                .with_resolution(11)
+                .with_weak_threshold(0.25),
+        );
+
+        assert!(!report.is_complete());
+        assert!(report.gaps.is_empty());
+        assert_eq!(report.inputs.len(), 1);
+        assert_eq!(report.inputs[0].input, None);
+        assert_eq!(
+            report.inputs[0].intervals,
+            vec![
+                CoverageInterval {
+                    min: 3.0,
+                    max: 5.0,
+                    level: CoverageLevel::Uncovered,
+                    strength: 0.0,
+                    undefined_outputs: vec!["health"],
+                },
+                CoverageInterval {
+                    min: 6.0,
+                    max: 6.0,
+                    level: CoverageLevel::Weak,
+                    strength: 0.2,
+                    undefined_outputs: vec![],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_variable_undefined_output() {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (100.0, 0.0)]))
+                .with_term(FuzzySet::new("high", vec![(50.0, 0.0), (100.0, 1.0)])),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low")).with_consequence("flee", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high"))
+                .with_consequence("attack", 1.0)
+                .with_consequence("block", 1.0)
+                .with_consequence("aim", 1.0),
+        );
+
+        let report = system.coverage(&CoverageOptions::new().with_resolution(11));
+        let intervals = &report.inputs[0].intervals;
+
+        assert_eq!(report.inputs[0].input.as_deref(), Some("health"));
+        assert_eq!(intervals.len(), 2);
+        assert_eq!((intervals[0].min, intervals[0].max), (0.0, 50.0));
+        assert_eq!(intervals[0].level, CoverageLevel::Covered);
+        assert_eq!(
+            intervals[0].undefined_outputs,
+            vec!["aim", "attack", "block"]
+        );
+        assert_eq!((intervals[1].min, intervals[1].max), (100.0, 100.0));
+        assert_eq!(intervals[1].undefined_outputs, vec!["flee"]);
+    }
+
+    #[test]
+    fn test_multiple_inputs() {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (100.0, 0.0)]))
+                .with_term(FuzzySet::new("high", vec![(0.0, 0.0), (100.0, 1.0)])),
+        );
+        system.add_input_variable(
+            FuzzyVariable::new("ammo", 0.0, 10.0)
+                .with_term(FuzzySet::new("empty", vec![(0.0, 1.0), (5.0, 0.0)])),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").and(Antecedent::is("ammo", "empty")))
+                .with_consequence("flee", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high")).with_consequence("flee", 0.0),
+        );
+
+        let report = system.coverage(&CoverageOptions::new().with_resolution(3));
+
+        // Terms of a variable are swept on their own
+        assert!(report.inputs[0].intervals.is_empty());
+        assert_eq!(report.inputs[1].input.as_deref(), Some("ammo"));
+        assert_eq!(
+            (
+                report.inputs[1].intervals[0].min,
+                report.inputs[1].intervals[0].max
+            ),
+            (5.0, 10.0)
+        );
+
+        // Only health 0 with ammo above 5 leaves every rule unfired
+        let uncovered: Vec<_> = report
+            .gaps
+            .iter()
+            .filter(|gap| gap.level == CoverageLevel::Uncovered)
+            .map(|gap| gap.inputs.clone())
+            .collect();
+        assert_eq!(
+            uncovered,
+            vec![
+                vec![("health".to_string(), 0.0), ("ammo".to_string(), 5.0)],
+                vec![("health".to_string(), 0.0), ("ammo".to_string(), 10.0)],
+            ]
+        );
+        assert_eq!(report.gaps[0].undefined_outputs, vec!["flee"]);
+    }
+
+    #[test]
+    fn test_grid_resolution_is_capped() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_variable(FuzzyVariable::new("a", 0.0, 1.0));
+        system.add_input_variable(FuzzyVariable::new("b", 0.0, 1.0));
+
+        let report = system.coverage(&CoverageOptions::new().with_max_samples(30));
+        assert_eq!(report.gaps.len(), 25);
+
+        // The grid keeps at least 2 samples per input
+        system.add_input_variable(FuzzyVariable::new("c", 0.0, 1.0));
+        let report = system.coverage(&CoverageOptions::new().with_max_samples(1));
+        assert_eq!(report.gaps.len(), 8);
+    }
+
+    #[test]
+    fn test_complete_coverage() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("low", vec![(0.0, 1.0), (10.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("low").with_consequence("out", 0.0));
+        system.add_rule(FuzzyRule::new("high").with_consequence("out", 1.0));
+
+        assert!(system.coverage(&CoverageOptions::default()).is_complete());
+    }
+}
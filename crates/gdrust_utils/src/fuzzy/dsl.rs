@@ -0,0 +1,841 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use super::{Antecedent, FuzzyRule, FuzzySystem, FuzzyVariable, Hedge};
+
+/// Why a rule could not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    UnexpectedCharacter(char),
+    UnexpectedToken {
+        expected: String,
+        found: String,
+    },
+    UnexpectedEnd {
+        expected: String,
+    },
+    UnknownVariable(String),
+    UnknownTerm {
+        variable: String,
+        term: String,
+    },
+    UnknownOutput(String),
+    /// The output already has a consequence in the same rule
+    DuplicateOutput(String),
+}
+
+/// A rule syntax or vocabulary error at a 1-based line and column
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnexpectedCharacter(c) => write!(f, "unexpected character `{c}`"),
+            ParseErrorKind::UnexpectedToken { expected, found } => {
+                write!(f, "expected {expected}, found `{found}`")
+            }
+            ParseErrorKind::UnexpectedEnd { expected } => {
+                write!(f, "expected {expected}, found end of rule")
+            }
+            ParseErrorKind::UnknownVariable(name) => write!(f, "unknown input variable `{name}`"),
+            ParseErrorKind::UnknownTerm { variable, term } => {
+                write!(f, "unknown term `{term}` for variable `{variable}`")
+            }
+            ParseErrorKind::UnknownOutput(name) => write!(f, "unknown output variable `{name}`"),
+            ParseErrorKind::DuplicateOutput(name) => {
+                write!(f, "output variable `{name}` is already set by this rule")
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parses human-readable rules like
+/// `IF health IS low AND ammo IS NOT empty THEN aggression IS 0.2` against declared
+/// variables and terms.
+///
+/// Keywords (`IF`, `THEN`, `IS`, `AND`, `OR`, `NOT`, and the hedges `VERY`, `EXTREMELY`,
+/// `SOMEWHAT`, `INDEED`) are case-insensitive, names are not. `AND` binds stronger than `OR`
+/// and parentheses group expressions. `NOT` negates with the
+/// [negation](super::LogicOperators::not) of the system, whether it comes before the input
+/// or after `IS`, so `ammo IS NOT empty` reads as `NOT ammo IS empty`. A consequence is
+/// either a number, for [`Inference::WeightedAverage`](super::Inference::WeightedAverage),
+/// or an output term, for [`Inference::Mamdani`](super::Inference::Mamdani), and each output
+/// can only appear once per rule. A trailing `WITH 0.5` sets the
+/// [weight](FuzzyRule::weight) of the rule.
+#[derive(Debug, Clone)]
+pub struct RuleParser<InputType, OutputType> {
+    inputs: Vec<(String, Terms<InputType>)>,
+    outputs: Vec<(String, OutputType, Terms<InputType>)>,
+}
+
+/// Term names and the categories they stand for
+type Terms<InputType> = Vec<(String, InputType)>;
+
+impl<InputType, OutputType> Default for RuleParser<InputType, OutputType> {
+    fn default() -> Self {
+        Self {
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+impl<InputType, OutputType> RuleParser<InputType, OutputType>
+where
+    InputType: Clone,
+    OutputType: Clone + Eq + std::hash::Hash,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares an input variable and the categories its term names stand for
+    pub fn with_input<'a>(
+        mut self,
+        name: impl Into<String>,
+        terms: impl IntoIterator<Item = (&'a str, InputType)>,
+    ) -> Self {
+        let terms = terms.into_iter().map(|(t, c)| (t.to_string(), c)).collect();
+        self.inputs.push((name.into(), terms));
+        self
+    }
+
+    /// Declares an output variable, and for Mamdani rules the categories of its term names
+    pub fn with_output<'a>(
+        mut self,
+        name: impl Into<String>,
+        output: OutputType,
+        terms: impl IntoIterator<Item = (&'a str, InputType)>,
+    ) -> Self {
+        let terms = terms.into_iter().map(|(t, c)| (t.to_string(), c)).collect();
+        self.outputs.push((name.into(), output, terms));
+        self
+    }
+
+    /// Declares the input and output variables of `system`, as well as the outputs of its
+    /// rules, naming terms and outputs by their [`Display`] output
+    pub fn for_system(system: &FuzzySystem<InputType, OutputType>) -> Self
+    where
+        InputType: Display + PartialEq,
+        OutputType: Display,
+    {
+        let named_terms = |variable: &FuzzyVariable<InputType>| {
+            variable
+                .terms
+                .iter()
+                .map(|set| (set.category.to_string(), set.category.clone()))
+                .collect()
+        };
+
+        let mut parser = Self::new();
+        for variable in &system.input_variables {
+            parser
+                .inputs
+                .push((variable.name.clone(), named_terms(variable)));
+        }
+        for (output, variable) in &system.output_variables {
+            parser
+                .outputs
+                .push((output.to_string(), output.clone(), named_terms(variable)));
+        }
+        for output in system
+            .rules
+            .iter()
+            .flat_map(|rule| rule.consequences.keys())
+        {
+            if parser.outputs.iter().all(|(_, o, _)| o != output) {
+                parser
+                    .outputs
+                    .push((output.to_string(), output.clone(), Vec::new()));
+            }
+        }
+        parser
+    }
+
+    /// Parses a single rule
+    pub fn parse_rule(&self, text: &str) -> Result<FuzzyRule<InputType, OutputType>, ParseError> {
+        self.parse_rule_at(text, 1, 1)
+    }
+
+    /// Parses one rule per line. Blank lines and lines starting with `#` are skipped.
+    pub fn parse_rules(
+        &self,
+        text: &str,
+    ) -> Result<Vec<FuzzyRule<InputType, OutputType>>, ParseError> {
+        text.lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            })
+            .map(|(index, line)| self.parse_rule_at(line, index + 1, 1))
+            .collect()
+    }
+
+    /// Parses a rule that starts at `line` and `column` of a larger document. The rule may
+    /// span several lines.
+    pub(crate) fn parse_rule_at(
+        &self,
+        text: &str,
+        line: usize,
+        column: usize,
+    ) -> Result<FuzzyRule<InputType, OutputType>, ParseError> {
+        let tokens = tokenize(text, line, column)?;
+        let mut parser = Parser {
+            vocabulary: self,
+            tokens: &tokens,
+            position: 0,
+            end: Position::new(line, column).advance(text),
+        };
+        let rule = parser.rule()?;
+        parser.expect_end()?;
+        Ok(rule)
+    }
+
+    fn input_term(&self, variable: &str, term: &str) -> Option<Option<&InputType>> {
+        self.inputs
+            .iter()
+            .find(|(name, _)| name == variable)
+            .map(|(_, terms)| find_term(terms, term))
+    }
+
+    fn output(&self, name: &str) -> Option<(&OutputType, &[(String, InputType)])> {
+        self.outputs
+            .iter()
+            .find(|(n, _, _)| n == name)
+            .map(|(_, output, terms)| (output, terms.as_slice()))
+    }
+}
+
+fn find_term<'a, T>(terms: &'a [(String, T)], name: &str) -> Option<&'a T> {
+    terms.iter().find(|(t, _)| t == name).map(|(_, c)| c)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Number(f32),
+    Open,
+    Close,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    line: usize,
+    column: usize,
+}
+
+/// Line and column of a character, advancing across line breaks
+#[derive(Debug, Clone, Copy)]
+struct Position {
+    line: usize,
+    column: usize,
+}
+
+impl Position {
+    fn new(line: usize, column: usize) -> Self {
+        Self { line, column }
+    }
+
+    fn step(self, c: char) -> Self {
+        if c == '\n' {
+            Self::new(self.line + 1, 1)
+        } else {
+            Self::new(self.line, self.column + 1)
+        }
+    }
+
+    fn advance(self, text: &str) -> (usize, usize) {
+        let end = text.chars().fold(self, Position::step);
+        (end.line, end.column)
+    }
+}
+
+fn tokenize(text: &str, first_line: usize, first_column: usize) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut position = Position::new(first_line, first_column);
+
+    while i < chars.len() {
+        let c = chars[i];
+        let Position { line, column } = position;
+        let start = i;
+
+        if c.is_whitespace() {
+            i += 1;
+            position = position.step(c);
+            continue;
+        }
+
+        let kind = if c == '(' {
+            i += 1;
+            TokenKind::Open
+        } else if c == ')' {
+            i += 1;
+            TokenKind::Close
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            TokenKind::Word(chars[start..i].iter().collect())
+        } else if c.is_ascii_digit() || c == '-' || c == '+' || c == '.' {
+            i += 1;
+            while i < chars.len()
+                && (chars[i].is_ascii_digit()
+                    || chars[i] == '.'
+                    || matches!(chars[i], 'e' | 'E')
+                    || (matches!(chars[i], '-' | '+') && matches!(chars[i - 1], 'e' | 'E')))
+            {
+                i += 1;
+            }
+            let number: String = chars[start..i].iter().collect();
+            match number.parse() {
+                Ok(value) => TokenKind::Number(value),
+                Err(_) => {
+                    return Err(ParseError {
+                        line,
+                        column,
+                        kind: ParseErrorKind::UnexpectedToken {
+                            expected: "a number".to_string(),
+                            found: number,
+                        },
+                    })
+                }
+            }
+        } else {
+            return Err(ParseError {
+                line,
+                column,
+                kind: ParseErrorKind::UnexpectedCharacter(c),
+            });
+        };
+
+        let text: String = chars[start..i].iter().collect();
+        position = Position::new(line, column + i - start);
+        tokens.push(Token {
+            kind,
+            text,
+            line,
+            column,
+        });
+    }
+
+    Ok(tokens)
+}
+
+struct Parser<'a, InputType, OutputType> {
+    vocabulary: &'a RuleParser<InputType, OutputType>,
+    tokens: &'a [Token],
+    position: usize,
+    /// Line and column just past the end of the rule
+    end: (usize, usize),
+}
+
+impl<'a, InputType, OutputType> Parser<'a, InputType, OutputType>
+where
+    InputType: Clone,
+    OutputType: Clone + Eq + std::hash::Hash,
+{
+    fn rule(&mut self) -> Result<FuzzyRule<InputType, OutputType>, ParseError> {
+        self.expect_keyword("IF")?;
+        let condition = self.or_expression()?;
+        self.expect_keyword("THEN")?;
+
+        let mut rule = FuzzyRule::new(condition);
+        loop {
+            rule = self.consequence(rule)?;
+            if !self.accept_keyword("AND") {
+                break;
+            }
+        }
+        if self.accept_keyword("WITH") {
+            let token = self.next_or_end("a rule weight")?;
+            match token.kind {
+                TokenKind::Number(weight) if (0.0..=1.0).contains(&weight) => {
+                    rule = rule.with_weight(weight);
+                }
+                _ => return Err(unexpected(token, "a rule weight in 0..=1")),
+            }
+        }
+        Ok(rule)
+    }
+
+    fn or_expression(&mut self) -> Result<Antecedent<InputType>, ParseError> {
+        let mut expression = self.and_expression()?;
+        while self.accept_keyword("OR") {
+            expression = expression.or(self.and_expression()?);
+        }
+        Ok(expression)
+    }
+
+    fn and_expression(&mut self) -> Result<Antecedent<InputType>, ParseError> {
+        let mut expression = self.unary()?;
+        while self.accept_keyword("AND") {
+            expression = expression.and(self.unary()?);
+        }
+        Ok(expression)
+    }
+
+    fn unary(&mut self) -> Result<Antecedent<InputType>, ParseError> {
+        if self.accept_keyword("NOT") {
+            return Ok(!self.unary()?);
+        }
+        if self.accept(&TokenKind::Open) {
+            let expression = self.or_expression()?;
+            self.expect(&TokenKind::Close, "`)`")?;
+            return Ok(expression);
+        }
+
+        let variable = self.expect_name("an input variable")?;
+        self.expect_keyword("IS")?;
+        let hedges = self.hedges();
+        let term = self.expect_name("a term")?;
+
+        let category = match self.vocabulary.input_term(&variable.text, &term.text) {
+            None => return Err(error_at(variable, unknown_variable(variable))),
+            Some(None) => return Err(error_at(term, unknown_term(variable, term))),
+            Some(Some(category)) => category.clone(),
+        };
+
+        Ok(hedges.into_iter().rev().fold(
+            Antecedent::is(variable.text.clone(), category),
+            |a, h| match h {
+                Hedge::Not => !a,
+                h => a.hedged(h),
+            },
+        ))
+    }
+
+    fn hedges(&mut self) -> Vec<Hedge> {
+        let mut hedges = Vec::new();
+        while let Some(token) = self.peek() {
+            let hedge = match keyword(token).as_deref() {
+                Some("VERY") => Hedge::Very,
+                Some("EXTREMELY") => Hedge::Extremely,
+                Some("SOMEWHAT") => Hedge::Somewhat,
+                Some("INDEED") => Hedge::Indeed,
+                Some("NOT") => Hedge::Not,
+                _ => break,
+            };
+            hedges.push(hedge);
+            self.position += 1;
+        }
+        hedges
+    }
+
+    fn consequence(
+        &mut self,
+        rule: FuzzyRule<InputType, OutputType>,
+    ) -> Result<FuzzyRule<InputType, OutputType>, ParseError> {
+        let name = self.expect_name("an output variable")?;
+        let vocabulary = self.vocabulary;
+        let (output, terms) = vocabulary
+            .output(&name.text)
+            .ok_or_else(|| error_at(name, ParseErrorKind::UnknownOutput(name.text.clone())))?;
+        if rule.consequences.contains_key(output) {
+            return Err(error_at(
+                name,
+                ParseErrorKind::DuplicateOutput(name.text.clone()),
+            ));
+        }
+        self.expect_keyword("IS")?;
+
+        let token = self.next_or_end("a number or an output term")?;
+        match &token.kind {
+            TokenKind::Number(value) => Ok(rule.with_consequence(output.clone(), *value)),
+            TokenKind::Word(term) => match find_term(terms, term) {
+                Some(category) => Ok(rule.with_output_term(output.clone(), category.clone())),
+                None => Err(error_at(token, unknown_term(name, token))),
+            },
+            _ => Err(unexpected(token, "a number or an output term")),
+        }
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn accept_keyword(&mut self, word: &str) -> bool {
+        let found = self
+            .peek()
+            .is_some_and(|token| keyword(token).as_deref() == Some(word));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn accept(&mut self, kind: &TokenKind) -> bool {
+        let found = self.peek().is_some_and(|token| &token.kind == kind);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn next_or_end(&mut self, expected: &str) -> Result<&'a Token, ParseError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token)
+            }
+            None => Err(ParseError {
+                line: self.end.0,
+                column: self.end.1,
+                kind: ParseErrorKind::UnexpectedEnd {
+                    expected: expected.to_string(),
+                },
+            }),
+        }
+    }
+
+    fn expect(&mut self, kind: &TokenKind, expected: &str) -> Result<(), ParseError> {
+        let token = self.next_or_end(expected)?;
+        if &token.kind == kind {
+            Ok(())
+        } else {
+            Err(unexpected(token, expected))
+        }
+    }
+
+    fn expect_keyword(&mut self, word: &str) -> Result<(), ParseError> {
+        let expected = format!("`{word}`");
+        let token = self.next_or_end(&expected)?;
+        if keyword(token).as_deref() == Some(word) {
+            Ok(())
+        } else {
+            Err(unexpected(token, &expected))
+        }
+    }
+
+    fn expect_name(&mut self, expected: &str) -> Result<&'a Token, ParseError> {
+        let token = self.next_or_end(expected)?;
+        match token.kind {
+            TokenKind::Word(_) if keyword(token).is_none() => Ok(token),
+            _ => Err(unexpected(token, expected)),
+        }
+    }
+
+    fn expect_end(&self) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(token) => Err(unexpected(token, "end of rule")),
+            None => Ok(()),
+        }
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "IF",
+    "THEN",
+    "IS",
+    "AND",
+    "OR",
+    "NOT",
+    "VERY",
+    "EXTREMELY",
+    "SOMEWHAT",
+    "INDEED",
+    "WITH",
+];
+
+/// The upper case keyword a token stands for, if any
+fn keyword(token: &Token) -> Option<String> {
+    match &token.kind {
+        TokenKind::Word(word) => {
+            let upper = word.to_uppercase();
+            KEYWORDS.contains(&upper.as_str()).then_some(upper)
+        }
+        _ => None,
+    }
+}
+
+fn error_at(token: &Token, kind: ParseErrorKind) -> ParseError {
+    ParseError {
+        line: token.line,
+        column: token.column,
+        kind,
+    }
+}
+
+fn unexpected(token: &Token, expected: &str) -> ParseError {
+    error_at(
+        token,
+        ParseErrorKind::UnexpectedToken {
+            expected: expected.to_string(),
+            found: token.text.clone(),
+        },
+    )
+}
+
+fn unknown_variable(variable: &Token) -> ParseErrorKind {
+    ParseErrorKind::UnknownVariable(variable.text.clone())
+}
+
+fn unknown_term(variable: &Token, term: &Token) -> ParseErrorKind {
+    ParseErrorKind::UnknownTerm {
+        variable: variable.text.clone(),
+        term: term.text.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{Consequent, FuzzySet};
+    use std::collections::HashMap;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Term {
+        Low,
+        High,
+        Empty,
+        Full,
+        Calm,
+        Angry,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Output {
+        Aggression,
+    }
+
+    fn parser() -> RuleParser<Term, Output> {
+        RuleParser::new()
+            .with_input("health", [("low", Term::Low), ("high", Term::High)])
+            .with_input("ammo", [("empty", Term::Empty), ("full", Term::Full)])
+            .with_output(
+                "aggression",
+                Output::Aggression,
+                [("calm", Term::Calm), ("angry", Term::Angry)],
+            )
+    }
+
+    #[test]
+    fn test_parse_simple_rule() {
+        let rule = parser()
+            .parse_rule("IF health IS low AND ammo IS empty THEN aggression IS 0.2")
+            .unwrap();
+
+        assert_eq!(
+            rule.condition,
+            Antecedent::is("health", Term::Low).and(Antecedent::is("ammo", Term::Empty))
+        );
+        assert_eq!(
+            rule.consequences.get(&Output::Aggression),
+            Some(&Consequent::Constant(0.2))
+        );
+    }
+
+    #[test]
+    fn test_parse_precedence_and_parentheses() {
+        let parser = parser();
+
+        let rule = parser
+            .parse_rule("if health is low or health is high and ammo is full then aggression is 1")
+            .unwrap();
+        assert_eq!(
+            rule.condition,
+            Antecedent::is("health", Term::Low)
+                .or(Antecedent::is("health", Term::High).and(Antecedent::is("ammo", Term::Full)))
+        );
+
+        let rule = parser
+            .parse_rule(
+                "IF (health IS low OR health IS high) AND ammo IS full THEN aggression IS 1",
+            )
+            .unwrap();
+        assert_eq!(
+            rule.condition,
+            Antecedent::is("health", Term::Low)
+                .or(Antecedent::is("health", Term::High))
+                .and(Antecedent::is("ammo", Term::Full))
+        );
+    }
+
+    #[test]
+    fn test_parse_not_and_hedges() {
+        let rule = parser()
+            .parse_rule(
+                "IF NOT health IS very low AND ammo IS NOT somewhat full THEN aggression IS 1",
+            )
+            .unwrap();
+
+        assert_eq!(
+            rule.condition,
+            (!Antecedent::is("health", Term::Low).very())
+                .and(!Antecedent::is("ammo", Term::Full).somewhat())
+        );
+    }
+
+    #[test]
+    fn test_parse_output_terms_and_multiple_consequences() {
+        let parser = RuleParser::new()
+            .with_input("health", [("low", "low")])
+            .with_output("aggression", "aggression", [("calm", "calm")])
+            .with_output("speed", "speed", []);
+
+        let rule = parser
+            .parse_rule("IF health IS low THEN aggression IS calm AND speed IS -1.5e1")
+            .unwrap();
+        assert_eq!(
+            rule.consequences.get("aggression"),
+            Some(&Consequent::Term("calm"))
+        );
+        assert_eq!(
+            rule.consequences.get("speed"),
+            Some(&Consequent::Constant(-15.0))
+        );
+    }
+
+    #[test]
+    fn test_parse_rules_reports_lines() {
+        let text = "# aggression rules\n\
+                    IF health IS low THEN aggression IS 0.2\n\
+                    \n\
+                    IF ammo IS emty THEN aggression IS 0.1\n";
+
+        let error = parser().parse_rules(text).unwrap_err();
+        assert_eq!(error.line, 4);
+        assert_eq!(error.column, 12);
+        assert_eq!(
+            error.kind,
+            ParseErrorKind::UnknownTerm {
+                variable: "ammo".to_string(),
+                term: "emty".to_string()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "line 4, column 12: unknown term `emty` for variable `ammo`"
+        );
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = parser()
+            .parse_rules(
+                "IF health IS low THEN aggression IS 0.2\nIF health IS high THEN aggression IS angry",
+            )
+            .unwrap();
+        assert_eq!(rules.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let parser = parser();
+        let error = |text: &str| parser.parse_rule(text).unwrap_err();
+
+        let e = error("IF mana IS low THEN aggression IS 1");
+        assert_eq!(
+            (e.column, e.kind),
+            (4, ParseErrorKind::UnknownVariable("mana".into()))
+        );
+
+        let e = error("IF health IS low THEN rage IS 1");
+        assert_eq!(
+            (e.column, e.kind),
+            (23, ParseErrorKind::UnknownOutput("rage".into()))
+        );
+
+        let e = error("IF health low THEN aggression IS 1");
+        assert_eq!(e.column, 11);
+        assert_eq!(
+            e.kind,
+            ParseErrorKind::UnexpectedToken {
+                expected: "`IS`".into(),
+                found: "low".into()
+            }
+        );
+
+        let e = error("IF health IS low THEN aggression IS");
+        assert_eq!(e.column, 36);
+        assert!(matches!(e.kind, ParseErrorKind::UnexpectedEnd { .. }));
+
+        let e = error("IF (health IS low THEN aggression IS 1");
+        assert_eq!(e.column, 19);
+
+        let e = error("IF health IS low THEN aggression IS 1 extra");
+        assert_eq!(e.column, 39);
+
+        let e = error("IF health IS low THEN aggression IS 0.2 AND aggression IS 0.9");
+        assert_eq!(
+            (e.column, e.kind),
+            (45, ParseErrorKind::DuplicateOutput("aggression".into()))
+        );
+
+        let e = error("IF health IS low! THEN aggression IS 1");
+        assert_eq!(
+            (e.column, e.kind),
+            (17, ParseErrorKind::UnexpectedCharacter('!'))
+        );
+    }
+
+    #[test]
+    fn test_for_system() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (50.0, 0.0)]))
+                .with_term(FuzzySet::new("high", vec![(50.0, 0.0), (100.0, 1.0)])),
+        );
+        system.add_output_set("flee", FuzzySet::new("yes", vec![(0.0, 0.0), (1.0, 1.0)]));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high")).with_consequence("idle", 1.0),
+        );
+
+        let rules = RuleParser::for_system(&system)
+            .parse_rules(
+                "IF health IS low THEN flee IS 1.0\n\
+                 IF health IS high THEN flee IS 0.0 AND idle IS 1.0",
+            )
+            .unwrap();
+        for rule in rules {
+            system.add_rule(rule);
+        }
+
+        let result = system.evaluate_inputs(&HashMap::from([("health", 25.0)]));
+        assert_eq!(result.get("flee"), Some(&1.0));
+    }
+
+    #[test]
+    fn test_parse_rule_across_lines() {
+        let text = "IF health IS low\n    AND ammo IS emty\n    THEN aggression IS 0.2";
+        let error = parser().parse_rule_at(text, 10, 14).unwrap_err();
+        assert_eq!((error.line, error.column), (11, 17));
+
+        let error = parser()
+            .parse_rule_at("IF health IS low\n  THEN aggression IS", 3, 5)
+            .unwrap_err();
+        assert_eq!((error.line, error.column), (4, 21));
+        assert!(matches!(error.kind, ParseErrorKind::UnexpectedEnd { .. }));
+    }
+
+    #[test]
+    fn test_parse_rule_weight() {
+        let rule = parser()
+            .parse_rule("IF health IS low THEN aggression IS calm with 0.5")
+            .unwrap();
+        assert_eq!(rule.weight, 0.5);
+
+        let error = parser()
+            .parse_rule("IF health IS low THEN aggression IS 0.2 WITH 2")
+            .unwrap_err();
+        assert_eq!(error.column, 46);
+
+        let error = parser()
+            .parse_rule("IF health IS low THEN aggression IS 0.2 WITH")
+            .unwrap_err();
+        assert!(matches!(error.kind, ParseErrorKind::UnexpectedEnd { .. }));
+    }
+}
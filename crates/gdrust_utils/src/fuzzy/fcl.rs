@@ -0,0 +1,1618 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+
+use super::{
+    inference, Antecedent, Consequent, Defuzzifier, FuzzyRule, FuzzySet, FuzzySystem,
+    FuzzyVariable, Hedge, Implication, Inference, LogicOperators, Negation, ParseError,
+    ParseErrorKind, RuleBlock, RuleParser, SNorm, Shape, TNorm,
+};
+
+/// Number of points used to write [`Shape::Custom`] terms as polylines
+const CUSTOM_SHAPE_POINTS: usize = 21;
+
+impl FuzzySystem<String, String> {
+    /// Reads an IEC 61131-7 Fuzzy Control Language `FUNCTION_BLOCK`.
+    ///
+    /// Terms can be polylines `(x, y) ...`, singletons, or the `trian`, `trape`, `gauss`,
+    /// `gbell` and `sigm` shapes known from jFuzzyLogic. The defuzzification methods `COG`,
+    /// `COA`, `MM`, `LM` and `RM` select [`Inference::Mamdani`], while `COGS` turns the
+    /// singleton output terms into constant consequences for [`Inference::WeightedAverage`].
+    /// All outputs have to share the same method, which is `COG` if a `DEFUZZIFY` section
+    /// leaves it out. Rules are parsed like [`RuleParser`] rules, and every `RULEBLOCK`
+    /// becomes a [`RuleBlock`] with its own operators. `ACCU` in a `DEFUZZIFY` section sets
+    /// the aggregation of the system, which combines the blocks. `DEFAULT` values are read
+    /// but not used.
+    pub fn from_fcl(text: &str) -> Result<Self, ParseError> {
+        FclParser::new(text)?.function_block()
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq + Display,
+    OutputType: Clone + Eq + Hash + Display,
+{
+    /// Writes the system as an FCL `FUNCTION_BLOCK` called `name`, naming variables, terms
+    /// and outputs by their [`Display`] output. Custom shapes are written as polylines.
+    /// Under [`Inference::WeightedAverage`] constant consequences become singleton output
+    /// terms with `METHOD : COGS`.
+    pub fn to_fcl(&self, name: &str) -> Result<String, FclExportError> {
+        // Rules without a block go into a block of their own with the system's operators
+        let block_of = |rule: &FuzzyRule<InputType, OutputType>| {
+            rule.block.filter(|block| *block < self.rule_blocks.len())
+        };
+        let mut blocks: Vec<(&str, &LogicOperators, Option<usize>)> = Vec::new();
+        if self.rule_blocks.is_empty() || self.rules.iter().any(|rule| block_of(rule).is_none()) {
+            blocks.push(("rules", &self.operators, None));
+        }
+        for (index, block) in self.rule_blocks.iter().enumerate() {
+            blocks.push((&block.name, &block.operators, Some(index)));
+        }
+        for (_, operators, _) in &blocks {
+            if operators.not != Negation::Standard {
+                return Err(FclExportError::Negation(operators.not));
+            }
+        }
+        self.check_finite()?;
+        // Combines the blocks, so it only matters with several of them
+        let accumulation = match (blocks.len() > 1, self.inference) {
+            (true, Inference::Mamdani { .. }) if self.operators.aggregation != SNorm::Max => {
+                Some(accumulation_name(self.operators.aggregation)?)
+            }
+            _ => None,
+        };
+
+        let mut outputs: Vec<String> = self
+            .output_variables
+            .iter()
+            .map(|(output, _)| output.to_string())
+            .collect();
+        for rule in &self.rules {
+            for output in sorted_outputs(&rule.consequences) {
+                if !outputs.contains(&output.to_string()) {
+                    outputs.push(output.to_string());
+                }
+            }
+        }
+
+        let singletons = match self.inference {
+            Inference::WeightedAverage => Some(self.singleton_terms()),
+            Inference::Mamdani { .. } => None,
+        };
+
+        let mut fcl = format!("FUNCTION_BLOCK {name}\n\nVAR_INPUT\n");
+        for variable in &self.input_variables {
+            fcl.push_str(&format!("    {} : REAL;\n", variable.name));
+        }
+        fcl.push_str("END_VAR\n\nVAR_OUTPUT\n");
+        for output in &outputs {
+            fcl.push_str(&format!("    {output} : REAL;\n"));
+        }
+        fcl.push_str("END_VAR\n");
+
+        for variable in &self.input_variables {
+            fcl.push_str(&format!("\nFUZZIFY {}\n", variable.name));
+            write_terms(&mut fcl, variable);
+            fcl.push_str("END_FUZZIFY\n");
+        }
+
+        for output in &outputs {
+            fcl.push_str(&format!("\nDEFUZZIFY {output}\n"));
+            let variable = self
+                .output_variables
+                .iter()
+                .find(|(o, _)| &o.to_string() == output)
+                .map(|(_, variable)| variable);
+            match (&singletons, self.inference) {
+                (Some(singletons), _) => {
+                    let terms = &singletons[output];
+                    // Outputs without constants only have ignored consequences, so any range
+                    // does
+                    let (min, max) = match variable {
+                        Some(variable) => (variable.min, variable.max),
+                        None => terms
+                            .iter()
+                            .map(|(_, value)| *value)
+                            .fold(None, |range, value| match range {
+                                Some((min, max)) => Some((value.min(min), value.max(max))),
+                                None => Some((value, value)),
+                            })
+                            .unwrap_or((0.0, 0.0)),
+                    };
+                    fcl.push_str(&format!("    RANGE := ({min} .. {max});\n"));
+                    for (term, value) in terms {
+                        fcl.push_str(&format!("    TERM {term} := {value};\n"));
+                    }
+                    fcl.push_str("    METHOD : COGS;\n");
+                }
+                (None, Inference::Mamdani { defuzzifier, .. }) => {
+                    if let Some(variable) = variable {
+                        write_terms(&mut fcl, variable);
+                    }
+                    if let Some(accumulation) = accumulation {
+                        fcl.push_str(&format!("    ACCU : {accumulation};\n"));
+                    }
+                    fcl.push_str(&format!("    METHOD : {};\n", method_name(defuzzifier)));
+                }
+                (None, Inference::WeightedAverage) => unreachable!(),
+            }
+            fcl.push_str("END_DEFUZZIFY\n");
+        }
+
+        let mut index = 0;
+        for (block_name, operators, block) in blocks {
+            fcl.push_str(&format!("\nRULEBLOCK {block_name}\n"));
+            fcl.push_str(&format!(
+                "    AND : {};\n    OR : {};\n    ACT : {};\n    ACCU : {};\n",
+                tnorm_name(operators.and)?,
+                snorm_name(operators.or)?,
+                match operators.implication {
+                    Implication::Min => "MIN",
+                    Implication::Product => "PROD",
+                },
+                accumulation_name(operators.aggregation)?,
+            ));
+            // Disabled rules never fire, so leaving them out doesn't change the outputs
+            let rules = self
+                .rules
+                .iter()
+                .filter(|rule| rule.enabled && block_of(rule) == block);
+            for rule in rules {
+                if let Some(line) = self.rule_to_fcl(rule, singletons.as_ref(), index + 1)? {
+                    index += 1;
+                    fcl.push_str(&line);
+                }
+            }
+            fcl.push_str("END_RULEBLOCK\n");
+        }
+        fcl.push_str("\nEND_FUNCTION_BLOCK\n");
+
+        Ok(fcl)
+    }
+
+    /// The `RULE` line of a rule, or `None` if the inference mode uses none of its
+    /// consequences
+    fn rule_to_fcl(
+        &self,
+        rule: &FuzzyRule<InputType, OutputType>,
+        singletons: Option<&HashMap<String, Vec<(String, f32)>>>,
+        index: usize,
+    ) -> Result<Option<String>, FclExportError> {
+        let mut consequences = Vec::new();
+        for output in sorted_outputs(&rule.consequences) {
+            let name = output.to_string();
+            match (&rule.consequences[output], singletons) {
+                (Consequent::Constant(value), Some(singletons)) => {
+                    let (term, _) = singletons[&name]
+                        .iter()
+                        .find(|(_, v)| v == value)
+                        .expect("every constant has a singleton term");
+                    consequences.push(format!("{name} IS {term}"));
+                }
+                (Consequent::Linear(_), Some(_)) => {
+                    return Err(FclExportError::LinearConsequence(name))
+                }
+                (Consequent::Term(category), None) => {
+                    consequences.push(format!("{name} IS {category}"))
+                }
+                // Ignored by the inference mode, so they don't change the outputs
+                _ => {}
+            }
+        }
+        if consequences.is_empty() {
+            return Ok(None);
+        }
+
+        let weight = if rule.weight == 1.0 {
+            String::new()
+        } else {
+            format!(" WITH {}", rule.weight)
+        };
+        Ok(Some(format!(
+            "    RULE {index} : IF {} THEN {}{weight};\n",
+            self.antecedent_to_fcl(&rule.condition)?,
+            consequences.join(" AND ")
+        )))
+    }
+
+    /// Singleton output terms for the constant consequences of each output, reusing the
+    /// singleton terms of its output variable
+    fn singleton_terms(&self) -> HashMap<String, Vec<(String, f32)>> {
+        let mut terms: HashMap<String, Vec<(String, f32)>> = self
+            .output_variables
+            .iter()
+            .map(|(output, variable)| {
+                let singletons = variable
+                    .terms
+                    .iter()
+                    .filter_map(|set| match set.shape {
+                        Shape::Singleton(value) => Some((set.category.to_string(), value)),
+                        _ => None,
+                    })
+                    .collect();
+                (output.to_string(), singletons)
+            })
+            .collect();
+
+        for rule in &self.rules {
+            for output in sorted_outputs(&rule.consequences) {
+                let output_terms = terms.entry(output.to_string()).or_default();
+                if let Consequent::Constant(value) = rule.consequences[output] {
+                    if output_terms.iter().all(|(_, v)| *v != value) {
+                        let term = format!("{output}_{}", output_terms.len() + 1);
+                        output_terms.push((term, value));
+                    }
+                }
+            }
+        }
+        terms
+    }
+
+    fn antecedent_to_fcl(
+        &self,
+        antecedent: &Antecedent<InputType>,
+    ) -> Result<String, FclExportError> {
+        let grouped = |antecedent: &Antecedent<InputType>| {
+            let text = self.antecedent_to_fcl(antecedent)?;
+            Ok(match antecedent {
+                Antecedent::And(terms) | Antecedent::Or(terms) if terms.len() > 1 => {
+                    format!("({text})")
+                }
+                _ => text,
+            })
+        };
+
+        match antecedent {
+            Antecedent::Is { input, category } => {
+                let input = self.fcl_input(input.as_deref())?;
+                Ok(format!("{input} IS {category}"))
+            }
+            Antecedent::And(terms) if terms.is_empty() => Err(FclExportError::EmptyGroup),
+            Antecedent::Or(terms) if terms.is_empty() => Err(FclExportError::EmptyGroup),
+            Antecedent::And(terms) => {
+                let terms = terms
+                    .iter()
+                    .map(|term| match term {
+                        Antecedent::Or(_) => grouped(term),
+                        _ => self.antecedent_to_fcl(term),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(terms.join(" AND "))
+            }
+            Antecedent::Or(terms) => {
+                let terms = terms
+                    .iter()
+                    .map(|term| self.antecedent_to_fcl(term))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(terms.join(" OR "))
+            }
+            Antecedent::Not(inner) => Ok(format!("NOT {}", grouped(inner)?)),
+            Antecedent::Hedged(hedge, inner) => {
+                // A chain of hedges on a single term, as in `input IS very somewhat category`
+                let mut hedges = vec![*hedge];
+                let mut term = inner.as_ref();
+                while let Antecedent::Hedged(hedge, inner) = term {
+                    hedges.push(*hedge);
+                    term = inner;
+                }
+                match term {
+                    Antecedent::Is { input, category } => {
+                        let input = self.fcl_input(input.as_deref())?;
+                        let hedges = hedges
+                            .iter()
+                            .map(|hedge| hedge_name(*hedge).ok_or(FclExportError::Hedge(*hedge)))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        Ok(format!("{input} IS {} {category}", hedges.join(" ")))
+                    }
+                    _ if *hedge == Hedge::Not => Ok(format!("NOT {}", grouped(inner)?)),
+                    _ => Err(FclExportError::Hedge(*hedge)),
+                }
+            }
+        }
+    }
+
+    /// Fails on a NaN or infinite rule weight, constant, variable range or term parameter,
+    /// which FCL can't write
+    fn check_finite(&self) -> Result<(), FclExportError> {
+        let variables = self
+            .input_variables
+            .iter()
+            .chain(self.output_variables.iter().map(|(_, variable)| variable));
+        let ranges = variables.flat_map(|variable| {
+            let terms = variable
+                .terms
+                .iter()
+                .flat_map(|set| shape_numbers(&set.shape, variable.min, variable.max));
+            [variable.min, variable.max].into_iter().chain(terms)
+        });
+        let rules = self.rules.iter().flat_map(|rule| {
+            let constants = rule
+                .consequences
+                .values()
+                .filter_map(|consequent| match consequent {
+                    Consequent::Constant(value) => Some(*value),
+                    _ => None,
+                });
+            std::iter::once(rule.weight).chain(constants)
+        });
+        match ranges.chain(rules).find(|value| !value.is_finite()) {
+            Some(value) => Err(FclExportError::NonFinite(value)),
+            None => Ok(()),
+        }
+    }
+
+    fn fcl_input<'a>(&self, input: Option<&'a str>) -> Result<&'a str, FclExportError> {
+        let input = input.ok_or(FclExportError::UnnamedTerm)?;
+        match self.input_variable(input) {
+            Some(_) => Ok(input),
+            None => Err(FclExportError::MissingVariable(input.to_string())),
+        }
+    }
+}
+
+/// Why a [`FuzzySystem`] could not be written as FCL
+#[derive(Debug, Clone, PartialEq)]
+pub enum FclExportError {
+    /// A rule term without an input name
+    UnnamedTerm,
+    /// A rule term reading an input without an input variable
+    MissingVariable(String),
+    /// A hedge FCL can't express, or a hedge other than `NOT` on a compound expression
+    Hedge(Hedge),
+    /// An empty `AND` or `OR` group
+    EmptyGroup,
+    /// A linear consequence of this output
+    LinearConsequence(String),
+    /// A negation other than [`Negation::Standard`]
+    Negation(Negation),
+    /// A NaN or infinite number
+    NonFinite(f32),
+    /// A t-norm FCL has no name for
+    TNorm(TNorm),
+    /// An s-norm FCL has no name for, as `OR` or as accumulation
+    SNorm(SNorm),
+}
+
+impl Display for FclExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FclExportError::UnnamedTerm => write!(f, "rule terms must name their input"),
+            FclExportError::MissingVariable(name) => {
+                write!(f, "no input variable called `{name}`")
+            }
+            FclExportError::Hedge(hedge) => write!(f, "hedge {hedge:?} can't be written as FCL"),
+            FclExportError::EmptyGroup => write!(f, "empty groups can't be written as FCL"),
+            FclExportError::LinearConsequence(output) => {
+                write!(
+                    f,
+                    "linear consequence of `{output}` can't be written as FCL"
+                )
+            }
+            FclExportError::Negation(negation) => {
+                write!(f, "negation {negation:?} can't be written as FCL")
+            }
+            FclExportError::NonFinite(value) => write!(f, "{value} can't be written as FCL"),
+            FclExportError::TNorm(tnorm) => write!(f, "t-norm {tnorm:?} can't be written as FCL"),
+            FclExportError::SNorm(snorm) => write!(f, "s-norm {snorm:?} can't be written as FCL"),
+        }
+    }
+}
+
+impl Error for FclExportError {}
+
+fn sorted_outputs<OutputType: Display, V>(
+    consequences: &HashMap<OutputType, V>,
+) -> Vec<&OutputType> {
+    let mut outputs: Vec<&OutputType> = consequences.keys().collect();
+    outputs.sort_by_key(|output| output.to_string());
+    outputs
+}
+
+fn write_terms<InputType: Display>(fcl: &mut String, variable: &FuzzyVariable<InputType>) {
+    fcl.push_str(&format!(
+        "    RANGE := ({} .. {});\n",
+        variable.min, variable.max
+    ));
+    for set in &variable.terms {
+        fcl.push_str(&format!(
+            "    TERM {} := {};\n",
+            set.category,
+            shape_to_fcl(&set.shape, variable.min, variable.max)
+        ));
+    }
+}
+
+fn shape_to_fcl(shape: &Shape, min: f32, max: f32) -> String {
+    let points = |points: &mut dyn Iterator<Item = (f32, f32)>| {
+        points
+            .map(|(x, y)| format!("({x}, {y})"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    match shape {
+        Shape::Points(p) => points(&mut p.iter().copied()),
+        Shape::Triangle { left, peak, right } => format!("trian {left} {peak} {right}"),
+        Shape::Trapezoid {
+            left,
+            left_top,
+            right_top,
+            right,
+        } => format!("trape {left} {left_top} {right_top} {right}"),
+        Shape::Gaussian { mean, sigma } => format!("gauss {mean} {sigma}"),
+        Shape::Bell {
+            width,
+            slope,
+            center,
+        } => format!("gbell {width} {slope} {center}"),
+        Shape::Sigmoid { slope, center } => format!("sigm {slope} {center}"),
+        Shape::Singleton(value) => format!("{value}"),
+        Shape::Custom(_) => points(
+            &mut inference::sample_positions(min, max, CUSTOM_SHAPE_POINTS)
+                .map(|x| (x, shape.membership(x))),
+        ),
+    }
+}
+
+/// The numbers [`shape_to_fcl`] writes for `shape`
+fn shape_numbers(shape: &Shape, min: f32, max: f32) -> Vec<f32> {
+    match shape {
+        Shape::Points(points) => points.iter().flat_map(|(x, y)| [*x, *y]).collect(),
+        Shape::Triangle { left, peak, right } => vec![*left, *peak, *right],
+        Shape::Trapezoid {
+            left,
+            left_top,
+            right_top,
+            right,
+        } => vec![*left, *left_top, *right_top, *right],
+        Shape::Gaussian { mean, sigma } => vec![*mean, *sigma],
+        Shape::Bell {
+            width,
+            slope,
+            center,
+        } => vec![*width, *slope, *center],
+        Shape::Sigmoid { slope, center } => vec![*slope, *center],
+        Shape::Singleton(value) => vec![*value],
+        Shape::Custom(_) => inference::sample_positions(min, max, CUSTOM_SHAPE_POINTS)
+            .flat_map(|x| [x, shape.membership(x)])
+            .collect(),
+    }
+}
+
+fn method_name(defuzzifier: Defuzzifier) -> &'static str {
+    match defuzzifier {
+        Defuzzifier::Centroid => "COG",
+        Defuzzifier::Bisector => "COA",
+        Defuzzifier::MeanOfMaximum => "MM",
+        Defuzzifier::SmallestOfMaximum => "LM",
+        Defuzzifier::LargestOfMaximum => "RM",
+    }
+}
+
+fn tnorm_name(tnorm: TNorm) -> Result<&'static str, FclExportError> {
+    match tnorm {
+        TNorm::Min => Ok("MIN"),
+        TNorm::Product => Ok("PROD"),
+        TNorm::Lukasiewicz => Ok("BDIF"),
+        _ => Err(FclExportError::TNorm(tnorm)),
+    }
+}
+
+fn snorm_name(snorm: SNorm) -> Result<&'static str, FclExportError> {
+    match snorm {
+        SNorm::Max => Ok("MAX"),
+        SNorm::ProbabilisticSum => Ok("ASUM"),
+        SNorm::BoundedSum => Ok("BSUM"),
+        _ => Err(FclExportError::SNorm(snorm)),
+    }
+}
+
+fn accumulation_name(snorm: SNorm) -> Result<&'static str, FclExportError> {
+    match snorm {
+        SNorm::Max => Ok("MAX"),
+        SNorm::ProbabilisticSum => Ok("PROBOR"),
+        SNorm::BoundedSum => Ok("BSUM"),
+        _ => Err(FclExportError::SNorm(snorm)),
+    }
+}
+
+fn hedge_name(hedge: Hedge) -> Option<&'static str> {
+    match hedge {
+        Hedge::Very => Some("VERY"),
+        Hedge::Extremely => Some("EXTREMELY"),
+        Hedge::Somewhat => Some("SOMEWHAT"),
+        Hedge::Indeed => Some("INDEED"),
+        Hedge::Not => Some("NOT"),
+        Hedge::Power(_) => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Word(String),
+    Number(f32),
+    Symbol(&'static str),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    text: String,
+    line: usize,
+    column: usize,
+    /// Byte range in the source
+    start: usize,
+    end: usize,
+}
+
+impl Token {
+    /// The upper case text of a word
+    fn word(&self) -> Option<String> {
+        match &self.kind {
+            TokenKind::Word(word) => Some(word.to_uppercase()),
+            _ => None,
+        }
+    }
+}
+
+const SYMBOLS: &[&str] = &[":=", "..", ":", ";", "(", ")", ","];
+
+/// Replaces `(* ... *)` and `// ...` comments with spaces, keeping line breaks so that
+/// positions don't change
+fn strip_comments(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let blank = |c: char| if c == '\n' { '\n' } else { ' ' };
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('(', Some('*')) => {
+                chars.next();
+                stripped.push_str("  ");
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    stripped.push(blank(c));
+                    if previous == '*' && c == ')' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            ('/', Some('/')) => {
+                stripped.push(' ');
+                while let Some(c) = chars.next_if(|c| *c != '\n') {
+                    stripped.push(blank(c));
+                }
+            }
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let at = |i: usize| chars.get(i).map(|(_, c)| *c);
+    let byte = |i: usize| chars.get(i).map_or(text.len(), |(b, _)| *b);
+    let mut tokens = Vec::new();
+    let (mut line, mut column) = (1, 1);
+    let mut i = 0;
+
+    while let Some(c) = at(i) {
+        if c == '\n' {
+            line += 1;
+            column = 1;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            column += 1;
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        let starts_number = c.is_ascii_digit()
+            || (matches!(c, '-' | '+' | '.')
+                && at(i + 1).is_some_and(|n| n.is_ascii_digit() || (n == '.' && c != '.')));
+
+        let kind = if c.is_alphabetic() || c == '_' {
+            while at(i).is_some_and(|c| c.is_alphanumeric() || c == '_') {
+                i += 1;
+            }
+            TokenKind::Word(text[byte(start)..byte(i)].to_string())
+        } else if starts_number {
+            i += 1;
+            while let Some(c) = at(i) {
+                let exponent_sign = matches!(c, '-' | '+') && matches!(at(i - 1), Some('e' | 'E'));
+                // `0..10` is a range, not a number
+                let decimal_point = c == '.' && at(i + 1) != Some('.');
+                if c.is_ascii_digit() || decimal_point || matches!(c, 'e' | 'E') || exponent_sign {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            let number = &text[byte(start)..byte(i)];
+            match number.parse() {
+                Ok(value) => TokenKind::Number(value),
+                Err(_) => {
+                    return Err(ParseError {
+                        line,
+                        column,
+                        kind: ParseErrorKind::UnexpectedToken {
+                            expected: "a number".to_string(),
+                            found: number.to_string(),
+                        },
+                    })
+                }
+            }
+        } else if let Some(symbol) = SYMBOLS.iter().find(|s| text[byte(i)..].starts_with(**s)) {
+            i += symbol.len();
+            TokenKind::Symbol(symbol)
+        } else {
+            return Err(ParseError {
+                line,
+                column,
+                kind: ParseErrorKind::UnexpectedCharacter(c),
+            });
+        };
+
+        tokens.push(Token {
+            kind,
+            text: text[byte(start)..byte(i)].to_string(),
+            line,
+            column,
+            start: byte(start),
+            end: byte(i),
+        });
+        column += i - start;
+    }
+
+    Ok(tokens)
+}
+
+/// How an output is defuzzified
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Method {
+    /// `COGS`, weighted average of singleton terms
+    Singletons,
+    Defuzzify(Defuzzifier),
+}
+
+/// A variable declared in `VAR_INPUT` or `VAR_OUTPUT`
+struct Declaration {
+    name: String,
+    range: Option<(f32, f32)>,
+    terms: Vec<(FuzzySet<String>, Token)>,
+    method: Option<(Method, Token)>,
+}
+
+impl Declaration {
+    fn variable(&self) -> FuzzyVariable<String> {
+        let (min, max) = self
+            .range
+            .or_else(|| {
+                self.terms
+                    .iter()
+                    .filter_map(|(set, _)| set.support())
+                    .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)))
+            })
+            .unwrap_or((0.0, 1.0));
+
+        self.terms.iter().fold(
+            FuzzyVariable::new(&self.name, min, max),
+            |variable, (set, _)| variable.with_term(set.clone()),
+        )
+    }
+
+    fn singleton(&self, term: &str) -> Option<f32> {
+        self.terms.iter().find_map(|(set, _)| match set.shape {
+            Shape::Singleton(value) if set.category == term => Some(value),
+            _ => None,
+        })
+    }
+}
+
+struct FclParser {
+    text: String,
+    tokens: Vec<Token>,
+    position: usize,
+    inputs: Vec<Declaration>,
+    outputs: Vec<Declaration>,
+    system: FuzzySystem<String, String>,
+}
+
+impl FclParser {
+    fn new(text: &str) -> Result<Self, ParseError> {
+        let text = strip_comments(text);
+        let tokens = tokenize(&text)?;
+        Ok(Self {
+            text,
+            tokens,
+            position: 0,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            system: FuzzySystem::new(),
+        })
+    }
+
+    fn function_block(mut self) -> Result<FuzzySystem<String, String>, ParseError> {
+        const SECTIONS: &str = "a section or `END_FUNCTION_BLOCK`";
+
+        self.expect_keyword("FUNCTION_BLOCK")?;
+        self.optional_name(&[
+            "VAR_INPUT",
+            "VAR_OUTPUT",
+            "FUZZIFY",
+            "DEFUZZIFY",
+            "RULEBLOCK",
+            "END_FUNCTION_BLOCK",
+        ]);
+
+        loop {
+            let token = self.next_or_end(SECTIONS)?;
+            match token.word().as_deref() {
+                Some("VAR_INPUT") => self.declarations(false)?,
+                Some("VAR_OUTPUT") => self.declarations(true)?,
+                Some("FUZZIFY") => self.fuzzify()?,
+                Some("DEFUZZIFY") => self.defuzzify()?,
+                Some("RULEBLOCK") => self.rule_block()?,
+                Some("END_FUNCTION_BLOCK") => break,
+                _ => return Err(unexpected(&token, SECTIONS)),
+            }
+        }
+        if let Some(token) = self.peek() {
+            return Err(unexpected(token, "end of file"));
+        }
+
+        let mut system = self.system;
+        for input in &self.inputs {
+            system.add_input_variable(input.variable());
+        }
+        for output in &self.outputs {
+            system.add_output_variable(output.name.clone(), output.variable());
+        }
+        if let Some(Method::Defuzzify(defuzzifier)) = self
+            .outputs
+            .iter()
+            .find_map(|output| output.method.as_ref().map(|(method, _)| *method))
+        {
+            system.inference = Inference::mamdani(defuzzifier);
+        }
+        Ok(system)
+    }
+
+    /// `name : TYPE;` declarations up to `END_VAR`
+    fn declarations(&mut self, outputs: bool) -> Result<(), ParseError> {
+        while !self.accept_keyword("END_VAR") {
+            let name = self.expect_name("a variable name or `END_VAR`")?;
+            self.expect_symbol(":")?;
+            self.expect_name("a type")?;
+            self.expect_symbol(";")?;
+
+            let declaration = Declaration {
+                name: name.text,
+                range: None,
+                terms: Vec::new(),
+                method: None,
+            };
+            if outputs {
+                self.outputs.push(declaration);
+            } else {
+                self.inputs.push(declaration);
+            }
+        }
+        Ok(())
+    }
+
+    fn fuzzify(&mut self) -> Result<(), ParseError> {
+        const STATEMENTS: &str = "`TERM`, `RANGE` or `END_FUZZIFY`";
+
+        let name = self.expect_name("an input variable")?;
+        let index = self
+            .inputs
+            .iter()
+            .position(|input| input.name == name.text)
+            .ok_or_else(|| error_at(&name, ParseErrorKind::UnknownVariable(name.text.clone())))?;
+
+        loop {
+            let token = self.next_or_end(STATEMENTS)?;
+            match token.word().as_deref() {
+                Some("TERM") => {
+                    let term = self.term()?;
+                    self.inputs[index].terms.push(term);
+                }
+                Some("RANGE") => self.inputs[index].range = Some(self.range()?),
+                Some("END_FUZZIFY") => return Ok(()),
+                _ => return Err(unexpected(&token, STATEMENTS)),
+            }
+        }
+    }
+
+    fn defuzzify(&mut self) -> Result<(), ParseError> {
+        const STATEMENTS: &str = "`TERM`, `RANGE`, `METHOD`, `DEFAULT`, `ACCU` or `END_DEFUZZIFY`";
+
+        let name = self.expect_name("an output variable")?;
+        let index = self
+            .outputs
+            .iter()
+            .position(|output| output.name == name.text)
+            .ok_or_else(|| error_at(&name, ParseErrorKind::UnknownOutput(name.text.clone())))?;
+
+        let mut method = None;
+        loop {
+            let token = self.next_or_end(STATEMENTS)?;
+            match token.word().as_deref() {
+                Some("TERM") => {
+                    let term = self.term()?;
+                    self.outputs[index].terms.push(term);
+                }
+                Some("RANGE") => self.outputs[index].range = Some(self.range()?),
+                Some("METHOD") => {
+                    self.expect_symbol(":")?;
+                    let method_token = self.peek().cloned();
+                    let parsed = self.choice(
+                        &[
+                            ("COG", Method::Defuzzify(Defuzzifier::Centroid)),
+                            ("COA", Method::Defuzzify(Defuzzifier::Bisector)),
+                            ("MM", Method::Defuzzify(Defuzzifier::MeanOfMaximum)),
+                            ("LM", Method::Defuzzify(Defuzzifier::SmallestOfMaximum)),
+                            ("RM", Method::Defuzzify(Defuzzifier::LargestOfMaximum)),
+                            ("COGS", Method::Singletons),
+                        ],
+                        "a defuzzification method",
+                    )?;
+                    self.expect_symbol(";")?;
+                    method = Some((parsed, method_token.expect("a method was parsed")));
+                }
+                Some("DEFAULT") => {
+                    self.expect_symbol(":=")?;
+                    let token = self.next_or_end("a number or `NC`")?;
+                    if !matches!(token.kind, TokenKind::Number(_))
+                        && token.word().as_deref() != Some("NC")
+                    {
+                        return Err(unexpected(&token, "a number or `NC`"));
+                    }
+                    self.expect_symbol(";")?;
+                }
+                Some("ACCU") => self.system.operators.aggregation = self.accumulation()?,
+                Some("END_DEFUZZIFY") => break,
+                _ => return Err(unexpected(&token, STATEMENTS)),
+            }
+        }
+
+        // Without a method the standard defaults to `COG`
+        let (method, method_token) =
+            method.unwrap_or((Method::Defuzzify(Defuzzifier::Centroid), name));
+        if let Some((_, other)) = self
+            .outputs
+            .iter()
+            .filter_map(|output| output.method.as_ref())
+            .find(|(other, _)| *other != method)
+        {
+            return Err(unexpected(
+                &method_token,
+                &format!("`{}` like the other outputs", other.text),
+            ));
+        }
+        self.outputs[index].method = Some((method, method_token));
+
+        let output = &self.outputs[index];
+        if let Some((Method::Singletons, _)) = output.method {
+            if let Some((_, token)) = output
+                .terms
+                .iter()
+                .find(|(set, _)| !matches!(set.shape, Shape::Singleton(_)))
+            {
+                return Err(unexpected(token, "a singleton term for `COGS`"));
+            }
+        }
+        Ok(())
+    }
+
+    fn rule_block(&mut self) -> Result<(), ParseError> {
+        const STATEMENTS: &str = "`AND`, `OR`, `ACT`, `ACCU`, `RULE` or `END_RULEBLOCK`";
+
+        let name = self.optional_name(&["AND", "OR", "ACT", "ACCU", "RULE", "END_RULEBLOCK"]);
+        let block = self.system.add_rule_block(RuleBlock::new(
+            name.unwrap_or_default(),
+            LogicOperators::default(),
+        ));
+        let rules = self.rule_parser();
+
+        loop {
+            let token = self.next_or_end(STATEMENTS)?;
+            match token.word().as_deref() {
+                Some("AND") => {
+                    self.expect_symbol(":")?;
+                    self.system.rule_blocks[block].operators.and = self.choice(
+                        &[
+                            ("MIN", TNorm::Min),
+                            ("PROD", TNorm::Product),
+                            ("BDIF", TNorm::Lukasiewicz),
+                        ],
+                        "`MIN`, `PROD` or `BDIF`",
+                    )?;
+                    self.expect_symbol(";")?;
+                }
+                Some("OR") => {
+                    self.expect_symbol(":")?;
+                    self.system.rule_blocks[block].operators.or = self.choice(
+                        &[
+                            ("MAX", SNorm::Max),
+                            ("ASUM", SNorm::ProbabilisticSum),
+                            ("BSUM", SNorm::BoundedSum),
+                        ],
+                        "`MAX`, `ASUM` or `BSUM`",
+                    )?;
+                    self.expect_symbol(";")?;
+                }
+                Some("ACT") => {
+                    self.expect_symbol(":")?;
+                    self.system.rule_blocks[block].operators.implication = self.choice(
+                        &[("MIN", Implication::Min), ("PROD", Implication::Product)],
+                        "`MIN` or `PROD`",
+                    )?;
+                    self.expect_symbol(";")?;
+                }
+                Some("ACCU") => {
+                    self.system.rule_blocks[block].operators.aggregation = self.accumulation()?
+                }
+                Some("RULE") => self.rule(&rules, block)?,
+                Some("END_RULEBLOCK") => return Ok(()),
+                _ => return Err(unexpected(&token, STATEMENTS)),
+            }
+        }
+    }
+
+    /// `RULE name : IF ... THEN ... [WITH weight];`
+    fn rule(&mut self, rules: &RuleParser<String, String>, block: usize) -> Result<(), ParseError> {
+        let name = self.next_or_end("a rule name")?;
+        if !matches!(name.kind, TokenKind::Word(_) | TokenKind::Number(_)) {
+            return Err(unexpected(&name, "a rule name"));
+        }
+        let colon = self.expect_symbol(":")?;
+
+        let Some(end) = self.tokens[self.position..]
+            .iter()
+            .position(|token| token.kind == TokenKind::Symbol(";"))
+            .map(|offset| self.position + offset)
+        else {
+            return Err(self.end_error("`;`"));
+        };
+        let text = &self.text[colon.end..self.tokens[end].start];
+        let mut rule = rules
+            .parse_rule_at(text, colon.line, colon.column + 1)?
+            .in_block(block);
+        self.position = end + 1;
+
+        // `COGS` outputs are singletons, which weighted average inference reads as constants
+        for (output, consequent) in rule.consequences.iter_mut() {
+            let declaration = self.outputs.iter().find(|o| &o.name == output);
+            if let (Some(declaration), Consequent::Term(term)) = (declaration, &consequent) {
+                if let (Some((Method::Singletons, _)), Some(value)) =
+                    (&declaration.method, declaration.singleton(term))
+                {
+                    *consequent = Consequent::Constant(value);
+                }
+            }
+        }
+        self.system.add_rule(rule);
+        Ok(())
+    }
+
+    fn rule_parser(&self) -> RuleParser<String, String> {
+        let terms = |declaration: &Declaration| {
+            declaration
+                .terms
+                .iter()
+                .map(|(set, _)| set.category.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mut parser = RuleParser::new();
+        for input in &self.inputs {
+            let terms = terms(input);
+            parser = parser.with_input(&input.name, terms.iter().map(|t| (t.as_str(), t.clone())));
+        }
+        for output in &self.outputs {
+            let terms = terms(output);
+            parser = parser.with_output(
+                &output.name,
+                output.name.clone(),
+                terms.iter().map(|t| (t.as_str(), t.clone())),
+            );
+        }
+        parser
+    }
+
+    /// `name := shape;` after `TERM`
+    fn term(&mut self) -> Result<(FuzzySet<String>, Token), ParseError> {
+        let name = self.expect_name("a term name")?;
+        self.expect_symbol(":=")?;
+
+        let token = self.next_or_end("a membership function")?;
+        let shape = match (&token.kind, token.word().as_deref()) {
+            (TokenKind::Number(value), _) => Shape::Singleton(*value),
+            (TokenKind::Symbol("("), _) => {
+                self.position -= 1;
+                let mut points = Vec::new();
+                while self.accept_symbol("(") {
+                    let x = self.expect_number()?;
+                    self.expect_symbol(",")?;
+                    let y = self.expect_number()?;
+                    self.expect_symbol(")")?;
+                    points.push((x, y));
+                }
+                Shape::Points(points)
+            }
+            (_, Some("TRIAN")) => Shape::Triangle {
+                left: self.expect_number()?,
+                peak: self.expect_number()?,
+                right: self.expect_number()?,
+            },
+            (_, Some("TRAPE")) => Shape::Trapezoid {
+                left: self.expect_number()?,
+                left_top: self.expect_number()?,
+                right_top: self.expect_number()?,
+                right: self.expect_number()?,
+            },
+            (_, Some("GAUSS")) => Shape::Gaussian {
+                mean: self.expect_number()?,
+                sigma: self.expect_number()?,
+            },
+            (_, Some("GBELL")) => Shape::Bell {
+                width: self.expect_number()?,
+                slope: self.expect_number()?,
+                center: self.expect_number()?,
+            },
+            (_, Some("SIGM")) => Shape::Sigmoid {
+                slope: self.expect_number()?,
+                center: self.expect_number()?,
+            },
+            (_, Some("SINGLETON")) => Shape::Singleton(self.expect_number()?),
+            _ => return Err(unexpected(&token, "a membership function")),
+        };
+        self.expect_symbol(";")?;
+
+        Ok((FuzzySet::with_shape(name.text.clone(), shape), name))
+    }
+
+    /// `:= (min .. max);` after `RANGE`
+    fn range(&mut self) -> Result<(f32, f32), ParseError> {
+        self.expect_symbol(":=")?;
+        self.expect_symbol("(")?;
+        let min = self.expect_number()?;
+        self.expect_symbol("..")?;
+        let max = self.expect_number()?;
+        self.expect_symbol(")")?;
+        self.expect_symbol(";")?;
+        Ok((min, max))
+    }
+
+    /// `: MAX;`, `: BSUM;` or `: PROBOR;` after `ACCU`
+    fn accumulation(&mut self) -> Result<SNorm, ParseError> {
+        self.expect_symbol(":")?;
+        let aggregation = self.choice(
+            &[
+                ("MAX", SNorm::Max),
+                ("BSUM", SNorm::BoundedSum),
+                ("PROBOR", SNorm::ProbabilisticSum),
+            ],
+            "`MAX`, `BSUM` or `PROBOR`",
+        )?;
+        self.expect_symbol(";")?;
+        Ok(aggregation)
+    }
+
+    /// Reads the name of a block, which is optional
+    fn optional_name(&mut self, followers: &[&str]) -> Option<String> {
+        let token = self.peek()?;
+        let word = token.word()?;
+        if followers.contains(&word.as_str()) {
+            return None;
+        }
+        let name = token.text.clone();
+        self.position += 1;
+        Some(name)
+    }
+
+    fn choice<T: Copy>(&mut self, options: &[(&str, T)], expected: &str) -> Result<T, ParseError> {
+        let token = self.next_or_end(expected)?;
+        let word = token.word();
+        options
+            .iter()
+            .find(|(name, _)| word.as_deref() == Some(*name))
+            .map(|(_, value)| *value)
+            .ok_or_else(|| unexpected(&token, expected))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_or_end(&mut self, expected: &str) -> Result<Token, ParseError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => Err(self.end_error(expected)),
+        }
+    }
+
+    fn end_error(&self, expected: &str) -> ParseError {
+        let (line, column) = self.text.chars().fold((1, 1), |(line, column), c| {
+            if c == '\n' {
+                (line + 1, 1)
+            } else {
+                (line, column + 1)
+            }
+        });
+        ParseError {
+            line,
+            column,
+            kind: ParseErrorKind::UnexpectedEnd {
+                expected: expected.to_string(),
+            },
+        }
+    }
+
+    fn accept_keyword(&mut self, word: &str) -> bool {
+        let found = self
+            .peek()
+            .is_some_and(|token| token.word().as_deref() == Some(word));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn accept_symbol(&mut self, symbol: &str) -> bool {
+        let found = self
+            .peek()
+            .is_some_and(|token| matches!(token.kind, TokenKind::Symbol(s) if s == symbol));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, word: &str) -> Result<Token, ParseError> {
+        let expected = format!("`{word}`");
+        let token = self.next_or_end(&expected)?;
+        match token.word() {
+            Some(found) if found == word => Ok(token),
+            _ => Err(unexpected(&token, &expected)),
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<Token, ParseError> {
+        let expected = format!("`{symbol}`");
+        let token = self.next_or_end(&expected)?;
+        match token.kind {
+            TokenKind::Symbol(s) if s == symbol => Ok(token),
+            _ => Err(unexpected(&token, &expected)),
+        }
+    }
+
+    fn expect_name(&mut self, expected: &str) -> Result<Token, ParseError> {
+        let token = self.next_or_end(expected)?;
+        match token.kind {
+            TokenKind::Word(_) => Ok(token),
+            _ => Err(unexpected(&token, expected)),
+        }
+    }
+
+    fn expect_number(&mut self) -> Result<f32, ParseError> {
+        let token = self.next_or_end("a number")?;
+        match token.kind {
+            TokenKind::Number(value) => Ok(value),
+            _ => Err(unexpected(&token, "a number")),
+        }
+    }
+}
+
+fn error_at(token: &Token, kind: ParseErrorKind) -> ParseError {
+    ParseError {
+        line: token.line,
+        column: token.column,
+        kind,
+    }
+}
+
+fn unexpected(token: &Token, expected: &str) -> ParseError {
+    error_at(
+        token,
+        ParseErrorKind::UnexpectedToken {
+            expected: expected.to_string(),
+            found: token.text.clone(),
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::FuzzyRule;
+
+    const TIPPER: &str = "
+(* Classic tipper example *)
+FUNCTION_BLOCK tipper
+
+VAR_INPUT
+    service : REAL;
+    food : REAL;
+END_VAR
+
+VAR_OUTPUT
+    tip : REAL;
+END_VAR
+
+FUZZIFY service
+    TERM poor := gauss 0 1.5;
+    TERM good := gauss 5 1.5;
+    TERM excellent := gauss 10 1.5;
+    RANGE := (0 .. 10);
+END_FUZZIFY
+
+FUZZIFY food
+    TERM rancid := trape 0 0 1 3;
+    TERM delicious := (7, 0) (9, 1) (10, 1);
+END_FUZZIFY
+
+DEFUZZIFY tip
+    TERM cheap := trian 0 5 10;
+    TERM average := trian 10 15 20;
+    TERM generous := trian 20 25 30;
+    METHOD : COG;
+    DEFAULT := 0;
+    RANGE := (0 .. 30);
+END_DEFUZZIFY
+
+RULEBLOCK No1
+    AND : MIN;
+    ACT : MIN;
+    ACCU : MAX;
+    RULE 1 : IF service IS poor OR food IS rancid THEN tip IS cheap;
+    RULE 2 : IF service IS good THEN tip IS average; // plain rule
+    RULE 3 : IF service IS excellent
+             AND food IS delicious THEN tip IS generous;
+END_RULEBLOCK
+
+END_FUNCTION_BLOCK
+";
+
+    fn tip(system: &FuzzySystem<String, String>, service: f32, food: f32) -> f32 {
+        system.evaluate_inputs(&HashMap::from([("service", service), ("food", food)]))["tip"]
+    }
+
+    #[test]
+    fn test_import_tipper() {
+        let system = FuzzySystem::from_fcl(TIPPER).unwrap();
+
+        let service = system.input_variable("service").unwrap();
+        assert_eq!((service.min, service.max), (0.0, 10.0));
+        assert_eq!(service.terms.len(), 3);
+        let food = system.input_variable("food").unwrap();
+        assert_eq!((food.min, food.max), (0.0, 10.0));
+        assert_eq!(
+            food.term(&"delicious".to_string()).unwrap().shape,
+            Shape::Points(vec![(7.0, 0.0), (9.0, 1.0), (10.0, 1.0)])
+        );
+        let output = system.output_variable(&"tip".to_string()).unwrap();
+        assert_eq!((output.min, output.max), (0.0, 30.0));
+
+        assert_eq!(system.rules.len(), 3);
+        assert_eq!(system.inference, Inference::mamdani(Defuzzifier::Centroid));
+
+        assert!((tip(&system, 5.0, 5.0) - 15.0).abs() < 0.1);
+        assert!(tip(&system, 0.0, 0.0) < 6.0);
+        assert!(tip(&system, 10.0, 10.0) > 20.0);
+    }
+
+    #[test]
+    fn test_import_singletons() {
+        let fcl = "FUNCTION_BLOCK
+            VAR_INPUT health : REAL; END_VAR
+            VAR_OUTPUT aggression : REAL; END_VAR
+            FUZZIFY health
+                TERM low := (0, 1) (50, 0);
+                TERM high := (50, 0) (100, 1);
+            END_FUZZIFY
+            DEFUZZIFY aggression
+                TERM calm := 0.2;
+                TERM angry := singleton 0.9;
+                METHOD : COGS;
+            END_DEFUZZIFY
+            RULEBLOCK
+                AND : PROD;
+                OR : ASUM;
+                RULE calm : IF health IS low THEN aggression IS calm;
+                RULE angry : IF health IS high THEN aggression IS angry;
+            END_RULEBLOCK
+            END_FUNCTION_BLOCK";
+        let system = FuzzySystem::from_fcl(fcl).unwrap();
+
+        assert_eq!(system.inference, Inference::WeightedAverage);
+        assert_eq!(system.rule_blocks.len(), 1);
+        assert_eq!(system.rule_blocks[0].operators.and, TNorm::Product);
+        assert_eq!(system.rule_blocks[0].operators.or, SNorm::ProbabilisticSum);
+        assert!(system.rules.iter().all(|rule| rule.block == Some(0)));
+        assert_eq!(
+            system.rules[1].consequences["aggression"],
+            Consequent::Constant(0.9)
+        );
+        let outputs = system.evaluate_inputs(&HashMap::from([("health", 0.0)]));
+        assert_eq!(outputs["aggression"], 0.2);
+    }
+
+    #[test]
+    fn test_import_default_method() {
+        let fcl = "FUNCTION_BLOCK
+            VAR_INPUT x : REAL; END_VAR
+            VAR_OUTPUT y : REAL; END_VAR
+            FUZZIFY x TERM low := (0, 1) (1, 0); END_FUZZIFY
+            DEFUZZIFY y TERM a := trian 0 1 2; END_DEFUZZIFY
+            RULEBLOCK RULE 1 : IF x IS low THEN y IS a; END_RULEBLOCK
+            END_FUNCTION_BLOCK";
+        let system = FuzzySystem::from_fcl(fcl).unwrap();
+
+        assert_eq!(system.inference, Inference::mamdani(Defuzzifier::Centroid));
+        let outputs = system.evaluate_inputs(&HashMap::from([("x", 0.0)]));
+        assert!((outputs["y"] - 1.0).abs() < 1e-3);
+
+        // A missing method still has to match the other outputs
+        let e = FuzzySystem::from_fcl(
+            "FUNCTION_BLOCK\nVAR_OUTPUT y : REAL; z : REAL; END_VAR\n\
+             DEFUZZIFY y METHOD : COGS; END_DEFUZZIFY\nDEFUZZIFY z END_DEFUZZIFY",
+        )
+        .unwrap_err();
+        assert_eq!((e.line, e.column), (4, 11));
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let error = |fcl: &str| FuzzySystem::from_fcl(fcl).unwrap_err();
+
+        let e = error("FUNCTION_BLOCK\nFUZZIFY speed\nEND_FUZZIFY\nEND_FUNCTION_BLOCK");
+        assert_eq!((e.line, e.column), (2, 9));
+        assert_eq!(e.kind, ParseErrorKind::UnknownVariable("speed".to_string()));
+
+        let e =
+            error("FUNCTION_BLOCK\nVAR_INPUT x : REAL; END_VAR\nFUZZIFY x\n  TERM a := blob 1;\n");
+        assert_eq!((e.line, e.column), (4, 13));
+
+        // Rule errors point into the rule text
+        let e = error(
+            "FUNCTION_BLOCK\nVAR_INPUT x : REAL; END_VAR\nVAR_OUTPUT y : REAL; END_VAR\n\
+             FUZZIFY x TERM a := 1; END_FUZZIFY\n\
+             RULEBLOCK r\n  RULE 1 : IF x IS b THEN y IS 1;\nEND_RULEBLOCK\nEND_FUNCTION_BLOCK",
+        );
+        assert_eq!((e.line, e.column), (6, 20));
+        assert_eq!(
+            e.kind,
+            ParseErrorKind::UnknownTerm {
+                variable: "x".to_string(),
+                term: "b".to_string()
+            }
+        );
+
+        let e = error("FUNCTION_BLOCK\nVAR_OUTPUT y : REAL; END_VAR\nDEFUZZIFY y\n  TERM a := trian 0 1 2;\n  METHOD : COGS;\nEND_DEFUZZIFY");
+        assert_eq!((e.line, e.column), (4, 8));
+
+        let e = error("FUNCTION_BLOCK\nVAR_OUTPUT y : REAL; z : REAL; END_VAR\nDEFUZZIFY y METHOD : COG; END_DEFUZZIFY\nDEFUZZIFY z METHOD : MM; END_DEFUZZIFY");
+        assert_eq!((e.line, e.column), (4, 22));
+
+        let e = error("FUNCTION_BLOCK\nRULEBLOCK r\n  ACCU : NSUM;");
+        assert_eq!((e.line, e.column), (3, 10));
+
+        let e = error("FUNCTION_BLOCK\n");
+        assert_eq!((e.line, e.column), (2, 1));
+        assert!(matches!(e.kind, ParseErrorKind::UnexpectedEnd { .. }));
+    }
+
+    #[test]
+    fn test_export_round_trip_mamdani() {
+        let system = FuzzySystem::from_fcl(TIPPER).unwrap();
+        let fcl = system.to_fcl("tipper").unwrap();
+        let reimported = FuzzySystem::from_fcl(&fcl).unwrap();
+
+        assert!(fcl.contains("TERM poor := gauss 0 1.5;"));
+        assert!(fcl.contains("METHOD : COG;"));
+        for (service, food) in [(0.0, 0.0), (3.0, 8.0), (5.0, 5.0), (9.0, 9.5)] {
+            assert_eq!(tip(&system, service, food), tip(&reimported, service, food));
+        }
+    }
+
+    #[test]
+    fn test_export_round_trip_constants() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::triangle("low", 0.0, 0.0, 50.0))
+                .with_term(FuzzySet::custom("high", |x: f32| {
+                    (x / 100.0).clamp(0.0, 1.0)
+                })),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").very())
+                .with_consequence("aggression", 0.2)
+                .with_consequence("speed", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(
+                !Antecedent::is("health", "low")
+                    .and(Antecedent::is("health", "high").or(Antecedent::is("health", "low"))),
+            )
+            .with_consequence("aggression", 0.9),
+        );
+
+        let fcl = system.to_fcl("enemy").unwrap();
+        assert!(fcl.contains("TERM aggression_2 := 0.9;"));
+        assert!(fcl.contains("METHOD : COGS;"));
+        assert!(fcl.contains(
+            "RULE 1 : IF health IS VERY low THEN aggression IS aggression_1 AND speed IS speed_1;"
+        ));
+        assert!(fcl.contains(
+            "RULE 2 : IF NOT (health IS low AND (health IS high OR health IS low)) THEN aggression IS aggression_2;"
+        ));
+
+        let reimported = FuzzySystem::from_fcl(&fcl).unwrap();
+        for health in [0.0, 20.0, 50.0, 80.0] {
+            let a = system.evaluate_inputs(&HashMap::from([("health", health)]));
+            let b = reimported.evaluate_inputs(&HashMap::from([("health", health)]));
+            assert_eq!(a.len(), b.len());
+            for (output, value) in a {
+                assert!((value - b[output]).abs() < 1e-5, "{output} at {health}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_export_errors() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_rule(FuzzyRule::new("low").with_consequence("out", 1.0));
+        assert_eq!(system.to_fcl("f"), Err(FclExportError::UnnamedTerm));
+
+        system.rules[0].condition = Antecedent::is("health", "low");
+        assert_eq!(
+            system.to_fcl("f"),
+            Err(FclExportError::MissingVariable("health".to_string()))
+        );
+
+        system.add_input_variable(FuzzyVariable::new("health", 0.0, 1.0));
+        system.rules[0].condition = Antecedent::is("health", "low").hedged(Hedge::Power(2.0));
+        assert_eq!(
+            system.to_fcl("f"),
+            Err(FclExportError::Hedge(Hedge::Power(2.0)))
+        );
+
+        system.rules[0].condition = Antecedent::is("health", "low");
+        system.rules[0] = system.rules[0]
+            .clone()
+            .with_linear_consequence("out", crate::fuzzy::LinearConsequent::new(1.0));
+        assert_eq!(
+            system.to_fcl("f"),
+            Err(FclExportError::LinearConsequence("out".to_string()))
+        );
+
+        system.operators.not = Negation::Sugeno(1.0);
+        assert_eq!(
+            system.to_fcl("f"),
+            Err(FclExportError::Negation(Negation::Sugeno(1.0)))
+        );
+    }
+
+    #[test]
+    fn test_export_non_finite_values() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 1.0)
+                .with_term(FuzzySet::triangle("low", 0.0, 0.0, 1.0)),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low")).with_consequence("out", f32::NAN),
+        );
+        assert!(matches!(
+            system.to_fcl("f"),
+            Err(FclExportError::NonFinite(value)) if value.is_nan()
+        ));
+
+        system.rules[0] =
+            FuzzyRule::new(Antecedent::is("health", "low")).with_consequence("out", 1.0);
+        system.rules[0].weight = f32::INFINITY;
+        assert_eq!(
+            system.to_fcl("f"),
+            Err(FclExportError::NonFinite(f32::INFINITY))
+        );
+
+        system.rules[0].weight = 1.0;
+        system.input_variables[0].terms[0] = FuzzySet::triangle("low", f32::NAN, 0.0, 1.0);
+        assert!(matches!(
+            system.to_fcl("f"),
+            Err(FclExportError::NonFinite(value)) if value.is_nan()
+        ));
+        system.input_variables[0].terms[0] =
+            FuzzySet::new("low", vec![(0.0, 1.0), (f32::NEG_INFINITY, 0.0)]);
+        assert_eq!(
+            system.to_fcl("f"),
+            Err(FclExportError::NonFinite(f32::NEG_INFINITY))
+        );
+    }
+
+    #[test]
+    fn test_export_output_without_constants() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 1.0)
+                .with_term(FuzzySet::triangle("low", 0.0, 0.0, 1.0)),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low"))
+                .with_consequence("out", 1.0)
+                .with_output_term("mood", "calm"),
+        );
+        let fcl = system.to_fcl("f").unwrap();
+        assert!(
+            fcl.contains("DEFUZZIFY mood\n    RANGE := (0 .. 0);"),
+            "{fcl}"
+        );
+    }
+
+    #[test]
+    fn test_rule_weights() {
+        let mut system = FuzzySystem::from_fcl(TIPPER).unwrap();
+        assert!(system.rules.iter().all(|rule| rule.weight == 1.0));
+        system.rules[1].weight = 0.5;
+        system.rules[2].enabled = false;
+
+        let fcl = system.to_fcl("tipper").unwrap();
+        assert!(fcl.contains("THEN tip IS average WITH 0.5;"));
+        assert!(!fcl.contains("RULE 3"));
+
+        let reimported = FuzzySystem::from_fcl(&fcl).unwrap();
+        assert_eq!(reimported.rules.len(), 2);
+        assert_eq!(reimported.rules[1].weight, 0.5);
+        for (service, food) in [(0.0, 0.0), (5.0, 5.0), (9.0, 9.5)] {
+            assert_eq!(tip(&system, service, food), tip(&reimported, service, food));
+        }
+    }
+
+    #[test]
+    fn test_rule_blocks() {
+        let fcl = TIPPER.replace(
+            "    RULE 3",
+            "END_RULEBLOCK\nRULEBLOCK No2\n    AND : BDIF;\n    ACT : PROD;\n    ACCU : BSUM;\n    RULE 3",
+        );
+        let system = FuzzySystem::from_fcl(&fcl).unwrap();
+
+        assert_eq!(system.rule_blocks.len(), 2);
+        assert_eq!(system.rule_blocks[1].name, "No2");
+        assert_eq!(system.rule_blocks[1].operators.and, TNorm::Lukasiewicz);
+        assert_eq!(
+            system.rule_blocks[1].operators.aggregation,
+            SNorm::BoundedSum
+        );
+        assert_eq!(system.rules[1].block, Some(0));
+        assert_eq!(system.rules[2].block, Some(1));
+
+        let exported = system.to_fcl("tipper").unwrap();
+        assert!(exported.contains("RULEBLOCK No2\n    AND : BDIF;"));
+        assert!(exported.contains("ACCU : BSUM;\n    RULE 3 :"));
+        let reimported = FuzzySystem::from_fcl(&exported).unwrap();
+        for (service, food) in [(0.0, 0.0), (5.0, 5.0), (9.0, 9.5)] {
+            assert_eq!(tip(&system, service, food), tip(&reimported, service, food));
+        }
+
+        let mut system = system;
+        system.rule_blocks[0].operators.and = TNorm::Einstein;
+        assert_eq!(
+            system.to_fcl("tipper"),
+            Err(FclExportError::TNorm(TNorm::Einstein))
+        );
+    }
+}
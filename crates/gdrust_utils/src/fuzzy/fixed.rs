@@ -0,0 +1,1194 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::hash::Hash;
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use super::compiled::Condition;
+use super::{
+    sorted_by_output, Consequent, Defuzzifier, FuzzySystem, Hedge, Implication, Inference,
+    LogicOperators, Negation, OutOfRange, SNorm, Shape, TNorm,
+};
+
+/// A Q16.16 fixed-point number, counting 1/65536ths in an `i32`.
+///
+/// All arithmetic, including [`Fixed::exp`], [`Fixed::ln`] and [`Fixed::powf`], is integer
+/// math that saturates instead of overflowing, so it gives bit-identical results on every
+/// machine. The range is about ±32768.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Fixed(i32);
+
+const FRACTION_BITS: u32 = 16;
+/// Fraction bits of the intermediate results of `exp` and `ln`
+const WIDE_BITS: u32 = 32;
+const WIDE_ONE: i128 = 1 << WIDE_BITS;
+/// `ln(2)` with `WIDE_BITS` fraction bits
+const LN_2: i128 = 2_977_044_472;
+
+impl Fixed {
+    pub const ZERO: Fixed = Fixed(0);
+    pub const ONE: Fixed = Fixed(1 << FRACTION_BITS);
+    pub const MIN: Fixed = Fixed(i32::MIN);
+    pub const MAX: Fixed = Fixed(i32::MAX);
+    /// The smallest positive number, 1/65536
+    pub const EPSILON: Fixed = Fixed(1);
+
+    pub const fn from_bits(bits: i32) -> Self {
+        Fixed(bits)
+    }
+
+    pub const fn to_bits(self) -> i32 {
+        self.0
+    }
+
+    pub fn from_int(value: i32) -> Self {
+        Fixed::saturate((value as i64) << FRACTION_BITS)
+    }
+
+    /// The closest fixed-point number, saturating outside of the range and 0 for NaN. The
+    /// same `f32` gives the same number on every machine.
+    pub fn from_f32(value: f32) -> Self {
+        // Scaling by a power of two and rounding are exact, and `as` saturates
+        Fixed((value * Fixed::ONE.0 as f32).round() as i32)
+    }
+
+    pub fn to_f32(self) -> f32 {
+        self.0 as f32 / Fixed::ONE.0 as f32
+    }
+
+    /// Whether `value` is a finite number within the range
+    pub fn represents(value: f32) -> bool {
+        value.is_finite() && value.abs() < 32768.0
+    }
+
+    pub fn abs(self) -> Self {
+        Fixed(self.0.saturating_abs())
+    }
+
+    /// Square root, 0 for negative numbers
+    pub fn sqrt(self) -> Self {
+        if self.0 <= 0 {
+            return Fixed::ZERO;
+        }
+        let n = (self.0 as u64) << FRACTION_BITS;
+        // Newton's method on integers converges to the floor of the root
+        let (mut x, mut y) = (n, n.div_ceil(2));
+        while y < x {
+            x = y;
+            y = (x + n / x) / 2;
+        }
+        Fixed(x as i32)
+    }
+
+    /// `e^self`, saturating for large numbers
+    pub fn exp(self) -> Self {
+        Fixed::from_wide(exp_wide((self.0 as i128) << (WIDE_BITS - FRACTION_BITS)))
+    }
+
+    /// Natural logarithm, [`Fixed::MIN`] for 0 and negative numbers
+    pub fn ln(self) -> Self {
+        match ln_wide(self) {
+            Some(ln) => Fixed::from_wide(ln),
+            None => Fixed::MIN,
+        }
+    }
+
+    /// `self^exponent` for non-negative bases, 0 for negative ones
+    pub fn powf(self, exponent: Fixed) -> Self {
+        match ln_wide(self) {
+            Some(ln) => Fixed::from_wide(exp_wide((ln * exponent.0 as i128) >> FRACTION_BITS)),
+            None if self.0 < 0 || exponent.0 > 0 => Fixed::ZERO,
+            None if exponent.0 == 0 => Fixed::ONE,
+            None => Fixed::MAX,
+        }
+    }
+
+    fn saturate(bits: i64) -> Self {
+        Fixed(bits.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+
+    /// Rounds a number with `WIDE_BITS` fraction bits
+    fn from_wide(wide: i128) -> Self {
+        let shift = WIDE_BITS - FRACTION_BITS;
+        let bits = (wide + (1 << (shift - 1))) >> shift;
+        Fixed(bits.clamp(i32::MIN as i128, i32::MAX as i128) as i32)
+    }
+}
+
+/// `e^x` for `x` with `WIDE_BITS` fraction bits, from `e^x = 2^k * e^r` with `0 <= r < ln 2`
+fn exp_wide(x: i128) -> i128 {
+    let k = x.div_euclid(LN_2);
+    if k > 16 {
+        return (i32::MAX as i128) << WIDE_BITS;
+    }
+    if k < -(WIDE_BITS as i128) {
+        return 0;
+    }
+    let r = x - k * LN_2;
+    let (mut sum, mut term) = (WIDE_ONE, WIDE_ONE);
+    for n in 1..30 {
+        term = term * r / (n * WIDE_ONE);
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+    if k >= 0 {
+        sum << k
+    } else {
+        sum >> -k
+    }
+}
+
+/// `ln(x)` with `WIDE_BITS` fraction bits, from `ln(x) = k * ln 2 + ln(m)` with `1 <= m < 2`
+/// and the series `ln(m) = 2 * atanh((m - 1) / (m + 1))`
+fn ln_wide(x: Fixed) -> Option<i128> {
+    if x.0 <= 0 {
+        return None;
+    }
+    let exponent = 31 - x.0.leading_zeros();
+    let power = 1i128 << exponent;
+    let bits = x.0 as i128;
+    let z = ((bits - power) << WIDE_BITS) / (bits + power);
+    let z_squared = (z * z) >> WIDE_BITS;
+    let (mut sum, mut term) = (z, z);
+    for n in (3..).step_by(2) {
+        term = (term * z_squared) >> WIDE_BITS;
+        if term == 0 {
+            break;
+        }
+        sum += term / n;
+    }
+    Some((exponent as i128 - FRACTION_BITS as i128) * LN_2 + 2 * sum)
+}
+
+impl Add for Fixed {
+    type Output = Fixed;
+
+    fn add(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_add(other.0))
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Fixed;
+
+    fn sub(self, other: Fixed) -> Fixed {
+        Fixed(self.0.saturating_sub(other.0))
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Fixed;
+
+    /// Rounds to the nearest number
+    fn mul(self, other: Fixed) -> Fixed {
+        let product = self.0 as i64 * other.0 as i64;
+        Fixed::saturate((product + (1 << (FRACTION_BITS - 1))) >> FRACTION_BITS)
+    }
+}
+
+impl Div for Fixed {
+    type Output = Fixed;
+
+    /// Rounds towards 0. Dividing by 0 saturates, and `0 / 0` is 0.
+    fn div(self, other: Fixed) -> Fixed {
+        match other.0 {
+            0 if self.0 > 0 => Fixed::MAX,
+            0 if self.0 < 0 => Fixed::MIN,
+            0 => Fixed::ZERO,
+            divisor => Fixed::saturate(((self.0 as i64) << FRACTION_BITS) / divisor as i64),
+        }
+    }
+}
+
+impl Neg for Fixed {
+    type Output = Fixed;
+
+    fn neg(self) -> Fixed {
+        Fixed(self.0.saturating_neg())
+    }
+}
+
+impl Display for Fixed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.to_f32(), f)
+    }
+}
+
+/// Why a [`FuzzySystem`] could not be compiled to fixed-point numbers
+#[derive(Debug, Clone, PartialEq)]
+pub enum FixedError {
+    /// A custom shape, which computes its memberships in `f32`
+    CustomShape,
+    /// A number outside of the range of [`Fixed`], or NaN
+    Unrepresentable(f32),
+}
+
+impl Display for FixedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FixedError::CustomShape => write!(f, "custom shapes can't be evaluated in fixed point"),
+            FixedError::Unrepresentable(value) => {
+                write!(f, "{value} is out of the fixed-point range")
+            }
+        }
+    }
+}
+
+impl Error for FixedError {}
+
+fn fixed(value: f32) -> Result<Fixed, FixedError> {
+    if Fixed::represents(value) {
+        Ok(Fixed::from_f32(value))
+    } else {
+        Err(FixedError::Unrepresentable(value))
+    }
+}
+
+/// A [`FuzzySystem`] compiled to [`Fixed`] arithmetic by [`FuzzySystem::compile_fixed`], so
+/// peers in lockstep multiplayer reach bit-identical decisions.
+///
+/// Works like a [`CompiledSystem`](super::CompiledSystem), except that undefined outputs are
+/// `None`.
+#[derive(Debug, Clone)]
+pub struct FixedSystem<OutputType> {
+    inputs: Vec<FixedInput>,
+    outputs: Vec<OutputType>,
+    terms: Vec<FixedTerm>,
+    rules: Vec<FixedRule>,
+    operators: LogicOperators,
+    mamdani: Option<Mamdani>,
+    scratch: Scratch,
+}
+
+#[derive(Debug, Clone)]
+struct FixedInput {
+    name: Option<String>,
+    /// Universe and out of range policy of the input variable
+    variable: Option<(Fixed, Fixed, OutOfRange)>,
+}
+
+impl FixedInput {
+    /// Mirrors [`FuzzyVariable::resolve`](super::FuzzyVariable::resolve)
+    fn resolve(&self, value: Fixed) -> Option<Fixed> {
+        match self.variable {
+            Some((min, max, _)) if (min..=max).contains(&value) => Some(value),
+            Some((min, max, OutOfRange::Clamp)) => Some(value.clamp(min, max)),
+            Some((_, _, OutOfRange::Ignore)) => None,
+            _ => Some(value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FixedTerm {
+    input: Option<usize>,
+    shape: Option<FixedShape>,
+}
+
+/// [`Shape`] in fixed point, with triangles as trapezoids
+#[derive(Debug, Clone)]
+enum FixedShape {
+    Points(Vec<(Fixed, Fixed)>),
+    Trapezoid {
+        left: Fixed,
+        left_top: Fixed,
+        right_top: Fixed,
+        right: Fixed,
+    },
+    Gaussian {
+        mean: Fixed,
+        sigma: Fixed,
+    },
+    Bell {
+        width: Fixed,
+        slope: Fixed,
+        center: Fixed,
+    },
+    Sigmoid {
+        slope: Fixed,
+        center: Fixed,
+    },
+    Singleton(Fixed),
+}
+
+impl FixedShape {
+    fn new(shape: &Shape) -> Result<Self, FixedError> {
+        Ok(match shape {
+            Shape::Points(points) => FixedShape::Points(
+                points
+                    .iter()
+                    .map(|(x, y)| Ok((fixed(*x)?, fixed(*y)?)))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Shape::Triangle { left, peak, right } => FixedShape::Trapezoid {
+                left: fixed(*left)?,
+                left_top: fixed(*peak)?,
+                right_top: fixed(*peak)?,
+                right: fixed(*right)?,
+            },
+            Shape::Trapezoid {
+                left,
+                left_top,
+                right_top,
+                right,
+            } => FixedShape::Trapezoid {
+                left: fixed(*left)?,
+                left_top: fixed(*left_top)?,
+                right_top: fixed(*right_top)?,
+                right: fixed(*right)?,
+            },
+            Shape::Gaussian { mean, sigma } => FixedShape::Gaussian {
+                mean: fixed(*mean)?,
+                sigma: fixed(*sigma)?,
+            },
+            Shape::Bell {
+                width,
+                slope,
+                center,
+            } => FixedShape::Bell {
+                width: fixed(*width)?,
+                slope: fixed(*slope)?,
+                center: fixed(*center)?,
+            },
+            Shape::Sigmoid { slope, center } => FixedShape::Sigmoid {
+                slope: fixed(*slope)?,
+                center: fixed(*center)?,
+            },
+            Shape::Singleton(value) => FixedShape::Singleton(fixed(*value)?),
+            Shape::Custom(_) => return Err(FixedError::CustomShape),
+        })
+    }
+
+    /// Mirrors [`Shape::membership`]
+    fn membership(&self, input: Fixed) -> Fixed {
+        match self {
+            FixedShape::Points(points) => {
+                let (Some(first), Some(last)) = (points.first(), points.last()) else {
+                    return Fixed::ZERO;
+                };
+                if input <= first.0 {
+                    return first.1;
+                }
+                if input >= last.0 {
+                    return last.1;
+                }
+                for pair in points.windows(2) {
+                    let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+                    if input >= x1 && input <= x2 {
+                        if x2 == x1 {
+                            return y1;
+                        }
+                        // In integers, so the slope isn't rounded on its own
+                        let rise = (y2.0 as i64 - y1.0 as i64) * (input.0 as i64 - x1.0 as i64)
+                            / (x2.0 as i64 - x1.0 as i64);
+                        return Fixed::saturate(y1.0 as i64 + rise);
+                    }
+                }
+                Fixed::ZERO
+            }
+            FixedShape::Trapezoid {
+                left,
+                left_top,
+                right_top,
+                right,
+            } => {
+                if input < *left || input > *right {
+                    Fixed::ZERO
+                } else if input < *left_top {
+                    (input - *left) / (*left_top - *left)
+                } else if input <= *right_top {
+                    Fixed::ONE
+                } else {
+                    (*right - input) / (*right - *right_top)
+                }
+            }
+            FixedShape::Gaussian { mean, sigma } => {
+                let distance = (input - *mean) / *sigma;
+                (-(distance * distance) / Fixed::from_int(2)).exp()
+            }
+            FixedShape::Bell {
+                width,
+                slope,
+                center,
+            } => {
+                let distance = ((input - *center) / *width).abs();
+                Fixed::ONE / (Fixed::ONE + distance.powf(Fixed::from_int(2) * *slope))
+            }
+            FixedShape::Sigmoid { slope, center } => {
+                Fixed::ONE / (Fixed::ONE + (-*slope * (input - *center)).exp())
+            }
+            FixedShape::Singleton(value) => {
+                if input == *value {
+                    Fixed::ONE
+                } else {
+                    Fixed::ZERO
+                }
+            }
+        }
+    }
+}
+
+/// Mirrors [`Antecedent::evaluate`] on precomputed term memberships
+fn evaluate(condition: &Condition, operators: &LogicOperators, memberships: &[Fixed]) -> Fixed {
+    match condition {
+        Condition::Term(term) => memberships[*term],
+        Condition::And(terms) => terms.iter().fold(Fixed::ONE, |acc, t| {
+            and(operators.and, acc, evaluate(t, operators, memberships))
+        }),
+        Condition::Or(terms) => terms.iter().fold(Fixed::ZERO, |acc, t| {
+            or(operators.or, acc, evaluate(t, operators, memberships))
+        }),
+        Condition::Not(inner) => not(operators.not, evaluate(inner, operators, memberships)),
+        Condition::Hedged(hedge, inner) => {
+            apply_hedge(*hedge, evaluate(inner, operators, memberships))
+        }
+    }
+}
+
+/// Mirrors [`TNorm::apply`]
+fn and(tnorm: TNorm, a: Fixed, b: Fixed) -> Fixed {
+    match tnorm {
+        TNorm::Min => a.min(b),
+        TNorm::Product => a * b,
+        TNorm::Lukasiewicz => (a + b - Fixed::ONE).max(Fixed::ZERO),
+        TNorm::Drastic => {
+            if a == Fixed::ONE || b == Fixed::ONE {
+                a.min(b)
+            } else {
+                Fixed::ZERO
+            }
+        }
+        TNorm::Einstein => a * b / (Fixed::from_int(2) - (a + b - a * b)),
+        TNorm::Hamacher(p) => {
+            let p = Fixed::from_f32(p);
+            let denominator = p + (Fixed::ONE - p) * (a + b - a * b);
+            if denominator == Fixed::ZERO {
+                Fixed::ZERO
+            } else {
+                a * b / denominator
+            }
+        }
+    }
+}
+
+/// Mirrors [`SNorm::apply`]
+fn or(snorm: SNorm, a: Fixed, b: Fixed) -> Fixed {
+    match snorm {
+        SNorm::Max => a.max(b),
+        SNorm::ProbabilisticSum => a + b - a * b,
+        SNorm::BoundedSum => (a + b).min(Fixed::ONE),
+        SNorm::Drastic => {
+            if a == Fixed::ZERO || b == Fixed::ZERO {
+                a.max(b)
+            } else {
+                Fixed::ONE
+            }
+        }
+        SNorm::Einstein => (a + b) / (Fixed::ONE + a * b),
+        SNorm::Hamacher(p) => {
+            let p = Fixed::from_f32(p);
+            let denominator = Fixed::ONE + (p - Fixed::ONE) * a * b;
+            if denominator == Fixed::ZERO {
+                Fixed::ONE
+            } else {
+                (a + b + (p - Fixed::from_int(2)) * a * b) / denominator
+            }
+        }
+    }
+}
+
+/// Mirrors [`Negation::apply`]
+fn not(negation: Negation, a: Fixed) -> Fixed {
+    match negation {
+        Negation::Standard => Fixed::ONE - a,
+        Negation::Sugeno(lambda) => (Fixed::ONE - a) / (Fixed::ONE + Fixed::from_f32(lambda) * a),
+        Negation::Yager(w) => {
+            let w = Fixed::from_f32(w);
+            (Fixed::ONE - a.powf(w)).powf(Fixed::ONE / w)
+        }
+    }
+}
+
+/// Mirrors [`Hedge::apply`]
+fn apply_hedge(hedge: Hedge, a: Fixed) -> Fixed {
+    match hedge {
+        Hedge::Very => a * a,
+        Hedge::Extremely => a * a * a,
+        Hedge::Somewhat => a.sqrt(),
+        Hedge::Not => Fixed::ONE - a,
+        Hedge::Indeed => {
+            let two = Fixed::from_int(2);
+            if a <= Fixed::ONE / two {
+                two * a * a
+            } else {
+                Fixed::ONE - two * (Fixed::ONE - a) * (Fixed::ONE - a)
+            }
+        }
+        Hedge::Power(exponent) => a.powf(Fixed::from_f32(exponent)),
+    }
+}
+
+/// Mirrors [`Implication::apply`]
+fn imply(implication: Implication, strength: Fixed, membership: Fixed) -> Fixed {
+    match implication {
+        Implication::Min => strength.min(membership),
+        Implication::Product => strength * membership,
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FixedRule {
+    condition: Condition,
+    weight: Fixed,
+    operators: LogicOperators,
+    /// Index of an existing rule block
+    block: Option<usize>,
+    /// Consequences the inference mode uses, by output index
+    consequences: Vec<(usize, FixedConsequent)>,
+}
+
+#[derive(Debug, Clone)]
+enum FixedConsequent {
+    Constant(Fixed),
+    /// `constant + sum(coefficient * input)`, with `None` for inputs that are never given
+    Linear {
+        constant: Fixed,
+        coefficients: Vec<(Option<usize>, Fixed)>,
+    },
+    /// The output set, if it exists
+    Term(Option<FixedShape>),
+}
+
+#[derive(Debug, Clone)]
+struct Mamdani {
+    defuzzifier: Defuzzifier,
+    resolution: usize,
+    /// Universe of each output, `None` without an output variable
+    universes: Vec<Option<(Fixed, Fixed)>>,
+}
+
+/// Buffers reused by every evaluation
+#[derive(Debug, Clone, Default)]
+struct Scratch {
+    inputs: Vec<Option<Fixed>>,
+    memberships: Vec<Fixed>,
+    strengths: Vec<Fixed>,
+    /// Weighted sum, with twice the fraction bits, and total weight of each output
+    sums: Vec<(i128, i64)>,
+    /// Aggregated membership of each rule block at the current sample
+    blocks: Vec<Fixed>,
+    samples: Vec<(Fixed, Fixed)>,
+}
+
+impl<OutputType: PartialEq> FixedSystem<OutputType> {
+    /// Input names in the order [`FixedSystem::evaluate_into`] reads them. A single `None`
+    /// input is read by every term, like [`FuzzySystem::evaluate`].
+    pub fn inputs(&self) -> impl Iterator<Item = Option<&str>> {
+        self.inputs.iter().map(|input| input.name.as_deref())
+    }
+
+    pub fn input_index(&self, name: &str) -> Option<usize> {
+        self.inputs
+            .iter()
+            .position(|input| input.name.as_deref() == Some(name))
+    }
+
+    /// Outputs in the order [`FixedSystem::evaluate_into`] writes them, the outputs of
+    /// [`FuzzySystem::output_variables`] first
+    pub fn outputs(&self) -> &[OutputType] {
+        &self.outputs
+    }
+
+    pub fn output_index(&self, output: &OutputType) -> Option<usize> {
+        self.outputs.iter().position(|o| o == output)
+    }
+
+    /// Evaluates the system for one value per input, writing one value per output into
+    /// `outputs`, or `None` where the output is undefined.
+    ///
+    /// # Panics
+    ///
+    /// If `inputs` or `outputs` are shorter than [`FixedSystem::inputs`] or
+    /// [`FixedSystem::outputs`].
+    pub fn evaluate_into(&mut self, inputs: &[Fixed], outputs: &mut [Option<Fixed>]) {
+        let Self {
+            inputs: fixed_inputs,
+            terms,
+            rules,
+            operators,
+            mamdani,
+            scratch,
+            ..
+        } = self;
+        assert!(inputs.len() >= fixed_inputs.len(), "one value per input");
+        let outputs = &mut outputs[..scratch.sums.len()];
+
+        for ((resolved, input), value) in scratch
+            .inputs
+            .iter_mut()
+            .zip(fixed_inputs.iter())
+            .zip(inputs)
+        {
+            *resolved = input.resolve(*value);
+        }
+        for (membership, term) in scratch.memberships.iter_mut().zip(terms.iter()) {
+            *membership = match (term.input.and_then(|i| scratch.inputs[i]), &term.shape) {
+                (Some(value), Some(shape)) => shape.membership(value),
+                _ => Fixed::ZERO,
+            };
+        }
+        for (strength, rule) in scratch.strengths.iter_mut().zip(rules.iter()) {
+            *strength =
+                rule.weight * evaluate(&rule.condition, &rule.operators, &scratch.memberships);
+        }
+
+        let Some(mamdani) = mamdani else {
+            scratch.sums.fill((0, 0));
+            for (rule, strength) in rules.iter().zip(&scratch.strengths) {
+                if *strength <= Fixed::ZERO {
+                    continue;
+                }
+                for (output, consequent) in &rule.consequences {
+                    let value = match consequent {
+                        FixedConsequent::Constant(value) => Some(*value),
+                        FixedConsequent::Linear {
+                            constant,
+                            coefficients,
+                        } => {
+                            coefficients
+                                .iter()
+                                .try_fold(*constant, |acc, (input, coefficient)| {
+                                    input
+                                        .and_then(|input| scratch.inputs[input])
+                                        .map(|value| acc + *coefficient * value)
+                                })
+                        }
+                        FixedConsequent::Term(_) => None,
+                    };
+                    if let Some(value) = value {
+                        let (weighted_sum, total_weight) = &mut scratch.sums[*output];
+                        *weighted_sum += value.0 as i128 * strength.0 as i128;
+                        *total_weight += strength.0 as i64;
+                    }
+                }
+            }
+            for (value, (weighted_sum, total_weight)) in outputs.iter_mut().zip(&scratch.sums) {
+                *value = (*total_weight > 0).then(|| {
+                    let average = weighted_sum / *total_weight as i128;
+                    Fixed::saturate(average.clamp(i64::MIN as i128, i64::MAX as i128) as i64)
+                });
+            }
+            return;
+        };
+
+        for (output, value) in outputs.iter_mut().enumerate() {
+            *value = None;
+            let Some((min, max)) = mamdani.universes[output] else {
+                continue;
+            };
+            let fired = rules
+                .iter()
+                .zip(&scratch.strengths)
+                .any(|(rule, strength)| {
+                    *strength > Fixed::ZERO && rule.consequences.iter().any(|(o, _)| *o == output)
+                });
+            if !fired {
+                continue;
+            }
+
+            let steps = scratch.samples.len() as i64 - 1;
+            for (i, sample) in scratch.samples.iter_mut().enumerate() {
+                // From the ends, so no rounding error accumulates over the steps
+                let x = Fixed::saturate(
+                    min.0 as i64 + (max.0 as i64 - min.0 as i64) * i as i64 / steps,
+                );
+                scratch.blocks.fill(Fixed::ZERO);
+                let mut membership = Fixed::ZERO;
+                for (rule, strength) in rules.iter().zip(&scratch.strengths) {
+                    if *strength <= Fixed::ZERO {
+                        continue;
+                    }
+                    for (_, consequent) in rule.consequences.iter().filter(|(o, _)| *o == output) {
+                        let FixedConsequent::Term(Some(shape)) = consequent else {
+                            continue;
+                        };
+                        let implied =
+                            imply(rule.operators.implication, *strength, shape.membership(x));
+                        match rule.block {
+                            Some(block) => {
+                                scratch.blocks[block] =
+                                    or(rule.operators.aggregation, scratch.blocks[block], implied)
+                            }
+                            None => {
+                                membership = or(rule.operators.aggregation, membership, implied)
+                            }
+                        }
+                    }
+                }
+                let membership = scratch.blocks.iter().fold(membership, |acc, block| {
+                    or(operators.aggregation, acc, *block)
+                });
+                *sample = (x, membership);
+            }
+            *value = defuzzify(mamdani.defuzzifier, &scratch.samples);
+        }
+    }
+
+    /// Evaluates the system for one value per input, see [`FixedSystem::evaluate_into`]
+    pub fn evaluate(&mut self, inputs: &[Fixed]) -> Vec<Option<Fixed>> {
+        let mut outputs = vec![None; self.outputs.len()];
+        self.evaluate_into(inputs, &mut outputs);
+        outputs
+    }
+}
+
+/// Mirrors [`Defuzzifier::defuzzify`], with exact maxima
+fn defuzzify(defuzzifier: Defuzzifier, samples: &[(Fixed, Fixed)]) -> Option<Fixed> {
+    let max = samples
+        .iter()
+        .map(|(_, mu)| *mu)
+        .fold(Fixed::ZERO, Fixed::max);
+    if max <= Fixed::ZERO {
+        return None;
+    }
+    let area: i64 = samples.iter().map(|(_, mu)| mu.0 as i64).sum();
+    match defuzzifier {
+        Defuzzifier::Centroid => {
+            let moment: i128 = samples
+                .iter()
+                .map(|(x, mu)| x.0 as i128 * mu.0 as i128)
+                .sum();
+            Some(Fixed::saturate((moment / area as i128) as i64))
+        }
+        Defuzzifier::Bisector => {
+            let left = half_area_position(samples.iter(), area)?;
+            let right = half_area_position(samples.iter().rev(), area)?;
+            Some(Fixed::saturate((left + right) / 2))
+        }
+        Defuzzifier::MeanOfMaximum => {
+            let (sum, count) = samples
+                .iter()
+                .filter(|(_, mu)| *mu == max)
+                .fold((0, 0), |(sum, count), (x, _)| (sum + x.0 as i64, count + 1));
+            Some(Fixed::saturate(sum / count))
+        }
+        Defuzzifier::SmallestOfMaximum => {
+            samples.iter().find(|(_, mu)| *mu == max).map(|(x, _)| *x)
+        }
+        Defuzzifier::LargestOfMaximum => samples
+            .iter()
+            .rev()
+            .find(|(_, mu)| *mu == max)
+            .map(|(x, _)| *x),
+    }
+}
+
+/// First position at which the accumulated membership reaches half of `area`
+fn half_area_position<'a>(
+    samples: impl Iterator<Item = &'a (Fixed, Fixed)>,
+    area: i64,
+) -> Option<i64> {
+    let mut accumulated = 0;
+    for (x, mu) in samples {
+        accumulated += mu.0 as i64;
+        if 2 * accumulated >= area {
+            return Some(x.0 as i64);
+        }
+    }
+    None
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    /// Compiles the system to fixed-point arithmetic for [`FixedSystem::evaluate_into`].
+    ///
+    /// Inputs, outputs and consequences are ordered like [`FuzzySystem::compile`]. Fails for
+    /// custom shapes and for numbers [`Fixed`] can't represent. Operator and hedge parameters
+    /// are rounded to the nearest [`Fixed`].
+    pub fn compile_fixed(&self) -> Result<FixedSystem<OutputType>, FixedError> {
+        let names = self.input_names();
+        let inputs: Vec<FixedInput> = if names.is_empty() {
+            vec![FixedInput {
+                name: None,
+                variable: None,
+            }]
+        } else {
+            names
+                .into_iter()
+                .map(|name| {
+                    let variable = match self.input_variable(&name) {
+                        Some(variable) => {
+                            let (min, max) = (fixed(variable.min)?, fixed(variable.max)?);
+                            // Inverted bounds are swapped, like in the variable
+                            Some((min.min(max), min.max(max), variable.out_of_range))
+                        }
+                        None => None,
+                    };
+                    Ok(FixedInput {
+                        name: Some(name),
+                        variable,
+                    })
+                })
+                .collect::<Result<_, _>>()?
+        };
+        let input_index = |name: Option<&str>| match inputs.as_slice() {
+            [FixedInput { name: None, .. }] => Some(0),
+            _ => inputs
+                .iter()
+                .position(|input| input.name.as_deref() == name),
+        };
+
+        let outputs = self.outputs();
+        let mut terms = Vec::new();
+        // Input and set index of the sets already in `terms`, to share memberships between
+        // rules
+        let mut term_keys: Vec<(Option<usize>, Option<usize>)> = Vec::new();
+        let mut rules = Vec::new();
+        for rule in self.rules.iter().filter(|rule| rule.enabled) {
+            let mut error = None;
+            let condition = self.compile_condition(&rule.condition, &mut |name, category| {
+                let input = input_index(name);
+                let set = self.term(name, category);
+                let key = (input, self.term_index(name, category));
+                if let Some(term) = term_keys.iter().position(|k| *k == key) {
+                    return term;
+                }
+                let shape = match set.map(|set| FixedShape::new(&set.shape)).transpose() {
+                    Ok(shape) => shape,
+                    Err(err) => {
+                        error.get_or_insert(err);
+                        None
+                    }
+                };
+                term_keys.push(key);
+                terms.push(FixedTerm { input, shape });
+                terms.len() - 1
+            });
+            if let Some(error) = error {
+                return Err(error);
+            }
+
+            let mut consequences = Vec::new();
+            for (output, consequent) in sorted_by_output(&rule.consequences) {
+                let index = outputs.iter().position(|o| o == output).expect("listed");
+                let compiled = match (consequent, &self.inference) {
+                    (Consequent::Constant(value), Inference::WeightedAverage) => {
+                        FixedConsequent::Constant(fixed(*value)?)
+                    }
+                    (Consequent::Linear(function), Inference::WeightedAverage) => {
+                        FixedConsequent::Linear {
+                            constant: fixed(function.constant)?,
+                            coefficients: function
+                                .coefficients
+                                .iter()
+                                .map(|(name, coefficient)| {
+                                    Ok((input_index(Some(name)), fixed(*coefficient)?))
+                                })
+                                .collect::<Result<_, _>>()?,
+                        }
+                    }
+                    (Consequent::Term(category), Inference::Mamdani { .. }) => {
+                        FixedConsequent::Term(
+                            self.output_variable(output)
+                                .and_then(|variable| variable.term(category))
+                                .map(|set| FixedShape::new(&set.shape))
+                                .transpose()?,
+                        )
+                    }
+                    _ => continue,
+                };
+                consequences.push((index, compiled));
+            }
+
+            rules.push(FixedRule {
+                condition,
+                weight: fixed(rule.weight)?,
+                operators: *self.rule_operators(rule),
+                block: rule.block.filter(|block| *block < self.rule_blocks.len()),
+                consequences,
+            });
+        }
+
+        let mamdani = match self.inference {
+            Inference::WeightedAverage => None,
+            Inference::Mamdani {
+                defuzzifier,
+                resolution,
+            } => Some(Mamdani {
+                defuzzifier,
+                resolution,
+                universes: outputs
+                    .iter()
+                    .map(|output| {
+                        self.output_variable(output)
+                            .map(|variable| Ok((fixed(variable.min)?, fixed(variable.max)?)))
+                            .transpose()
+                    })
+                    .collect::<Result<_, _>>()?,
+            }),
+        };
+
+        let scratch = Scratch {
+            inputs: vec![None; inputs.len()],
+            memberships: vec![Fixed::ZERO; terms.len()],
+            strengths: vec![Fixed::ZERO; rules.len()],
+            sums: vec![(0, 0); outputs.len()],
+            blocks: vec![Fixed::ZERO; self.rule_blocks.len()],
+            samples: match &mamdani {
+                Some(mamdani) => vec![(Fixed::ZERO, Fixed::ZERO); mamdani.resolution.max(2)],
+                None => Vec::new(),
+            },
+        };
+
+        Ok(FixedSystem {
+            inputs,
+            outputs,
+            terms,
+            rules,
+            operators: self.operators,
+            mamdani,
+            scratch,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{
+        Antecedent, FuzzyRule, FuzzySet, FuzzyVariable, LinearConsequent, RuleBlock,
+    };
+    use std::collections::HashMap;
+
+    fn assert_close(actual: Fixed, expected: f64, tolerance: f64) {
+        assert!(
+            (actual.to_f32() as f64 - expected).abs() <= tolerance,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    fn agent() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (100.0, 0.0)]))
+                .with_term(FuzzySet::gaussian("high", 100.0, 30.0)),
+        );
+        system.add_input_variable(
+            FuzzyVariable::new("distance", 0.0, 50.0)
+                .with_term(FuzzySet::triangle("near", 0.0, 0.0, 30.0))
+                .with_term(FuzzySet::triangle("far", 20.0, 50.0, 50.0))
+                .with_out_of_range(OutOfRange::Ignore),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").and(Antecedent::is("distance", "near")))
+                .with_consequence("flee", 1.0)
+                .with_linear_consequence(
+                    "speed",
+                    LinearConsequent::new(2.0).with_coefficient("distance", 0.1),
+                ),
+        );
+        system.add_rule(
+            FuzzyRule::new(
+                Antecedent::is("health", "high")
+                    .very()
+                    .or(!Antecedent::is("distance", "far")),
+            )
+            .with_consequence("flee", 0.0)
+            .with_consequence("speed", 1.0)
+            .with_weight(0.5),
+        );
+        system
+    }
+
+    /// Checks a fixed-point evaluation against [`FuzzySystem::evaluate_inputs`]
+    fn assert_matches(
+        system: &FuzzySystem<&'static str, &'static str>,
+        fixed: &mut FixedSystem<&'static str>,
+        inputs: &[f32],
+        tolerance: f64,
+    ) {
+        let named: HashMap<&str, f32> = fixed
+            .inputs()
+            .map(|name| name.unwrap())
+            .zip(inputs.iter().copied())
+            .collect();
+        let expected = system.evaluate_inputs(&named);
+        let inputs: Vec<Fixed> = inputs.iter().map(|x| Fixed::from_f32(*x)).collect();
+        let outputs = fixed.evaluate(&inputs);
+        for (output, value) in fixed.outputs().iter().zip(outputs) {
+            match (expected.get(output), value) {
+                (Some(expected), Some(value)) => assert_close(value, *expected as f64, tolerance),
+                (None, None) => {}
+                (expected, value) => panic!("{output}: expected {expected:?}, got {value:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let (a, b) = (Fixed::from_f32(2.5), Fixed::from_f32(-0.75));
+        assert_eq!(Fixed::from_int(2).to_bits(), 2 << 16);
+        assert_eq!((a + b).to_f32(), 1.75);
+        assert_eq!((a - b).to_f32(), 3.25);
+        assert_eq!((a * b).to_f32(), -1.875);
+        assert_eq!((a / b).to_bits(), -218453);
+        assert_eq!(Fixed::from_f32(f32::NAN), Fixed::ZERO);
+
+        // Saturates instead of wrapping
+        assert_eq!(Fixed::from_f32(1e9), Fixed::MAX);
+        assert_eq!(Fixed::MAX + Fixed::ONE, Fixed::MAX);
+        assert_eq!(Fixed::from_int(-20000) * Fixed::from_int(20000), Fixed::MIN);
+        assert_eq!(Fixed::ONE / Fixed::ZERO, Fixed::MAX);
+        assert_eq!(Fixed::ZERO / Fixed::ZERO, Fixed::ZERO);
+    }
+
+    #[test]
+    fn test_functions() {
+        for i in 0..=400 {
+            let x = i as f64 / 40.0 - 5.0;
+            assert_close(
+                Fixed::from_f32(x as f32).exp(),
+                x.exp(),
+                1e-4 * x.exp().max(1.0),
+            );
+        }
+        for i in 1..=400 {
+            let x = i as f64 / 16.0;
+            let fixed = Fixed::from_f32(x as f32);
+            assert_close(fixed.ln(), x.ln(), 2e-5);
+            assert_close(fixed.sqrt(), x.sqrt(), 2e-5);
+            assert_close(fixed.powf(Fixed::from_f32(0.5)), x.sqrt(), 1e-4 * x.sqrt());
+        }
+        assert_eq!(Fixed::from_int(20).exp(), Fixed::MAX);
+        assert_eq!(Fixed::from_int(-20).exp(), Fixed::ZERO);
+        assert_eq!(Fixed::ZERO.ln(), Fixed::MIN);
+        assert_eq!(Fixed::ZERO.powf(Fixed::ZERO), Fixed::ONE);
+        assert_eq!(Fixed::from_int(4).sqrt(), Fixed::from_int(2));
+    }
+
+    #[test]
+    fn test_matches_float_evaluation() {
+        let system = agent();
+        let mut fixed = system.compile_fixed().unwrap();
+        assert_eq!(
+            fixed.inputs().collect::<Vec<_>>(),
+            vec![Some("health"), Some("distance")]
+        );
+        for inputs in [
+            [10.0, 5.0],
+            [50.0, 25.0],
+            [90.0, 45.0],
+            [150.0, 10.0],
+            [10.0, 80.0],
+        ] {
+            assert_matches(&system, &mut fixed, &inputs, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_shared_terms_and_inverted_bounds() {
+        let mut system = agent();
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").or(Antecedent::is("distance", "far")))
+                .with_consequence("flee", 0.5),
+        );
+        system.input_variables[0].min = 100.0;
+        system.input_variables[0].max = 0.0;
+        let mut fixed = system.compile_fixed().unwrap();
+
+        // Terms are shared by input and set, and out of range inputs clamp into the swapped
+        // bounds
+        assert_eq!(fixed.terms.len(), 4);
+        for inputs in [[150.0, 10.0], [-20.0, 40.0], [50.0, 25.0]] {
+            assert_matches(&system, &mut fixed, &inputs, 1e-3);
+        }
+    }
+
+    #[test]
+    fn test_matches_mamdani_evaluation() {
+        let mut system = agent();
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_output_variable(
+            "flee",
+            FuzzyVariable::new("flee", 0.0, 1.0)
+                .with_term(FuzzySet::triangle("no", 0.0, 0.0, 0.6))
+                .with_term(FuzzySet::triangle("yes", 0.4, 1.0, 1.0)),
+        );
+        let block = system.add_rule_block(RuleBlock::new(
+            "sum",
+            LogicOperators::probabilistic().with_aggregation(SNorm::BoundedSum),
+        ));
+        system.rules[0] = system.rules[0].clone().with_output_term("flee", "yes");
+        system.rules[1] = system.rules[1]
+            .clone()
+            .with_output_term("flee", "no")
+            .in_block(block);
+
+        for defuzzifier in [
+            Defuzzifier::Centroid,
+            Defuzzifier::Bisector,
+            Defuzzifier::MeanOfMaximum,
+            Defuzzifier::SmallestOfMaximum,
+            Defuzzifier::LargestOfMaximum,
+        ] {
+            system.inference = Inference::mamdani(defuzzifier);
+            let mut fixed = system.compile_fixed().unwrap();
+            for inputs in [[10.0, 5.0], [50.0, 25.0], [90.0, 45.0]] {
+                // Maxima are found exactly, so they can land a sample apart from f32
+                assert_matches(&system, &mut fixed, &inputs, 0.011);
+            }
+        }
+    }
+
+    #[test]
+    fn test_order_is_independent_of_hashing() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("any", vec![(0.0, 1.0)]));
+        let mut rule = FuzzyRule::new("any");
+        for output in ["e", "b", "d", "a", "c"] {
+            rule = rule.with_linear_consequence(
+                output,
+                LinearConsequent::new(0.0).with_coefficient(output, 1.0),
+            );
+        }
+        system.add_rule(rule);
+        let fixed = system.compile_fixed().unwrap();
+        assert_eq!(fixed.outputs(), ["a", "b", "c", "d", "e"]);
+        assert_eq!(
+            fixed.inputs().collect::<Vec<_>>(),
+            ["a", "b", "c", "d", "e"].map(Some)
+        );
+    }
+
+    #[test]
+    fn test_results_are_pinned() {
+        // Every peer has to compute exactly these bits, so changes to the arithmetic must
+        // be deliberate. About 0.53846 and 2.21154 in f32.
+        let mut fixed = agent().compile_fixed().unwrap();
+        let flee = fixed.output_index(&"flee").unwrap();
+        let speed = fixed.output_index(&"speed").unwrap();
+        let outputs = fixed.evaluate(&[Fixed::from_int(40), Fixed::from_f32(12.5)]);
+        assert_eq!(outputs[flee].map(Fixed::to_bits), Some(35_288));
+        assert_eq!(outputs[speed].map(Fixed::to_bits), Some(144_937));
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut system = agent();
+        system.input_variables[0].max = 1e6;
+        assert_eq!(
+            system.compile_fixed().unwrap_err(),
+            FixedError::Unrepresentable(1e6)
+        );
+
+        let mut system = agent();
+        system.input_variables[1].terms[0].shape = Shape::custom(|x: f32| x);
+        assert_eq!(system.compile_fixed().unwrap_err(), FixedError::CustomShape);
+    }
+}
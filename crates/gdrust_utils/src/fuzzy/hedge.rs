@@ -0,0 +1,94 @@
+/// Linguistic modifier applied to a membership degree, as in "IF enemy_count IS very high"
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hedge {
+    /// Concentration, `a^2`
+    Very,
+    /// Stronger concentration, `a^3`
+    Extremely,
+    /// Dilation, `sqrt(a)`
+    Somewhat,
+    /// Negation, `1 - a`
+    Not,
+    /// Contrast intensification, pushing degrees above 0.5 up and below 0.5 down
+    Indeed,
+    /// `a^exponent`, concentrating for exponents above 1 and dilating below 1
+    Power(f32),
+}
+
+impl Hedge {
+    pub fn apply(&self, membership: f32) -> f32 {
+        match *self {
+            Hedge::Very => membership * membership,
+            Hedge::Extremely => membership * membership * membership,
+            Hedge::Somewhat => membership.sqrt(),
+            Hedge::Not => 1.0 - membership,
+            Hedge::Indeed => {
+                if membership <= 0.5 {
+                    2.0 * membership * membership
+                } else {
+                    1.0 - 2.0 * (1.0 - membership) * (1.0 - membership)
+                }
+            }
+            Hedge::Power(exponent) => membership.powf(exponent),
+        }
+    }
+
+    /// Applies hedges written in reading order, so `[Very, Somewhat]` means "very somewhat"
+    /// and `Somewhat` is applied first
+    pub fn apply_all(hedges: &[Hedge], membership: f32) -> f32 {
+        hedges
+            .iter()
+            .rev()
+            .fold(membership, |membership, hedge| hedge.apply(membership))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concentration_and_dilation() {
+        assert_eq!(Hedge::Very.apply(0.5), 0.25);
+        assert_eq!(Hedge::Extremely.apply(0.5), 0.125);
+        assert_eq!(Hedge::Somewhat.apply(0.25), 0.5);
+        assert_eq!(Hedge::Power(4.0).apply(0.5), 0.0625);
+    }
+
+    #[test]
+    fn test_negation() {
+        assert_eq!(Hedge::Not.apply(0.25), 0.75);
+    }
+
+    #[test]
+    fn test_intensification() {
+        assert_eq!(Hedge::Indeed.apply(0.25), 0.125);
+        assert_eq!(Hedge::Indeed.apply(0.5), 0.5);
+        assert_eq!(Hedge::Indeed.apply(0.75), 0.875);
+        assert_eq!(Hedge::Indeed.apply(0.0), 0.0);
+        assert_eq!(Hedge::Indeed.apply(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_hedges_keep_bounds() {
+        for hedge in [
+            Hedge::Very,
+            Hedge::Extremely,
+            Hedge::Somewhat,
+            Hedge::Indeed,
+            Hedge::Power(0.3),
+        ] {
+            assert_eq!(hedge.apply(0.0), 0.0, "{hedge:?}");
+            assert_eq!(hedge.apply(1.0), 1.0, "{hedge:?}");
+        }
+    }
+
+    #[test]
+    fn test_apply_all_reading_order() {
+        // "not very" = not(very(a)), "very not" = very(not(a))
+        assert_eq!(Hedge::apply_all(&[Hedge::Not, Hedge::Very], 0.5), 0.75);
+        assert_eq!(Hedge::apply_all(&[Hedge::Very, Hedge::Not], 0.25), 0.5625);
+        assert_eq!(Hedge::apply_all(&[], 0.3), 0.3);
+    }
+}
@@ -0,0 +1,184 @@
+/// How a [`FuzzySystem`](super::FuzzySystem) turns fired rules into crisp outputs
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Inference {
+    /// Weighted average of the constant and linear consequences of all fired rules
+    /// (Takagi-Sugeno). Output term consequences are ignored.
+    #[default]
+    WeightedAverage,
+    /// Output term consequences are clipped or scaled by the rule strength, combined with the
+    /// [aggregation](super::LogicOperators::aggregation) s-norm and defuzzified over
+    /// `resolution` samples of the output universe. Constant and linear consequences are
+    /// ignored.
+    Mamdani {
+        defuzzifier: Defuzzifier,
+        resolution: usize,
+    },
+}
+
+impl Inference {
+    /// Number of samples used when no resolution is given
+    pub const DEFAULT_RESOLUTION: usize = 101;
+
+    pub fn mamdani(defuzzifier: Defuzzifier) -> Self {
+        Inference::Mamdani {
+            defuzzifier,
+            resolution: Self::DEFAULT_RESOLUTION,
+        }
+    }
+}
+
+/// Method used to reduce an aggregated output set to a crisp value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Defuzzifier {
+    /// Center of gravity of the area under the curve
+    #[default]
+    Centroid,
+    /// Position that splits the area under the curve in two equal halves
+    Bisector,
+    /// Mean of all positions with maximal membership
+    MeanOfMaximum,
+    /// Smallest position with maximal membership
+    SmallestOfMaximum,
+    /// Largest position with maximal membership
+    LargestOfMaximum,
+}
+
+impl Defuzzifier {
+    /// Defuzzifies `(x, membership)` samples ordered by `x`. Returns `None` when the set is
+    /// empty everywhere.
+    pub fn defuzzify(&self, samples: &[(f32, f32)]) -> Option<f32> {
+        let max = samples.iter().map(|(_, mu)| *mu).fold(0.0, f32::max);
+        if max <= 0.0 {
+            return None;
+        }
+
+        // Samples within this distance of the maximum count as maximal
+        let at_max = |mu: f32| max - mu <= 1e-6;
+
+        match self {
+            Defuzzifier::Centroid => {
+                let area: f32 = samples.iter().map(|(_, mu)| mu).sum();
+                let moment: f32 = samples.iter().map(|(x, mu)| x * mu).sum();
+                Some(moment / area)
+            }
+            Defuzzifier::Bisector => {
+                // Any position in a zero-area gap splits the area, so take the middle of the
+                // positions found scanning from the left and from the right
+                let half = samples.iter().map(|(_, mu)| mu).sum::<f32>() / 2.0;
+                let left = half_area_position(samples.iter(), half)?;
+                let right = half_area_position(samples.iter().rev(), half)?;
+                Some((left + right) / 2.0)
+            }
+            Defuzzifier::MeanOfMaximum => {
+                let (sum, count) = samples
+                    .iter()
+                    .filter(|(_, mu)| at_max(*mu))
+                    .fold((0.0, 0), |(sum, count), (x, _)| (sum + x, count + 1));
+                Some(sum / count as f32)
+            }
+            Defuzzifier::SmallestOfMaximum => {
+                samples.iter().find(|(_, mu)| at_max(*mu)).map(|(x, _)| *x)
+            }
+            Defuzzifier::LargestOfMaximum => samples
+                .iter()
+                .rev()
+                .find(|(_, mu)| at_max(*mu))
+                .map(|(x, _)| *x),
+        }
+    }
+}
+
+/// First position at which the accumulated membership reaches `half`
+fn half_area_position<'a>(samples: impl Iterator<Item = &'a (f32, f32)>, half: f32) -> Option<f32> {
+    let mut accumulated = 0.0;
+    for &(x, mu) in samples {
+        accumulated += mu;
+        if accumulated >= half {
+            return Some(x);
+        }
+    }
+    None
+}
+
+/// `resolution` evenly spaced positions from `min` to `max`, both included
+pub(crate) fn sample_positions(min: f32, max: f32, resolution: usize) -> impl Iterator<Item = f32> {
+    let resolution = resolution.max(2);
+    let step = (max - min) / (resolution - 1) as f32;
+    (0..resolution).map(move |i| min + step * i as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(f: impl Fn(f32) -> f32) -> Vec<(f32, f32)> {
+        sample_positions(0.0, 10.0, 101)
+            .map(|x| (x, f(x)))
+            .collect()
+    }
+
+    fn trapezoid(x: f32) -> f32 {
+        // Ramps up from 2 to 4, flat until 6, ramps down to 8
+        if x <= 2.0 || x >= 8.0 {
+            0.0
+        } else if x < 4.0 {
+            (x - 2.0) / 2.0
+        } else if x <= 6.0 {
+            1.0
+        } else {
+            (8.0 - x) / 2.0
+        }
+    }
+
+    #[test]
+    fn test_sample_positions() {
+        let xs: Vec<f32> = sample_positions(0.0, 1.0, 5).collect();
+        assert_eq!(xs, vec![0.0, 0.25, 0.5, 0.75, 1.0]);
+    }
+
+    #[test]
+    fn test_empty_set() {
+        let samples = samples(|_| 0.0);
+        assert_eq!(Defuzzifier::Centroid.defuzzify(&samples), None);
+        assert_eq!(Defuzzifier::Bisector.defuzzify(&samples), None);
+        assert_eq!(Defuzzifier::MeanOfMaximum.defuzzify(&samples), None);
+        assert_eq!(Defuzzifier::Centroid.defuzzify(&[]), None);
+    }
+
+    #[test]
+    fn test_symmetric_set() {
+        let samples = samples(trapezoid);
+        for defuzzifier in [
+            Defuzzifier::Centroid,
+            Defuzzifier::Bisector,
+            Defuzzifier::MeanOfMaximum,
+        ] {
+            let value = defuzzifier.defuzzify(&samples).unwrap();
+            assert!((value - 5.0).abs() < 0.11, "{defuzzifier:?} gave {value}");
+        }
+        assert!((Defuzzifier::SmallestOfMaximum.defuzzify(&samples).unwrap() - 4.0).abs() < 1e-4);
+        assert!((Defuzzifier::LargestOfMaximum.defuzzify(&samples).unwrap() - 6.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_skewed_set() {
+        // Right triangle rising from 0 to 10: centroid at 2/3, bisector at 10 / sqrt(2)
+        let samples = samples(|x| x / 10.0);
+        let centroid = Defuzzifier::Centroid.defuzzify(&samples).unwrap();
+        let bisector = Defuzzifier::Bisector.defuzzify(&samples).unwrap();
+
+        assert!((centroid - 20.0 / 3.0).abs() < 0.05);
+        assert!((bisector - 10.0 / 2f32.sqrt()).abs() < 0.15);
+        assert_eq!(Defuzzifier::MeanOfMaximum.defuzzify(&samples), Some(10.0));
+    }
+
+    #[test]
+    fn test_bisector_with_gap() {
+        // Two equal blocks on 0..2 and 8..10: any position between them splits the area
+        let samples = samples(|x| if !(2.0..=8.0).contains(&x) { 1.0 } else { 0.0 });
+        let bisector = Defuzzifier::Bisector.defuzzify(&samples).unwrap();
+        assert!((bisector - 5.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,273 @@
+use std::fmt;
+use std::hash::Hash;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use super::FuzzySystem;
+
+/// Error returned when loading or saving a [`FuzzySystem`] as a data file
+#[derive(Debug)]
+pub enum DataError {
+    Io(std::io::Error),
+    #[cfg(feature = "json")]
+    Json(serde_json::Error),
+    /// Reading RON failed at the line and column of the span
+    #[cfg(feature = "ron")]
+    Ron(ron::error::SpannedError),
+    /// Writing RON failed
+    #[cfg(feature = "ron")]
+    RonWrite(ron::Error),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataError::Io(error) => write!(f, "io error: {error}"),
+            #[cfg(feature = "json")]
+            DataError::Json(error) => write!(f, "json error: {error}"),
+            #[cfg(feature = "ron")]
+            DataError::Ron(error) => write!(f, "ron error: {error}"),
+            #[cfg(feature = "ron")]
+            DataError::RonWrite(error) => write!(f, "ron error: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for DataError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DataError::Io(error) => Some(error),
+            #[cfg(feature = "json")]
+            DataError::Json(error) => Some(error),
+            #[cfg(feature = "ron")]
+            DataError::Ron(error) => Some(error),
+            #[cfg(feature = "ron")]
+            DataError::RonWrite(error) => Some(error),
+        }
+    }
+}
+
+impl From<std::io::Error> for DataError {
+    fn from(error: std::io::Error) -> Self {
+        DataError::Io(error)
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<serde_json::Error> for DataError {
+    fn from(error: serde_json::Error) -> Self {
+        DataError::Json(error)
+    }
+}
+
+#[cfg(feature = "ron")]
+impl From<ron::Error> for DataError {
+    fn from(error: ron::Error) -> Self {
+        DataError::RonWrite(error)
+    }
+}
+
+#[cfg(feature = "ron")]
+impl From<ron::error::SpannedError> for DataError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        DataError::Ron(error)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Serialize + DeserializeOwned,
+    OutputType: Serialize + DeserializeOwned + Eq + Hash,
+{
+    pub fn from_json_str(json: &str) -> Result<Self, DataError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    pub fn to_json_string(&self) -> Result<String, DataError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn load_json(path: impl AsRef<Path>) -> Result<Self, DataError> {
+        Self::from_json_str(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save_json(&self, path: impl AsRef<Path>) -> Result<(), DataError> {
+        Ok(std::fs::write(path, self.to_json_string()?)?)
+    }
+}
+
+#[cfg(feature = "ron")]
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Serialize + DeserializeOwned,
+    OutputType: Serialize + DeserializeOwned + Eq + Hash,
+{
+    pub fn from_ron_str(ron: &str) -> Result<Self, DataError> {
+        Ok(ron::from_str(ron)?)
+    }
+
+    pub fn to_ron_string(&self) -> Result<String, DataError> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn load_ron(path: impl AsRef<Path>) -> Result<Self, DataError> {
+        Self::from_ron_str(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save_ron(&self, path: impl AsRef<Path>) -> Result<(), DataError> {
+        Ok(std::fs::write(path, self.to_ron_string()?)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::fuzzy::{
+        Antecedent, FuzzyRule, FuzzySet, FuzzyVariable, Inference, LinearConsequent, OutOfRange,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, serde::Deserialize)]
+    enum Output {
+        Aggression,
+        Speed,
+    }
+
+    fn system() -> FuzzySystem<String, Output> {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_out_of_range(OutOfRange::Ignore)
+                .with_term(FuzzySet::new("low".into(), vec![(0.0, 1.0), (50.0, 0.0)]))
+                .with_term(FuzzySet::gaussian("high".into(), 100.0, 20.0)),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low".to_string()).very())
+                .with_consequence(Output::Aggression, 0.2)
+                .with_linear_consequence(
+                    Output::Speed,
+                    LinearConsequent::new(1.0).with_coefficient("health", 0.01),
+                ),
+        );
+        system.add_rule(
+            FuzzyRule::new(!Antecedent::is("health", "low".to_string()))
+                .with_consequence(Output::Aggression, 0.9)
+                .with_consequence(Output::Speed, 1.5),
+        );
+        system
+    }
+
+    fn assert_same_outputs(a: &FuzzySystem<String, Output>, b: &FuzzySystem<String, Output>) {
+        for health in [0.0, 25.0, 60.0, 100.0, 150.0] {
+            let inputs = HashMap::from([("health", health)]);
+            assert_eq!(a.evaluate_inputs(&inputs), b.evaluate_inputs(&inputs));
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_round_trip() {
+        let system = system();
+        let loaded = FuzzySystem::from_json_str(&system.to_json_string().unwrap()).unwrap();
+
+        assert_eq!(
+            loaded.input_variables[0].terms,
+            system.input_variables[0].terms
+        );
+        assert_eq!(loaded.input_variables[0].out_of_range, OutOfRange::Ignore);
+        assert_eq!(loaded.rules[0].condition, system.rules[0].condition);
+        assert_same_outputs(&system, &loaded);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_defaults() {
+        let json = r#"{
+            "input_variables": [{
+                "name": "health",
+                "min": 0.0,
+                "max": 100.0,
+                "terms": [{ "category": "low", "shape": { "Points": [[0.0, 1.0], [50.0, 0.0]] } }]
+            }],
+            "rules": [{
+                "condition": { "Is": { "input": "health", "category": "low" } },
+                "consequences": { "Aggression": { "Constant": 0.5 } }
+            }]
+        }"#;
+        let system: FuzzySystem<String, Output> = FuzzySystem::from_json_str(json).unwrap();
+
+        assert_eq!(system.input_variables[0].out_of_range, OutOfRange::Clamp);
+        assert_eq!(system.inference, Inference::WeightedAverage);
+        assert_eq!(
+            system.evaluate_inputs(&HashMap::from([("health", 0.0)]))[&Output::Aggression],
+            0.5
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_file() {
+        let path = std::env::temp_dir().join("gdrust_utils_fuzzy_test.json");
+        let system = system();
+        system.save_json(&path).unwrap();
+        let loaded = FuzzySystem::load_json(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_same_outputs(&system, &loaded);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_errors() {
+        let error = FuzzySystem::<String, Output>::from_json_str("{ \"rules\": 3 }").unwrap_err();
+        assert!(matches!(error, DataError::Json(_)));
+
+        let error =
+            FuzzySystem::<String, Output>::load_json("/nonexistent/system.json").unwrap_err();
+        assert!(matches!(error, DataError::Io(_)));
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_ron_round_trip() {
+        let mut system = system();
+        system.inference = Inference::mamdani(crate::fuzzy::Defuzzifier::Bisector);
+        system.add_output_set(Output::Speed, FuzzySet::singleton("fast".into(), 2.0));
+
+        let loaded = FuzzySystem::from_ron_str(&system.to_ron_string().unwrap()).unwrap();
+
+        assert_eq!(loaded.inference, system.inference);
+        assert_eq!(
+            loaded.output_variable(&Output::Speed).unwrap().terms,
+            system.output_variable(&Output::Speed).unwrap().terms
+        );
+        assert_same_outputs(&system, &loaded);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_ron_file() {
+        let path = std::env::temp_dir().join("gdrust_utils_fuzzy_test.ron");
+        let system = system();
+        system.save_ron(&path).unwrap();
+        let loaded = FuzzySystem::load_ron(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_same_outputs(&system, &loaded);
+    }
+
+    #[cfg(feature = "ron")]
+    #[test]
+    fn test_ron_errors() {
+        let error = FuzzySystem::<String, Output>::from_ron_str("(\n    rules: 3,\n)").unwrap_err();
+        assert!(matches!(error, DataError::Ron(_)));
+        assert!(error.to_string().starts_with("ron error: 2:11"), "{error}");
+    }
+}
@@ -0,0 +1,269 @@
+/// Operator used to combine the branches of an `AND` antecedent (t-norm)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TNorm {
+    /// `min(a, b)` (Zadeh)
+    #[default]
+    Min,
+    /// `a * b`
+    Product,
+    /// `max(a + b - 1, 0)`, also called bounded difference
+    Lukasiewicz,
+    /// `min(a, b)` if either is 1, otherwise 0
+    Drastic,
+    /// `a * b / (2 - (a + b - a * b))`
+    Einstein,
+    /// `a * b / (p + (1 - p) * (a + b - a * b))`, with `p >= 0`
+    Hamacher(f32),
+}
+
+impl TNorm {
+    pub fn apply(&self, a: f32, b: f32) -> f32 {
+        match *self {
+            TNorm::Min => a.min(b),
+            TNorm::Product => a * b,
+            TNorm::Lukasiewicz => (a + b - 1.0).max(0.0),
+            TNorm::Drastic => {
+                if a == 1.0 || b == 1.0 {
+                    a.min(b)
+                } else {
+                    0.0
+                }
+            }
+            TNorm::Einstein => a * b / (2.0 - (a + b - a * b)),
+            TNorm::Hamacher(p) => {
+                let denominator = p + (1.0 - p) * (a + b - a * b);
+                if denominator == 0.0 {
+                    0.0
+                } else {
+                    a * b / denominator
+                }
+            }
+        }
+    }
+}
+
+/// Operator used to combine the branches of an `OR` antecedent (s-norm)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SNorm {
+    /// `max(a, b)` (Zadeh)
+    #[default]
+    Max,
+    /// `a + b - a * b`
+    ProbabilisticSum,
+    /// `min(a + b, 1)` (Lukasiewicz), also called bounded sum
+    BoundedSum,
+    /// `max(a, b)` if either is 0, otherwise 1
+    Drastic,
+    /// `(a + b) / (1 + a * b)`
+    Einstein,
+    /// `(a + b + (p - 2) * a * b) / (1 + (p - 1) * a * b)`, with `p >= 0`
+    Hamacher(f32),
+}
+
+impl SNorm {
+    pub fn apply(&self, a: f32, b: f32) -> f32 {
+        match *self {
+            SNorm::Max => a.max(b),
+            SNorm::ProbabilisticSum => a + b - a * b,
+            SNorm::BoundedSum => (a + b).min(1.0),
+            SNorm::Drastic => {
+                if a == 0.0 || b == 0.0 {
+                    a.max(b)
+                } else {
+                    1.0
+                }
+            }
+            SNorm::Einstein => (a + b) / (1.0 + a * b),
+            SNorm::Hamacher(p) => {
+                let denominator = 1.0 + (p - 1.0) * a * b;
+                if denominator == 0.0 {
+                    1.0
+                } else {
+                    (a + b + (p - 2.0) * a * b) / denominator
+                }
+            }
+        }
+    }
+}
+
+/// Operator used for `NOT` antecedents (fuzzy complement)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Negation {
+    /// `1 - a`
+    #[default]
+    Standard,
+    /// `(1 - a) / (1 + lambda * a)`, with `lambda > -1`
+    Sugeno(f32),
+    /// `(1 - a^w)^(1/w)`, with `w > 0`
+    Yager(f32),
+}
+
+impl Negation {
+    pub fn apply(&self, a: f32) -> f32 {
+        match *self {
+            Negation::Standard => 1.0 - a,
+            Negation::Sugeno(lambda) => (1.0 - a) / (1.0 + lambda * a),
+            Negation::Yager(w) => (1.0 - a.powf(w)).powf(1.0 / w),
+        }
+    }
+}
+
+/// How a rule's firing strength shapes its output set during Mamdani inference
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Implication {
+    /// Clips the output set at the firing strength
+    #[default]
+    Min,
+    /// Scales the output set by the firing strength
+    Product,
+}
+
+impl Implication {
+    pub fn apply(&self, strength: f32, membership: f32) -> f32 {
+        match self {
+            Implication::Min => strength.min(membership),
+            Implication::Product => strength * membership,
+        }
+    }
+}
+
+/// The operators a [`FuzzySystem`](super::FuzzySystem) or a
+/// [`RuleBlock`](super::RuleBlock) uses to evaluate rule antecedents, implications and the
+/// aggregation of implied output sets
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LogicOperators {
+    pub and: TNorm,
+    pub or: SNorm,
+    pub not: Negation,
+    pub implication: Implication,
+    /// Combines the implied output sets of fired rules under Mamdani inference
+    pub aggregation: SNorm,
+}
+
+impl LogicOperators {
+    pub fn new(and: TNorm, or: SNorm, not: Negation) -> Self {
+        Self {
+            and,
+            or,
+            not,
+            implication: Implication::default(),
+            aggregation: SNorm::default(),
+        }
+    }
+
+    pub fn with_implication(mut self, implication: Implication) -> Self {
+        self.implication = implication;
+        self
+    }
+
+    pub fn with_aggregation(mut self, aggregation: SNorm) -> Self {
+        self.aggregation = aggregation;
+        self
+    }
+
+    /// Bounded difference for `AND` and bounded sum for `OR`
+    pub fn lukasiewicz() -> Self {
+        Self::new(TNorm::Lukasiewicz, SNorm::BoundedSum, Negation::Standard)
+    }
+
+    /// Product for `AND` and probabilistic sum for `OR`
+    pub fn probabilistic() -> Self {
+        Self::new(TNorm::Product, SNorm::ProbabilisticSum, Negation::Standard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_zadeh() {
+        let ops = LogicOperators::default();
+        assert_eq!(ops.and, TNorm::Min);
+        assert_eq!(ops.or, SNorm::Max);
+        assert_eq!(ops.not, Negation::Standard);
+        assert_eq!(ops.implication, Implication::Min);
+        assert_eq!(ops.aggregation, SNorm::Max);
+    }
+
+    #[test]
+    fn test_implications() {
+        assert_eq!(Implication::Min.apply(0.5, 0.8), 0.5);
+        assert_eq!(Implication::Min.apply(0.5, 0.2), 0.2);
+        assert_eq!(Implication::Product.apply(0.5, 0.8), 0.4);
+    }
+
+    #[test]
+    fn test_tnorms() {
+        assert_eq!(TNorm::Min.apply(0.25, 0.5), 0.25);
+        assert_eq!(TNorm::Product.apply(0.25, 0.5), 0.125);
+        assert_eq!(TNorm::Lukasiewicz.apply(0.25, 0.5), 0.0);
+        assert_eq!(TNorm::Lukasiewicz.apply(0.75, 0.5), 0.25);
+        assert_eq!(TNorm::Drastic.apply(0.75, 0.5), 0.0);
+        assert_eq!(TNorm::Drastic.apply(1.0, 0.5), 0.5);
+        assert!((TNorm::Einstein.apply(0.5, 0.5) - 0.2).abs() < 1e-6);
+        assert!((TNorm::Hamacher(1.0).apply(0.25, 0.5) - 0.125).abs() < 1e-6);
+        assert!((TNorm::Hamacher(0.0).apply(0.5, 0.5) - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(TNorm::Hamacher(0.0).apply(0.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_norm_boundaries() {
+        let tnorms = [
+            TNorm::Min,
+            TNorm::Product,
+            TNorm::Lukasiewicz,
+            TNorm::Drastic,
+            TNorm::Einstein,
+            TNorm::Hamacher(0.0),
+            TNorm::Hamacher(2.0),
+        ];
+        let snorms = [
+            SNorm::Max,
+            SNorm::ProbabilisticSum,
+            SNorm::BoundedSum,
+            SNorm::Drastic,
+            SNorm::Einstein,
+            SNorm::Hamacher(0.0),
+            SNorm::Hamacher(2.0),
+        ];
+        for a in [0.0, 0.3, 0.7, 1.0] {
+            for tnorm in tnorms {
+                // 1 is the identity of t-norms, 0 of s-norms
+                assert!((tnorm.apply(a, 1.0) - a).abs() < 1e-6, "{tnorm:?}");
+                assert!(tnorm.apply(a, 0.7) <= a.min(0.7) + 1e-6, "{tnorm:?}");
+            }
+            for snorm in snorms {
+                assert!((snorm.apply(a, 0.0) - a).abs() < 1e-6, "{snorm:?}");
+                assert!(snorm.apply(a, 0.7) >= a.max(0.7) - 1e-6, "{snorm:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_snorms() {
+        assert_eq!(SNorm::Max.apply(0.25, 0.5), 0.5);
+        assert_eq!(SNorm::ProbabilisticSum.apply(0.25, 0.5), 0.625);
+        assert_eq!(SNorm::BoundedSum.apply(0.25, 0.5), 0.75);
+        assert_eq!(SNorm::BoundedSum.apply(0.75, 0.5), 1.0);
+        assert_eq!(SNorm::Drastic.apply(0.25, 0.5), 1.0);
+        assert_eq!(SNorm::Drastic.apply(0.0, 0.5), 0.5);
+        assert!((SNorm::Einstein.apply(0.5, 0.5) - 0.8).abs() < 1e-6);
+        assert!((SNorm::Hamacher(1.0).apply(0.25, 0.5) - 0.625).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_negations() {
+        assert_eq!(Negation::Standard.apply(0.25), 0.75);
+        assert_eq!(Negation::Sugeno(0.0).apply(0.25), 0.75);
+        assert!((Negation::Sugeno(1.0).apply(0.25) - 0.6).abs() < 1e-6);
+        assert!((Negation::Yager(1.0).apply(0.25) - 0.75).abs() < 1e-6);
+        assert!((Negation::Yager(2.0).apply(0.6) - 0.8).abs() < 1e-6);
+    }
+}
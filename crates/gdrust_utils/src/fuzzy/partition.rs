@@ -0,0 +1,326 @@
+use super::{FuzzySet, FuzzyVariable, Shape};
+
+/// Shape of the sets generated by [`FuzzySet::partition`]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PartitionShape {
+    /// Triangles peaking at their center and reaching 0 at the neighboring centers
+    #[default]
+    Triangle,
+    /// Trapezoids, flat for the `plateau` fraction (0..=1) of the gap to each neighbor
+    Trapezoid { plateau: f32 },
+    /// Bell-shaped Gaussian edges, each normalized against the neighboring set, sampled
+    /// into points. Plain Gaussians never sum to 1.
+    Gaussian,
+}
+
+/// Samples per gap between two centers of Gaussian partitions
+const GAUSSIAN_SAMPLES: usize = 16;
+/// Standard deviation of the Gaussian edges, relative to the gap between two centers
+const GAUSSIAN_SIGMA: f32 = 0.35;
+
+impl<T> FuzzySet<T> {
+    /// Sets for `categories` with evenly spaced centers from `min` to `max`, see
+    /// [`FuzzySet::partition_at`]
+    pub fn partition(
+        categories: impl IntoIterator<Item = T>,
+        min: f32,
+        max: f32,
+        shape: PartitionShape,
+    ) -> Vec<Self> {
+        let categories: Vec<T> = categories.into_iter().collect();
+        let gaps = categories.len().saturating_sub(1).max(1) as f32;
+        let centers: Vec<f32> = (0..categories.len())
+            .map(|i| min + (max - min) * i as f32 / gaps)
+            .collect();
+        Self::partition_at(categories, &centers, shape)
+    }
+
+    /// Sets for `categories`, each fully true at its center in `centers` and fading out
+    /// towards its neighbors. The first and last set are shoulders staying true beyond
+    /// their center, and the memberships of all sets sum to 1 everywhere (a Ruspini
+    /// partition). The sets are sorted by center. Extra categories or centers are ignored,
+    /// and so are categories whose center isn't finite or repeats an earlier one.
+    pub fn partition_at(
+        categories: impl IntoIterator<Item = T>,
+        centers: &[f32],
+        shape: PartitionShape,
+    ) -> Vec<Self> {
+        let mut sets: Vec<(T, f32)> = categories
+            .into_iter()
+            .zip(centers.iter().copied())
+            .filter(|(_, center)| center.is_finite())
+            .collect();
+        sets.sort_by(|a, b| a.1.total_cmp(&b.1));
+        sets.dedup_by(|later, earlier| later.1 == earlier.1);
+        let centers: Vec<f32> = sets.iter().map(|(_, center)| *center).collect();
+        let last = centers.len().saturating_sub(1);
+        sets.into_iter()
+            .enumerate()
+            .map(|(i, (category, center))| {
+                let left = (i > 0).then(|| centers[i - 1]);
+                let right = (i < last).then(|| centers[i + 1]);
+                Self::with_shape(category, partition_shape(shape, left, center, right))
+            })
+            .collect()
+    }
+}
+
+impl<T> FuzzyVariable<T> {
+    /// Adds sets for `categories` evenly spread over the universe, see
+    /// [`FuzzySet::partition_at`]
+    pub fn with_partition(
+        mut self,
+        categories: impl IntoIterator<Item = T>,
+        shape: PartitionShape,
+    ) -> Self {
+        let sets = FuzzySet::partition(categories, self.min, self.max, shape);
+        self.terms.extend(sets);
+        self
+    }
+}
+
+/// The shape of the set at `center`, between the centers of its neighbors if it has them
+fn partition_shape(
+    shape: PartitionShape,
+    left: Option<f32>,
+    center: f32,
+    right: Option<f32>,
+) -> Shape {
+    match shape {
+        PartitionShape::Triangle => match (left, right) {
+            (Some(left), Some(right)) => Shape::Triangle {
+                left,
+                peak: center,
+                right,
+            },
+            _ => Shape::Points(edges(left, center, right, 0.0)),
+        },
+        PartitionShape::Trapezoid { plateau } => {
+            let plateau = plateau.clamp(0.0, 1.0);
+            match (left, right) {
+                (Some(left), Some(right)) => {
+                    let (left_flat, right_flat) = (
+                        (center - left) * plateau / 2.0,
+                        (right - center) * plateau / 2.0,
+                    );
+                    Shape::Trapezoid {
+                        left: left + left_flat,
+                        left_top: center - left_flat,
+                        right_top: center + right_flat,
+                        right: right - right_flat,
+                    }
+                }
+                _ => Shape::Points(edges(left, center, right, plateau)),
+            }
+        }
+        PartitionShape::Gaussian => {
+            let mut points = Vec::new();
+            if let Some(left) = left {
+                points.extend((0..GAUSSIAN_SAMPLES).map(|k| {
+                    let t = k as f32 / GAUSSIAN_SAMPLES as f32;
+                    (left + (center - left) * t, gaussian_edge(1.0 - t))
+                }));
+            }
+            points.push((center, 1.0));
+            if let Some(right) = right {
+                points.extend((1..=GAUSSIAN_SAMPLES).map(|k| {
+                    let t = k as f32 / GAUSSIAN_SAMPLES as f32;
+                    (center + (right - center) * t, gaussian_edge(t))
+                }));
+            }
+            Shape::Points(points)
+        }
+    }
+}
+
+/// Points falling linearly from `center` to the neighboring centers, flat for the `plateau`
+/// fraction of each gap
+fn edges(left: Option<f32>, center: f32, right: Option<f32>, plateau: f32) -> Vec<(f32, f32)> {
+    let mut points = Vec::new();
+    if let Some(left) = left {
+        let flat = (center - left) * plateau / 2.0;
+        points.push((left + flat, 0.0));
+        points.push((center - flat, 1.0));
+    }
+    if let Some(right) = right {
+        let flat = (right - center) * plateau / 2.0;
+        points.push((center + flat, 1.0));
+        points.push((right - flat, 0.0));
+    }
+    if points.is_empty() {
+        points.push((center, 1.0));
+    }
+    points
+}
+
+/// Membership at `t` (0..=1) of the way from the center of a set to the center of its
+/// neighbor. The edges of both sets mirror each other, so they always sum to 1.
+fn gaussian_edge(t: f32) -> f32 {
+    let bell = |t: f32| (-t * t / (2.0 * GAUSSIAN_SIGMA * GAUSSIAN_SIGMA)).exp();
+    let (falling, rising) = (bell(t) - bell(1.0), bell(1.0 - t) - bell(1.0));
+    falling / (falling + rising)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAPES: [PartitionShape; 4] = [
+        PartitionShape::Triangle,
+        PartitionShape::Trapezoid { plateau: 0.0 },
+        PartitionShape::Trapezoid { plateau: 0.5 },
+        PartitionShape::Gaussian,
+    ];
+
+    fn assert_ruspini(sets: &[FuzzySet<&str>], min: f32, max: f32) {
+        for i in 0..=200 {
+            let x = min - 2.0 + (max - min + 4.0) * i as f32 / 200.0;
+            let sum: f32 = sets.iter().map(|set| set.membership(x)).sum();
+            assert!((sum - 1.0).abs() < 1e-5, "sum {sum} at {x}");
+        }
+    }
+
+    #[test]
+    fn test_uniform_triangles() {
+        let sets = FuzzySet::partition(["low", "mid", "high"], 0.0, 10.0, PartitionShape::Triangle);
+        assert_eq!(sets.len(), 3);
+        assert_eq!(
+            sets[1].shape,
+            Shape::Triangle {
+                left: 0.0,
+                peak: 5.0,
+                right: 10.0
+            }
+        );
+        // Shoulders stay true beyond the universe
+        assert_eq!(sets[0].membership(-5.0), 1.0);
+        assert_eq!(sets[2].membership(15.0), 1.0);
+        assert_eq!(sets[0].membership(2.5), 0.5);
+    }
+
+    #[test]
+    fn test_partitions_sum_to_one() {
+        let categories = ["a", "b", "c", "d", "e"];
+        for shape in SHAPES {
+            assert_ruspini(
+                &FuzzySet::partition(categories, -3.0, 7.0, shape),
+                -3.0,
+                7.0,
+            );
+            let centers = [0.0, 1.0, 4.0, 4.5, 10.0];
+            assert_ruspini(
+                &FuzzySet::partition_at(categories, &centers, shape),
+                0.0,
+                10.0,
+            );
+        }
+    }
+
+    #[test]
+    fn test_sets_peak_at_their_center() {
+        let centers = [0.0, 2.0, 8.0];
+        for shape in SHAPES {
+            let sets = FuzzySet::partition_at(["a", "b", "c"], &centers, shape);
+            for (i, set) in sets.iter().enumerate() {
+                for (j, center) in centers.iter().enumerate() {
+                    let expected = if i == j { 1.0 } else { 0.0 };
+                    assert_eq!(set.membership(*center), expected, "{shape:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_hand_written_sets() {
+        let sets = FuzzySet::partition_at(
+            ["easy", "normal", "hard", "nightmare"],
+            &[3.0, 5.0, 7.0, 9.0],
+            PartitionShape::Triangle,
+        );
+        let easy = FuzzySet::new("easy", vec![(0.0, 1.0), (3.0, 1.0), (5.0, 0.0)]);
+        let nightmare = FuzzySet::new("nightmare", vec![(7.0, 0.0), (9.0, 1.0), (10.0, 1.0)]);
+        for i in 0..=20 {
+            let x = i as f32 / 2.0;
+            assert_eq!(sets[0].membership(x), easy.membership(x));
+            assert_eq!(sets[3].membership(x), nightmare.membership(x));
+        }
+    }
+
+    #[test]
+    fn test_trapezoid_plateau() {
+        let sets = FuzzySet::partition(
+            ["low", "mid", "high"],
+            0.0,
+            20.0,
+            PartitionShape::Trapezoid { plateau: 0.5 },
+        );
+        assert_eq!(
+            sets[1].shape,
+            Shape::Trapezoid {
+                left: 2.5,
+                left_top: 7.5,
+                right_top: 12.5,
+                right: 17.5
+            }
+        );
+        assert_eq!(sets[0].membership(2.5), 1.0);
+        assert_eq!(sets[0].membership(5.0), 0.5);
+    }
+
+    #[test]
+    fn test_gaussian_edges_are_bell_shaped() {
+        let sets = FuzzySet::partition(["low", "high"], 0.0, 1.0, PartitionShape::Gaussian);
+        assert!((sets[0].membership(0.5) - 0.5).abs() < 1e-6);
+        // Flatter than a triangle near the center
+        assert!(sets[0].membership(0.125) > 0.95);
+        assert!(sets[0].membership(0.875) < 0.05);
+    }
+
+    #[test]
+    fn test_degenerate_partitions() {
+        assert!(FuzzySet::<&str>::partition([], 0.0, 1.0, PartitionShape::Triangle).is_empty());
+
+        let sets = FuzzySet::partition(["all"], 0.0, 1.0, PartitionShape::Gaussian);
+        assert_eq!(sets[0].membership(-1.0), 1.0);
+        assert_eq!(sets[0].membership(3.0), 1.0);
+
+        let sets = FuzzySet::partition_at(["a", "b", "c"], &[0.0, 1.0], PartitionShape::Triangle);
+        assert_eq!(sets.len(), 2);
+        assert_ruspini(&sets, 0.0, 1.0);
+
+        let sets = FuzzySet::partition_at(["a", "b"], &[0.0, 1.0, 2.0], PartitionShape::Gaussian);
+        assert_eq!(sets.len(), 2);
+        assert_ruspini(&sets, 0.0, 2.0);
+    }
+
+    #[test]
+    fn test_unsorted_and_repeated_centers() {
+        for shape in SHAPES {
+            let sets = FuzzySet::partition_at(
+                ["c", "a", "b", "b2", "d", "e"],
+                &[8.0, 0.0, 2.0, 2.0, f32::NAN, 8.0],
+                shape,
+            );
+            let categories: Vec<&str> = sets.iter().map(|set| set.category).collect();
+            assert_eq!(categories, ["a", "b", "c"]);
+            assert_eq!(sets[0].membership(0.0), 1.0);
+            assert_eq!(sets[2].membership(8.0), 1.0);
+            assert_ruspini(&sets, 0.0, 8.0);
+        }
+
+        // All centers equal, as when fitting constant data
+        let sets = FuzzySet::partition(["low", "high"], 5.0, 5.0, PartitionShape::Triangle);
+        assert_eq!(sets.len(), 1);
+        assert_ruspini(&sets, 5.0, 5.0);
+    }
+
+    #[test]
+    fn test_variable_with_partition() {
+        let variable = FuzzyVariable::new("health", 0.0, 100.0)
+            .with_partition(["low", "medium", "high"], PartitionShape::Triangle);
+        assert_eq!(variable.terms.len(), 3);
+        assert_eq!(variable.membership(&"medium", 50.0), 1.0);
+        assert_eq!(variable.membership(&"high", 75.0), 0.5);
+    }
+}
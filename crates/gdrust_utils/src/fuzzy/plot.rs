@@ -0,0 +1,650 @@
+use std::fmt::{Debug, Write};
+use std::hash::Hash;
+
+use super::{inference, BakeOptions, BakedSystem, FuzzySystem, FuzzyVariable, Shape};
+
+/// Size and sampling of SVG plots
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PlotOptions {
+    /// Width of the image in pixels
+    pub width: usize,
+    /// Height of the image in pixels
+    pub height: usize,
+    /// Number of samples across each input universe
+    pub resolution: usize,
+}
+
+impl Default for PlotOptions {
+    fn default() -> Self {
+        Self {
+            width: 640,
+            height: 320,
+            resolution: 201,
+        }
+    }
+}
+
+impl PlotOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_size(mut self, width: usize, height: usize) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn with_resolution(mut self, resolution: usize) -> Self {
+        self.resolution = resolution;
+        self
+    }
+}
+
+/// Stroke colors of successive terms or curves
+const COLORS: [&str; 8] = [
+    "#1f77b4", "#d62728", "#2ca02c", "#ff7f0e", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+/// Marks of successive terms in ASCII charts
+const MARKS: [char; 8] = ['*', '+', 'o', '#', 'x', '%', '@', '&'];
+/// ASCII heatmap shades from low to high
+const SHADES: [char; 10] = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+/// Space around the plot area of SVGs for axis labels, in pixels
+const MARGIN: f32 = 40.0;
+
+impl<T: Debug> FuzzyVariable<T> {
+    /// Draws the membership functions of all terms over the universe as SVG
+    pub fn to_svg(&self, options: &PlotOptions) -> String {
+        let frame = Frame::new(options, self.min, self.max, 0.0, 1.0);
+        let mut svg = frame.begin(&self.name);
+        for (index, term) in self.terms.iter().enumerate() {
+            let color = COLORS[index % COLORS.len()];
+            let points: Vec<(f32, f32)> = self
+                .term_positions(&term.shape, options.resolution)
+                .map(|x| (x, term.membership(x)))
+                .collect();
+            frame.polyline(&mut svg, &points, color);
+            frame.legend(&mut svg, index, &label(&term.category), color);
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Draws the membership functions of all terms as a `columns` x `rows` character chart
+    /// with a legend, for terminals and test snapshots
+    pub fn to_ascii(&self, columns: usize, rows: usize) -> String {
+        let mut chart = Chart::new(columns, rows);
+        for (index, term) in self.terms.iter().enumerate() {
+            let mark = MARKS[index % MARKS.len()];
+            for column in 0..chart.columns {
+                // Zero is left out, so overlapping baselines don't hide each other
+                let membership = term.membership(chart.position(column, self.min, self.max));
+                if membership > 0.0 {
+                    chart.plot(column, membership, mark);
+                }
+            }
+        }
+        let mut ascii = chart.render("1", "0", self.min, self.max);
+        let legend: Vec<String> = self
+            .terms
+            .iter()
+            .enumerate()
+            .map(|(index, term)| {
+                format!("{} {}", MARKS[index % MARKS.len()], label(&term.category))
+            })
+            .collect();
+        let _ = writeln!(ascii, "{}", legend.join("  "));
+        ascii
+    }
+
+    /// Sample positions across the universe, plus the corners of the shape so that sharp
+    /// peaks and singletons show up
+    fn term_positions(&self, shape: &Shape, resolution: usize) -> impl Iterator<Item = f32> {
+        let mut positions: Vec<f32> =
+            inference::sample_positions(self.min, self.max, resolution).collect();
+        positions.extend(shape.corners().into_iter().filter(|x| self.contains(*x)));
+        positions.sort_by(f32::total_cmp);
+        positions.into_iter()
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord + Debug,
+{
+    /// Draws `output` over the input universe as a curve for systems with one input, or as
+    /// a heatmap over both universes for systems with two. Returns `None` for unknown
+    /// outputs and systems with more inputs.
+    pub fn response_svg(&self, output: &OutputType, options: &PlotOptions) -> Option<String> {
+        let response = Response::new(self, output, options.resolution)?;
+        let (min, max) = response.range();
+        let (x, y) = (&response.axes[0], response.axes.get(1));
+
+        let mut svg;
+        match y {
+            None => {
+                let frame = Frame::new(options, x.min, x.max, min, max);
+                svg = frame.begin(&format!("{} -> {}", x.name, label(output)));
+                // Undefined outputs split the curve
+                let samples: Vec<Option<(f32, f32)>> =
+                    inference::sample_positions(x.min, x.max, options.resolution)
+                        .map(|position| response.value(&[position]).map(|value| (position, value)))
+                        .collect();
+                for segment in samples.split(Option::is_none) {
+                    let points: Vec<(f32, f32)> = segment.iter().flatten().copied().collect();
+                    frame.polyline(&mut svg, &points, COLORS[0]);
+                }
+            }
+            Some(y) => {
+                let frame = Frame::new(options, x.min, x.max, y.min, y.max);
+                svg = frame.begin(&format!("{}, {} -> {}", x.name, y.name, label(output)));
+                let cells = options.resolution.clamp(2, 100);
+                let (width, height) = (frame.width / cells as f32, frame.height / cells as f32);
+                for row in 0..cells {
+                    for column in 0..cells {
+                        let position = [
+                            x.min + (x.max - x.min) * (column as f32 + 0.5) / cells as f32,
+                            y.min + (y.max - y.min) * (row as f32 + 0.5) / cells as f32,
+                        ];
+                        let fill = match response.value(&position) {
+                            Some(value) => color(normalize(value, min, max)),
+                            None => "#cccccc".to_string(),
+                        };
+                        let _ = writeln!(
+                            svg,
+                            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="{fill}"/>"#,
+                            MARGIN + column as f32 * width,
+                            MARGIN + frame.height - (row + 1) as f32 * height,
+                            width + 0.1,
+                            height + 0.1,
+                        );
+                    }
+                }
+                let _ = writeln!(
+                    svg,
+                    r#"<text x="{:.1}" y="{:.1}" font-size="10" text-anchor="end">{}: {} (dark) .. {} (light)</text>"#,
+                    MARGIN + frame.width,
+                    MARGIN - 6.0,
+                    escape(&label(output)),
+                    number(min),
+                    number(max),
+                );
+            }
+        }
+        svg.push_str("</svg>\n");
+        Some(svg)
+    }
+
+    /// Draws `output` like [`FuzzySystem::response_svg`] as a `columns` x `rows` character
+    /// chart. Heatmaps shade low values light and high values dark, with `?` where the
+    /// output is undefined.
+    pub fn response_ascii(
+        &self,
+        output: &OutputType,
+        columns: usize,
+        rows: usize,
+    ) -> Option<String> {
+        let response = Response::new(self, output, columns.max(rows))?;
+        let (min, max) = response.range();
+        let x = &response.axes[0];
+        let mut chart = Chart::new(columns, rows);
+
+        let Some(y) = response.axes.get(1) else {
+            for column in 0..chart.columns {
+                let position = chart.position(column, x.min, x.max);
+                if let Some(value) = response.value(&[position]) {
+                    chart.plot(column, normalize(value, min, max), '*');
+                }
+            }
+            let mut ascii = chart.render(&number(max), &number(min), x.min, x.max);
+            let _ = writeln!(ascii, "{} -> {}", x.name, label(output));
+            return Some(ascii);
+        };
+
+        for row in 0..chart.rows {
+            let position_y = y.max - (y.max - y.min) * row as f32 / (chart.rows - 1) as f32;
+            for column in 0..chart.columns {
+                let position = [chart.position(column, x.min, x.max), position_y];
+                chart.cells[row][column] = match response.value(&position) {
+                    Some(value) => {
+                        let shade = normalize(value, min, max) * (SHADES.len() - 1) as f32;
+                        SHADES[shade.round() as usize]
+                    }
+                    None => '?',
+                };
+            }
+        }
+        let mut ascii = chart.render(&number(y.max), &number(y.min), x.min, x.max);
+        let _ = writeln!(
+            ascii,
+            "{}, {} -> {}: {} '{}' .. {} '{}'",
+            x.name,
+            y.name,
+            label(output),
+            number(min),
+            SHADES[0],
+            number(max),
+            SHADES[SHADES.len() - 1],
+        );
+        Some(ascii)
+    }
+}
+
+/// An input axis of a response plot
+struct Axis {
+    name: String,
+    min: f32,
+    max: f32,
+}
+
+/// One output of a system sampled over at most two inputs
+struct Response<OutputType> {
+    baked: BakedSystem<OutputType>,
+    output: usize,
+    axes: Vec<Axis>,
+    /// Universe of the output variable, if declared
+    universe: Option<(f32, f32)>,
+}
+
+impl<OutputType> Response<OutputType>
+where
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    fn new<InputType>(
+        system: &FuzzySystem<InputType, OutputType>,
+        output: &OutputType,
+        resolution: usize,
+    ) -> Option<Self>
+    where
+        InputType: Clone + PartialEq,
+    {
+        if system.input_variables.len() > 2 {
+            return None;
+        }
+        let baked = system.bake(&BakeOptions::new().with_resolution(resolution));
+        let output_index = baked.output_index(output)?;
+        let axes = baked
+            .axes()
+            .iter()
+            .map(|axis| Axis {
+                name: axis.input.clone().unwrap_or_else(|| "input".to_string()),
+                min: axis.min,
+                max: axis.max,
+            })
+            .collect();
+        let universe = system
+            .output_variable(output)
+            .map(|variable| (variable.min, variable.max));
+        Some(Self {
+            baked,
+            output: output_index,
+            axes,
+            universe,
+        })
+    }
+
+    fn value(&self, inputs: &[f32]) -> Option<f32> {
+        self.baked.value(self.output, inputs)
+    }
+
+    /// The output universe, or else the range of the sampled values. Empty ranges are
+    /// widened so values can be normalized.
+    fn range(&self) -> (f32, f32) {
+        let (min, max) = self.universe.unwrap_or_else(|| {
+            let axes: Vec<Vec<f32>> = self
+                .baked
+                .axes()
+                .iter()
+                .map(|axis| {
+                    inference::sample_positions(axis.min, axis.max, axis.resolution).collect()
+                })
+                .collect();
+            let mut range = (f32::INFINITY, f32::NEG_INFINITY);
+            let mut visit = |inputs: &[f32]| {
+                if let Some(value) = self.value(inputs) {
+                    range = (range.0.min(value), range.1.max(value));
+                }
+            };
+            match axes.as_slice() {
+                [x] => x.iter().for_each(|x| visit(&[*x])),
+                [x, y] => {
+                    for y in y {
+                        x.iter().for_each(|x| visit(&[*x, *y]));
+                    }
+                }
+                _ => {}
+            }
+            range
+        });
+        if min.is_nan() || max.is_nan() || min >= max {
+            let center = if min.is_finite() { min } else { 0.0 };
+            return (center - 0.5, center + 0.5);
+        }
+        (min, max)
+    }
+}
+
+/// Plot area of an SVG, mapping data coordinates to pixels
+struct Frame {
+    width: f32,
+    height: f32,
+    x: (f32, f32),
+    y: (f32, f32),
+    total: (usize, usize),
+}
+
+impl Frame {
+    fn new(options: &PlotOptions, x_min: f32, x_max: f32, y_min: f32, y_max: f32) -> Self {
+        let total = (options.width.max(100), options.height.max(100));
+        Self {
+            width: total.0 as f32 - 2.0 * MARGIN,
+            height: total.1 as f32 - 2.0 * MARGIN,
+            x: (x_min, x_max),
+            y: (y_min, y_max),
+            total,
+        }
+    }
+
+    fn pixel(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            MARGIN + normalize(x, self.x.0, self.x.1) * self.width,
+            MARGIN + (1.0 - normalize(y, self.y.0, self.y.1)) * self.height,
+        )
+    }
+
+    /// The SVG header, background, title and axes with their range labels
+    fn begin(&self, title: &str) -> String {
+        let (width, height) = self.total;
+        let bottom = MARGIN + self.height;
+        let right = MARGIN + self.width;
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif">"#
+        );
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        let _ = writeln!(
+            svg,
+            r#"<text x="{MARGIN}" y="{:.1}" font-size="12">{}</text>"#,
+            MARGIN - 6.0,
+            escape(title)
+        );
+        let _ = writeln!(
+            svg,
+            r#"<polyline fill="none" stroke="black" points="{MARGIN},{MARGIN} {MARGIN},{bottom:.1} {right:.1},{bottom:.1}"/>"#
+        );
+        let labels = [
+            (MARGIN, bottom + 14.0, "middle", self.x.0),
+            (right, bottom + 14.0, "middle", self.x.1),
+            (MARGIN - 4.0, bottom, "end", self.y.0),
+            (MARGIN - 4.0, MARGIN + 4.0, "end", self.y.1),
+        ];
+        for (x, y, anchor, value) in labels {
+            let _ = writeln!(
+                svg,
+                r#"<text x="{x:.1}" y="{y:.1}" font-size="10" text-anchor="{anchor}">{}</text>"#,
+                number(value)
+            );
+        }
+        svg
+    }
+
+    fn polyline(&self, svg: &mut String, points: &[(f32, f32)], color: &str) {
+        if points.is_empty() {
+            return;
+        }
+        let points: Vec<String> = points
+            .iter()
+            .map(|(x, y)| {
+                let (x, y) = self.pixel(*x, *y);
+                format!("{x:.1},{y:.1}")
+            })
+            .collect();
+        let _ = writeln!(
+            svg,
+            r#"<polyline fill="none" stroke="{color}" stroke-width="2" points="{}"/>"#,
+            points.join(" ")
+        );
+    }
+
+    /// Colored label of the `index`th curve, listed from the top right
+    fn legend(&self, svg: &mut String, index: usize, text: &str, color: &str) {
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-size="11" text-anchor="end" fill="{color}">{}</text>"#,
+            MARGIN + self.width,
+            MARGIN + 12.0 + index as f32 * 14.0,
+            escape(text)
+        );
+    }
+}
+
+/// A character grid with a y axis on the left and an x axis below
+struct Chart {
+    columns: usize,
+    rows: usize,
+    cells: Vec<Vec<char>>,
+}
+
+impl Chart {
+    fn new(columns: usize, rows: usize) -> Self {
+        let (columns, rows) = (columns.max(2), rows.max(2));
+        Self {
+            columns,
+            rows,
+            cells: vec![vec![' '; columns]; rows],
+        }
+    }
+
+    fn position(&self, column: usize, min: f32, max: f32) -> f32 {
+        min + (max - min) * column as f32 / (self.columns - 1) as f32
+    }
+
+    /// Marks the height `level` in 0..=1 of a column
+    fn plot(&mut self, column: usize, level: f32, mark: char) {
+        let row = ((1.0 - level.min(1.0)) * (self.rows - 1) as f32).round() as usize;
+        self.cells[row][column] = mark;
+    }
+
+    fn render(&self, top: &str, bottom: &str, min: f32, max: f32) -> String {
+        let width = top.len().max(bottom.len());
+        let mut ascii = String::new();
+        for (row, cells) in self.cells.iter().enumerate() {
+            let label = match row {
+                0 => top,
+                row if row == self.rows - 1 => bottom,
+                _ => "",
+            };
+            let line: String = cells.iter().collect();
+            let _ = writeln!(ascii, "{label:>width$} |{}", line.trim_end());
+        }
+        let _ = writeln!(ascii, "{:>width$} +{}", "", "-".repeat(self.columns));
+        let (min, max) = (number(min), number(max));
+        // Both labels are aligned with the first and the last column
+        let gap = self.columns.saturating_sub(min.len() + max.len()).max(1);
+        let _ = writeln!(ascii, "{:>width$}  {min}{}{max}", "", " ".repeat(gap));
+        ascii
+    }
+}
+
+/// `Debug` form of a category or output, without the quotes of strings
+fn label(value: &impl Debug) -> String {
+    let label = format!("{value:?}");
+    match label
+        .strip_prefix('"')
+        .and_then(|label| label.strip_suffix('"'))
+    {
+        Some(unquoted) => unquoted.to_string(),
+        None => label,
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Up to two decimals, without trailing zeros
+fn number(value: f32) -> String {
+    let text = format!("{value:.2}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    match text {
+        "-0" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+fn normalize(value: f32, min: f32, max: f32) -> f32 {
+    if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Dark blue over teal to yellow for levels in 0..=1
+fn color(level: f32) -> String {
+    const STOPS: [(f32, f32, f32); 3] = [
+        (68.0, 1.0, 84.0),
+        (33.0, 145.0, 140.0),
+        (253.0, 231.0, 37.0),
+    ];
+    let scaled = level * (STOPS.len() - 1) as f32;
+    let index = (scaled as usize).min(STOPS.len() - 2);
+    let t = scaled - index as f32;
+    let (from, to) = (STOPS[index], STOPS[index + 1]);
+    let mix = |a: f32, b: f32| (a + (b - a) * t).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        mix(from.0, to.0),
+        mix(from.1, to.1),
+        mix(from.2, to.2)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{Antecedent, FuzzyRule, FuzzySet};
+
+    fn temperature() -> FuzzyVariable<&'static str> {
+        FuzzyVariable::new("temperature", 0.0, 40.0)
+            .with_term(FuzzySet::new("cold", vec![(0.0, 1.0), (20.0, 0.0)]))
+            .with_term(FuzzySet::triangle("warm", 10.0, 20.0, 30.0))
+            .with_term(FuzzySet::new("hot", vec![(20.0, 0.0), (40.0, 1.0)]))
+    }
+
+    fn thermostat() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(temperature());
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("temperature", "cold")).with_consequence("fan", 0.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("temperature", "warm")).with_consequence("fan", 0.5),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("temperature", "hot")).with_consequence("fan", 1.0),
+        );
+        system
+    }
+
+    #[test]
+    fn test_variable_ascii() {
+        let expected = "\
+1 |**        +        oo
+  |  **     + +     oo
+  |    ***++   ++ooo
+  |      +**   oo+
+0 |         * o
+  +---------------------
+   0                  40
+* cold  + warm  o hot
+";
+        assert_eq!(temperature().to_ascii(21, 5), expected);
+    }
+
+    #[test]
+    fn test_variable_svg() {
+        let svg = temperature().to_svg(&PlotOptions::new().with_size(400, 200));
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains(r#"width="400" height="200""#));
+        assert_eq!(svg.matches(r#"stroke-width="2""#).count(), 3);
+        for term in ["cold", "warm", "hot"] {
+            assert!(svg.contains(&format!(">{term}</text>")));
+        }
+    }
+
+    #[test]
+    fn test_singletons_are_drawn() {
+        let variable =
+            FuzzyVariable::new("<ammo>", 0.0, 10.0).with_term(FuzzySet::singleton("full", 3.3));
+        // The singleton is between two samples, but still peaks at 1
+        let svg = variable.to_svg(&PlotOptions::new().with_resolution(3));
+        assert!(svg.contains(",40.0"));
+        assert!(svg.contains("&lt;ammo&gt;"));
+    }
+
+    #[test]
+    fn test_response_curve() {
+        let system = thermostat();
+        let ascii = system.response_ascii(&"fan", 21, 5).unwrap();
+        let lines: Vec<&str> = ascii.lines().collect();
+        assert_eq!(lines[0], "1 |               ******");
+        assert_eq!(lines[2], "  |         ****");
+        assert_eq!(lines[4], "0 |******");
+        assert_eq!(lines[7], "temperature -> fan");
+
+        let svg = system.response_svg(&"fan", &PlotOptions::new()).unwrap();
+        assert!(svg.contains("temperature -&gt; fan"));
+        assert_eq!(svg.matches(r#"stroke-width="2""#).count(), 1);
+    }
+
+    #[test]
+    fn test_response_heatmap() {
+        let mut system = thermostat();
+        system.add_input_variable(
+            FuzzyVariable::new("humidity", 0.0, 1.0)
+                .with_term(FuzzySet::new("wet", vec![(0.0, 0.0), (1.0, 1.0)])),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("humidity", "wet")).with_consequence("fan", 1.0),
+        );
+
+        let ascii = system.response_ascii(&"fan", 11, 4).unwrap();
+        let lines: Vec<&str> = ascii.lines().collect();
+        // Wet air raises the speed of the fan, most where it is cold
+        assert_eq!(lines[0], "1 |++**##%%@@@");
+        assert_eq!(lines[3], "0 |   :-+*%@@@");
+        assert_eq!(lines[6], "temperature, humidity -> fan: 0 ' ' .. 1 '@'");
+
+        let svg = system
+            .response_svg(&"fan", &PlotOptions::new().with_resolution(10))
+            .unwrap();
+        assert_eq!(svg.matches("<rect").count(), 1 + 100);
+    }
+
+    #[test]
+    fn test_response_unknown_output_or_too_many_inputs() {
+        let mut system = thermostat();
+        assert_eq!(system.response_ascii(&"light", 10, 4), None);
+
+        system.add_input_variable(FuzzyVariable::new("humidity", 0.0, 1.0));
+        system.add_input_variable(FuzzyVariable::new("wind", 0.0, 1.0));
+        assert_eq!(system.response_svg(&"fan", &PlotOptions::new()), None);
+    }
+
+    #[test]
+    fn test_number() {
+        assert_eq!(number(40.0), "40");
+        assert_eq!(number(0.5), "0.5");
+        assert_eq!(number(-0.001), "0");
+        assert_eq!(number(1.234), "1.23");
+    }
+}
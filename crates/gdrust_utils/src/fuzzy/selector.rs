@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Settings of a [`BehaviorSelector`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct SelectorOptions {
+    /// How much higher than the current behavior another one has to score to take over
+    pub hysteresis: f32,
+    /// Time a behavior stays selected before another one may take over, in the unit of the
+    /// `delta` passed to [`BehaviorSelector::update`]
+    pub min_dwell: f32,
+    /// Score below which a behavior can not be selected
+    pub min_score: f32,
+}
+
+impl Default for SelectorOptions {
+    fn default() -> Self {
+        Self {
+            hysteresis: 0.1,
+            min_dwell: 0.0,
+            min_score: 0.0,
+        }
+    }
+}
+
+impl SelectorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis.max(0.0);
+        self
+    }
+
+    pub fn with_min_dwell(mut self, min_dwell: f32) -> Self {
+        self.min_dwell = min_dwell.max(0.0);
+        self
+    }
+
+    pub fn with_min_score(mut self, min_score: f32) -> Self {
+        self.min_score = min_score;
+        self
+    }
+}
+
+/// The behavior a [`BehaviorSelector`] currently runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct Selection<O> {
+    pub behavior: O,
+    /// Score of the behavior in the last update
+    pub score: f32,
+    /// Lead over the best other behavior relative to the score, from 0 for a tie to 1 when
+    /// no other behavior scores
+    pub confidence: f32,
+    /// Time since the behavior was selected
+    pub dwell: f32,
+}
+
+/// Emitted by [`BehaviorSelector::update`] when the selected behavior changes
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectionChange<O> {
+    /// The behavior that was running, if any
+    pub previous: Option<O>,
+    pub current: O,
+    /// How long the previous behavior ran
+    pub previous_dwell: f32,
+    pub confidence: f32,
+}
+
+/// Picks a discrete behavior like attack, flee or patrol from the highest output of a
+/// [`FuzzySystem`](super::FuzzySystem), without flickering between close outputs
+///
+/// Once a behavior is selected, another one only takes over after the minimum dwell time and
+/// when it scores higher by more than the hysteresis margin. While no behavior reaches the
+/// minimum score, the current one is kept.
+#[derive(Debug, Clone)]
+pub struct BehaviorSelector<O> {
+    pub options: SelectorOptions,
+    /// Outputs that are behaviors, in order of preference for ties. Empty means all outputs,
+    /// with ties going to the smallest one.
+    pub candidates: Vec<O>,
+    selection: Option<Selection<O>>,
+}
+
+impl<O> Default for BehaviorSelector<O> {
+    fn default() -> Self {
+        Self {
+            options: SelectorOptions::default(),
+            candidates: Vec::new(),
+            selection: None,
+        }
+    }
+}
+
+impl<O> BehaviorSelector<O>
+where
+    O: Clone + Eq + Hash + Ord,
+{
+    pub fn new(options: SelectorOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_candidates(mut self, candidates: impl IntoIterator<Item = O>) -> Self {
+        self.candidates = candidates.into_iter().collect();
+        self
+    }
+
+    pub fn selection(&self) -> Option<&Selection<O>> {
+        self.selection.as_ref()
+    }
+
+    pub fn behavior(&self) -> Option<&O> {
+        self.selection.as_ref().map(|selection| &selection.behavior)
+    }
+
+    /// Forgets the current behavior, so the next update selects the best one right away
+    pub fn reset(&mut self) {
+        self.selection = None;
+    }
+
+    /// Scores the behaviors with the outputs of a system evaluation, `delta` time after the
+    /// previous update. Returns the change if another behavior was selected.
+    pub fn update(&mut self, outputs: &HashMap<O, f32>, delta: f32) -> Option<SelectionChange<O>> {
+        let scores = scores(&self.candidates, outputs);
+        if let Some(selection) = self.selection.as_mut() {
+            selection.dwell += delta;
+            selection.score = score(&scores, &selection.behavior);
+            selection.confidence = confidence(&scores, &selection.behavior);
+        }
+
+        // The first of equal scores wins, so candidates, or outputs without them, are preferred
+        // in order
+        let (best, best_score) = scores
+            .iter()
+            .fold(
+                None,
+                |best: Option<(&O, f32)>, (behavior, score)| match best {
+                    Some((_, best_score)) if best_score >= *score => best,
+                    _ => Some((behavior, *score)),
+                },
+            )
+            .filter(|(_, score)| *score >= self.options.min_score)?;
+        if let Some(selection) = &self.selection {
+            if best == &selection.behavior
+                || selection.dwell < self.options.min_dwell
+                || best_score <= selection.score + self.options.hysteresis
+            {
+                return None;
+            }
+        }
+
+        let confidence = confidence(&scores, best);
+        let previous = self.selection.replace(Selection {
+            behavior: best.clone(),
+            score: best_score,
+            confidence,
+            dwell: 0.0,
+        });
+        Some(SelectionChange {
+            previous_dwell: previous.as_ref().map_or(0.0, |previous| previous.dwell),
+            previous: previous.map(|previous| previous.behavior),
+            current: best.clone(),
+            confidence,
+        })
+    }
+}
+
+/// Score of every candidate, with 0 for candidates without an output, or of every output in
+/// order without candidates
+fn scores<'a, O: Eq + Hash + Ord>(
+    candidates: &'a [O],
+    outputs: &'a HashMap<O, f32>,
+) -> Vec<(&'a O, f32)> {
+    if candidates.is_empty() {
+        let mut scores: Vec<(&O, f32)> = outputs
+            .iter()
+            .map(|(output, score)| (output, *score))
+            .collect();
+        scores.sort_by(|a, b| a.0.cmp(b.0));
+        return scores;
+    }
+    candidates
+        .iter()
+        .map(|candidate| (candidate, outputs.get(candidate).copied().unwrap_or(0.0)))
+        .collect()
+}
+
+fn score<O: PartialEq>(scores: &[(&O, f32)], behavior: &O) -> f32 {
+    scores
+        .iter()
+        .find(|(b, _)| *b == behavior)
+        .map_or(0.0, |(_, score)| *score)
+}
+
+/// Lead of `behavior` over the best other score, relative to its own score
+fn confidence<O: PartialEq>(scores: &[(&O, f32)], behavior: &O) -> f32 {
+    let score = score(scores, behavior);
+    if score <= 0.0 {
+        return 0.0;
+    }
+    let runner_up = scores
+        .iter()
+        .filter(|(b, _)| *b != behavior)
+        .map(|(_, score)| *score)
+        .fold(0.0, f32::max);
+    ((score - runner_up) / score).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outputs(attack: f32, flee: f32) -> HashMap<&'static str, f32> {
+        HashMap::from([("attack", attack), ("flee", flee)])
+    }
+
+    #[test]
+    fn test_first_update_selects_best() {
+        let mut selector = BehaviorSelector::new(SelectorOptions::new());
+        assert_eq!(selector.behavior(), None);
+
+        let change = selector.update(&outputs(0.8, 0.2), 0.1).unwrap();
+        assert_eq!(change.previous, None);
+        assert_eq!(change.current, "attack");
+        assert!((change.confidence - 0.75).abs() < 1e-6);
+        assert_eq!(selector.behavior(), Some(&"attack"));
+    }
+
+    #[test]
+    fn test_hysteresis() {
+        let mut selector = BehaviorSelector::new(SelectorOptions::new().with_hysteresis(0.2));
+        selector.update(&outputs(0.6, 0.4), 0.1);
+
+        // Flee leads, but not by more than the margin
+        assert_eq!(selector.update(&outputs(0.5, 0.65), 0.1), None);
+        assert_eq!(selector.update(&outputs(0.6, 0.5), 0.1), None);
+        let selection = selector.selection().unwrap();
+        assert_eq!(selection.behavior, "attack");
+        assert!((selection.dwell - 0.2).abs() < 1e-6);
+
+        let change = selector.update(&outputs(0.4, 0.7), 0.1).unwrap();
+        assert_eq!(change.previous, Some("attack"));
+        assert_eq!(change.current, "flee");
+        assert!((change.previous_dwell - 0.3).abs() < 1e-6);
+        assert_eq!(selector.selection().unwrap().dwell, 0.0);
+    }
+
+    #[test]
+    fn test_min_dwell() {
+        let options = SelectorOptions::new()
+            .with_hysteresis(0.0)
+            .with_min_dwell(1.0);
+        let mut selector = BehaviorSelector::new(options);
+        selector.update(&outputs(1.0, 0.0), 0.0);
+
+        assert_eq!(selector.update(&outputs(0.0, 1.0), 0.5), None);
+        assert_eq!(selector.update(&outputs(0.0, 1.0), 0.4), None);
+        assert_eq!(
+            selector
+                .update(&outputs(0.0, 1.0), 0.1)
+                .map(|change| change.current),
+            Some("flee")
+        );
+    }
+
+    #[test]
+    fn test_min_score_keeps_current() {
+        let mut selector = BehaviorSelector::new(SelectorOptions::new().with_min_score(0.3));
+        assert_eq!(selector.update(&outputs(0.2, 0.1), 0.1), None);
+        assert_eq!(selector.behavior(), None);
+
+        selector.update(&outputs(0.5, 0.1), 0.1);
+        assert_eq!(selector.update(&outputs(0.0, 0.2), 0.1), None);
+        assert_eq!(selector.behavior(), Some(&"attack"));
+        assert_eq!(selector.selection().unwrap().score, 0.0);
+        assert_eq!(selector.update(&HashMap::new(), 0.1), None);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let mut selector =
+            BehaviorSelector::new(SelectorOptions::new()).with_candidates(["patrol", "flee"]);
+        let mut scores = outputs(0.9, 0.3);
+        scores.insert("patrol", 0.3);
+
+        // Attack is no candidate and ties go to the earlier candidate
+        let change = selector.update(&scores, 0.1).unwrap();
+        assert_eq!(change.current, "patrol");
+        assert_eq!(change.confidence, 0.0);
+    }
+
+    #[test]
+    fn test_ties_without_candidates() {
+        for _ in 0..8 {
+            let mut selector = BehaviorSelector::new(SelectorOptions::new());
+            let mut scores = outputs(0.5, 0.5);
+            scores.insert("patrol", 0.5);
+            assert_eq!(selector.update(&scores, 0.1).unwrap().current, "attack");
+        }
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut selector = BehaviorSelector::new(SelectorOptions::new().with_hysteresis(1.0));
+        selector.update(&outputs(0.6, 0.4), 0.1);
+        assert_eq!(selector.update(&outputs(0.4, 0.6), 0.1), None);
+
+        selector.reset();
+        let change = selector.update(&outputs(0.4, 0.6), 0.1).unwrap();
+        assert_eq!((change.previous, change.current), (None, "flee"));
+    }
+}
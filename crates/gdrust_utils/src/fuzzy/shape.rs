@@ -0,0 +1,356 @@
+use std::fmt;
+use std::sync::Arc;
+
+/// A membership curve that can be plugged into a [`FuzzySet`](super::FuzzySet) with
+/// [`Shape::Custom`]. Closures `Fn(f32) -> f32` implement it as well.
+pub trait MembershipFunction: Send + Sync {
+    fn membership(&self, input: f32) -> f32;
+
+    /// The range of inputs outside which the membership no longer changes, if known
+    fn support(&self) -> Option<(f32, f32)> {
+        None
+    }
+}
+
+impl<F> MembershipFunction for F
+where
+    F: Fn(f32) -> f32 + Send + Sync,
+{
+    fn membership(&self, input: f32) -> f32 {
+        self(input)
+    }
+}
+
+/// Membership degree below which the tails of smooth shapes count as outside their support
+const SUPPORT_EPSILON: f32 = 1e-3;
+
+/// The membership curve of a [`FuzzySet`](super::FuzzySet)
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Shape {
+    /// Piecewise-linear polyline of `(input_value, membership_degree)` points, sorted by
+    /// input. Inputs beyond the first or last point keep that point's membership.
+    Points(Vec<(f32, f32)>),
+    /// Rises from `left` to 1 at `peak`, then falls to 0 at `right`
+    Triangle { left: f32, peak: f32, right: f32 },
+    /// Rises from `left` to 1 at `left_top`, stays 1 until `right_top`, then falls to 0 at
+    /// `right`
+    Trapezoid {
+        left: f32,
+        left_top: f32,
+        right_top: f32,
+        right: f32,
+    },
+    /// `exp(-(x - mean)^2 / (2 * sigma^2))`
+    Gaussian { mean: f32, sigma: f32 },
+    /// Generalized bell `1 / (1 + |(x - center) / width|^(2 * slope))`
+    Bell { width: f32, slope: f32, center: f32 },
+    /// `1 / (1 + exp(-slope * (x - center)))`, rising for positive slopes
+    Sigmoid { slope: f32, center: f32 },
+    /// 1 at exactly this input, 0 everywhere else
+    Singleton(f32),
+    /// Custom shapes can not be serialized
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(CustomShape),
+}
+
+impl Shape {
+    pub fn custom(function: impl MembershipFunction + 'static) -> Self {
+        Shape::Custom(CustomShape(Arc::new(function)))
+    }
+
+    pub fn membership(&self, input: f32) -> f32 {
+        match self {
+            Shape::Points(points) => points_membership(points, input),
+            Shape::Triangle { left, peak, right } => {
+                trapezoid_membership(input, *left, *peak, *peak, *right)
+            }
+            Shape::Trapezoid {
+                left,
+                left_top,
+                right_top,
+                right,
+            } => trapezoid_membership(input, *left, *left_top, *right_top, *right),
+            Shape::Gaussian { mean, sigma } => {
+                (-(input - mean).powi(2) / (2.0 * sigma * sigma)).exp()
+            }
+            Shape::Bell {
+                width,
+                slope,
+                center,
+            } => 1.0 / (1.0 + ((input - center) / width).abs().powf(2.0 * slope)),
+            Shape::Sigmoid { slope, center } => 1.0 / (1.0 + (-slope * (input - center)).exp()),
+            Shape::Singleton(value) => {
+                if input == *value {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            Shape::Custom(function) => function.0.membership(input),
+        }
+    }
+
+    /// The range of inputs outside which the membership no longer changes. Smooth shapes
+    /// are cut off where their membership drops below 0.001.
+    pub fn support(&self) -> Option<(f32, f32)> {
+        match self {
+            Shape::Points(points) => {
+                let first = points.first()?.0;
+                let last = points.last()?.0;
+                Some((first.min(last), first.max(last)))
+            }
+            Shape::Triangle { left, right, .. } | Shape::Trapezoid { left, right, .. } => {
+                Some((*left, *right))
+            }
+            Shape::Gaussian { mean, sigma } => {
+                let spread = sigma.abs() * (-2.0 * SUPPORT_EPSILON.ln()).sqrt();
+                Some((mean - spread, mean + spread))
+            }
+            Shape::Bell {
+                width,
+                slope,
+                center,
+            } => {
+                let spread = width.abs() * (1.0 / SUPPORT_EPSILON - 1.0).powf(0.5 / slope);
+                Some((center - spread, center + spread))
+            }
+            Shape::Sigmoid { slope, center } => {
+                let spread = (1.0 / SUPPORT_EPSILON - 1.0).ln() / slope.abs();
+                Some((center - spread, center + spread))
+            }
+            Shape::Singleton(value) => Some((*value, *value)),
+            Shape::Custom(function) => function.0.support(),
+        }
+    }
+
+    /// Inputs where a piecewise-linear shape bends, which sampling might step over
+    pub(crate) fn corners(&self) -> Vec<f32> {
+        match self {
+            Shape::Points(points) => points.iter().map(|(x, _)| *x).collect(),
+            Shape::Triangle { left, peak, right } => vec![*left, *peak, *right],
+            Shape::Trapezoid {
+                left,
+                left_top,
+                right_top,
+                right,
+            } => vec![*left, *left_top, *right_top, *right],
+            Shape::Singleton(value) => vec![*value],
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl MembershipFunction for Shape {
+    fn membership(&self, input: f32) -> f32 {
+        Shape::membership(self, input)
+    }
+
+    fn support(&self) -> Option<(f32, f32)> {
+        Shape::support(self)
+    }
+}
+
+/// A shared [`MembershipFunction`]. Two custom shapes are only equal if they share the same
+/// function.
+#[derive(Clone)]
+pub struct CustomShape(pub Arc<dyn MembershipFunction>);
+
+impl fmt::Debug for CustomShape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("..")
+    }
+}
+
+impl PartialEq for CustomShape {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+fn points_membership(points: &[(f32, f32)], input: f32) -> f32 {
+    // Handle edge cases
+    if points.is_empty() {
+        return 0.0;
+    }
+
+    if input <= points[0].0 {
+        return points[0].1;
+    }
+    if input >= points.last().unwrap().0 {
+        return points.last().unwrap().1;
+    }
+
+    // Find the two points to interpolate between
+    for i in 0..points.len() - 1 {
+        let (x1, y1) = points[i];
+        let (x2, y2) = points[i + 1];
+
+        if input >= x1 && input <= x2 {
+            if x2 == x1 {
+                return y1;
+            }
+            // Linear interpolation
+            return y1 + (y2 - y1) * (input - x1) / (x2 - x1);
+        }
+    }
+
+    0.0
+}
+
+fn trapezoid_membership(input: f32, left: f32, left_top: f32, right_top: f32, right: f32) -> f32 {
+    if input < left || input > right {
+        0.0
+    } else if input < left_top {
+        (input - left) / (left_top - left)
+    } else if input <= right_top {
+        1.0
+    } else {
+        (right - input) / (right - right_top)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-5,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_triangle() {
+        let shape = Shape::Triangle {
+            left: 0.0,
+            peak: 5.0,
+            right: 10.0,
+        };
+        assert_eq!(shape.membership(-1.0), 0.0);
+        assert_eq!(shape.membership(2.5), 0.5);
+        assert_eq!(shape.membership(5.0), 1.0);
+        assert_eq!(shape.membership(7.5), 0.5);
+        assert_eq!(shape.membership(11.0), 0.0);
+        assert_eq!(shape.support(), Some((0.0, 10.0)));
+    }
+
+    #[test]
+    fn test_triangle_vertical_edge() {
+        // Left shoulder with a vertical rising edge
+        let shape = Shape::Triangle {
+            left: 0.0,
+            peak: 0.0,
+            right: 10.0,
+        };
+        assert_eq!(shape.membership(0.0), 1.0);
+        assert_eq!(shape.membership(5.0), 0.5);
+    }
+
+    #[test]
+    fn test_trapezoid() {
+        let shape = Shape::Trapezoid {
+            left: 0.0,
+            left_top: 2.0,
+            right_top: 8.0,
+            right: 10.0,
+        };
+        assert_eq!(shape.membership(1.0), 0.5);
+        assert_eq!(shape.membership(5.0), 1.0);
+        assert_eq!(shape.membership(9.0), 0.5);
+        assert_eq!(shape.membership(10.5), 0.0);
+    }
+
+    #[test]
+    fn test_gaussian() {
+        let shape = Shape::Gaussian {
+            mean: 5.0,
+            sigma: 2.0,
+        };
+        assert_eq!(shape.membership(5.0), 1.0);
+        assert_close(shape.membership(7.0), (-0.5f32).exp());
+        assert_close(shape.membership(3.0), shape.membership(7.0));
+
+        let (min, max) = shape.support().unwrap();
+        assert_close(shape.membership(min), SUPPORT_EPSILON);
+        assert_close(shape.membership(max), SUPPORT_EPSILON);
+    }
+
+    #[test]
+    fn test_bell() {
+        let shape = Shape::Bell {
+            width: 2.0,
+            slope: 3.0,
+            center: 5.0,
+        };
+        assert_eq!(shape.membership(5.0), 1.0);
+        assert_close(shape.membership(7.0), 0.5);
+        assert_close(shape.membership(3.0), 0.5);
+
+        let (min, max) = shape.support().unwrap();
+        assert_close(shape.membership(min), SUPPORT_EPSILON);
+        assert_close(shape.membership(max), SUPPORT_EPSILON);
+    }
+
+    #[test]
+    fn test_sigmoid() {
+        let rising = Shape::Sigmoid {
+            slope: 2.0,
+            center: 5.0,
+        };
+        let falling = Shape::Sigmoid {
+            slope: -2.0,
+            center: 5.0,
+        };
+        assert_eq!(rising.membership(5.0), 0.5);
+        assert!(rising.membership(8.0) > 0.99);
+        assert!(falling.membership(8.0) < 0.01);
+
+        let (min, max) = rising.support().unwrap();
+        assert!(min < 5.0 && max > 5.0);
+        assert!((rising.membership(max) - (1.0 - SUPPORT_EPSILON)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_singleton() {
+        let shape = Shape::Singleton(3.0);
+        assert_eq!(shape.membership(3.0), 1.0);
+        assert_eq!(shape.membership(3.1), 0.0);
+        assert_eq!(shape.support(), Some((3.0, 3.0)));
+    }
+
+    #[test]
+    fn test_custom() {
+        let shape = Shape::custom(|x: f32| (x / 10.0).clamp(0.0, 1.0));
+        assert_eq!(shape.membership(5.0), 0.5);
+        assert_eq!(shape.support(), None);
+        assert_eq!(format!("{shape:?}"), "Custom(..)");
+    }
+
+    #[test]
+    fn test_custom_with_support() {
+        struct Ramp;
+        impl MembershipFunction for Ramp {
+            fn membership(&self, input: f32) -> f32 {
+                input.clamp(0.0, 1.0)
+            }
+            fn support(&self) -> Option<(f32, f32)> {
+                Some((0.0, 1.0))
+            }
+        }
+
+        let shape = Shape::custom(Ramp);
+        assert_eq!(shape.membership(0.25), 0.25);
+        assert_eq!(shape.support(), Some((0.0, 1.0)));
+    }
+
+    #[test]
+    fn test_equality() {
+        let custom = Shape::custom(|x: f32| x);
+        assert_eq!(custom, custom.clone());
+        assert_ne!(custom, Shape::custom(|x: f32| x));
+        assert_eq!(Shape::Singleton(1.0), Shape::Singleton(1.0));
+        assert_ne!(Shape::Singleton(1.0), Shape::Points(vec![(1.0, 1.0)]));
+    }
+}
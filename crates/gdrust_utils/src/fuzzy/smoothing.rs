@@ -0,0 +1,339 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::FuzzySystem;
+
+/// How an [`OutputSmoother`] follows the raw value of an output over time. Times and rates
+/// are in the unit of the `delta` passed to [`OutputSmoother::update`], usually seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Smoothing {
+    /// Jumps to the raw value right away
+    #[default]
+    Instant,
+    /// Closes half of the remaining distance every `half_life`
+    Exponential { half_life: f32 },
+    /// Critically damped spring, reaching the raw value in about `smooth_time` without
+    /// overshooting and without jumps in speed
+    Spring { smooth_time: f32 },
+    /// Moves towards the raw value by at most `max_rate` per unit of time
+    RateLimit { max_rate: f32 },
+}
+
+impl Smoothing {
+    pub fn exponential(half_life: f32) -> Self {
+        Smoothing::Exponential { half_life }
+    }
+
+    pub fn spring(smooth_time: f32) -> Self {
+        Smoothing::Spring { smooth_time }
+    }
+
+    pub fn rate_limit(max_rate: f32) -> Self {
+        Smoothing::RateLimit { max_rate }
+    }
+
+    /// Moves `state` towards `target` over `delta`. Non-positive deltas change nothing and
+    /// non-positive times or rates snap to the target.
+    fn step(&self, state: &mut SmoothedValue, target: f32, delta: f32) {
+        if delta <= 0.0 {
+            return;
+        }
+        match *self {
+            Smoothing::Exponential { half_life } if half_life > 0.0 => {
+                let remaining = 0.5f32.powf(delta / half_life);
+                state.value = target + (state.value - target) * remaining;
+            }
+            Smoothing::Spring { smooth_time } if smooth_time > 0.0 => {
+                // Exact solution of x'' = -omega^2 x - 2 omega x' for the offset x
+                let omega = 2.0 / smooth_time;
+                let offset = state.value - target;
+                let slope = state.velocity + omega * offset;
+                let decay = (-omega * delta).exp();
+                let offset_after = (offset + slope * delta) * decay;
+                state.velocity = (slope - omega * (offset + slope * delta)) * decay;
+                state.value = target + offset_after;
+            }
+            Smoothing::RateLimit { max_rate } if max_rate > 0.0 => {
+                let step = max_rate * delta;
+                state.value += (target - state.value).clamp(-step, step);
+            }
+            _ => {
+                state.value = target;
+                state.velocity = 0.0;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SmoothedValue {
+    value: f32,
+    /// Rate of change, for springs
+    velocity: f32,
+}
+
+/// Smooths the outputs of successive evaluations over time, so they don't pop when inputs
+/// cross set boundaries
+///
+/// Outputs are snapped to their raw value the first time they are defined, and keep their
+/// last smoothed value while an evaluation leaves them undefined.
+#[derive(Debug, Clone)]
+pub struct OutputSmoother<O> {
+    /// Smoothing of outputs without an override
+    pub smoothing: Smoothing,
+    pub overrides: HashMap<O, Smoothing>,
+    raw: HashMap<O, f32>,
+    smoothed: HashMap<O, SmoothedValue>,
+}
+
+impl<O> Default for OutputSmoother<O> {
+    fn default() -> Self {
+        Self {
+            smoothing: Smoothing::default(),
+            overrides: HashMap::new(),
+            raw: HashMap::new(),
+            smoothed: HashMap::new(),
+        }
+    }
+}
+
+impl<O> OutputSmoother<O>
+where
+    O: Clone + Eq + Hash,
+{
+    pub fn new(smoothing: Smoothing) -> Self {
+        Self {
+            smoothing,
+            ..Self::default()
+        }
+    }
+
+    /// Smooths `output` differently from the others
+    pub fn with_output(mut self, output: O, smoothing: Smoothing) -> Self {
+        self.overrides.insert(output, smoothing);
+        self
+    }
+
+    pub fn smoothing_of(&self, output: &O) -> Smoothing {
+        self.overrides
+            .get(output)
+            .copied()
+            .unwrap_or(self.smoothing)
+    }
+
+    /// Follows the outputs of an evaluation made `delta` after the previous one
+    pub fn update(&mut self, raw: HashMap<O, f32>, delta: f32) {
+        for (output, target) in &raw {
+            let smoothing = self.smoothing_of(output);
+            match self.smoothed.get_mut(output) {
+                Some(state) => smoothing.step(state, *target, delta),
+                None => {
+                    let state = SmoothedValue {
+                        value: *target,
+                        velocity: 0.0,
+                    };
+                    self.smoothed.insert(output.clone(), state);
+                }
+            }
+        }
+        self.raw = raw;
+    }
+
+    /// The smoothed value of `output`
+    pub fn value(&self, output: &O) -> Option<f32> {
+        self.smoothed.get(output).map(|state| state.value)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = (&O, f32)> {
+        self.smoothed
+            .iter()
+            .map(|(output, state)| (output, state.value))
+    }
+
+    /// The value of `output` in the last evaluation, before smoothing
+    pub fn raw(&self, output: &O) -> Option<f32> {
+        self.raw.get(output).copied()
+    }
+
+    pub fn raw_values(&self) -> &HashMap<O, f32> {
+        &self.raw
+    }
+
+    /// Jumps all outputs to their last raw value
+    pub fn snap(&mut self) {
+        for (output, state) in &mut self.smoothed {
+            if let Some(raw) = self.raw.get(output) {
+                state.value = *raw;
+                state.velocity = 0.0;
+            }
+        }
+    }
+
+    /// Forgets all values, so the next update snaps every output
+    pub fn reset(&mut self) {
+        self.raw.clear();
+        self.smoothed.clear();
+    }
+}
+
+/// A [`FuzzySystem`] whose outputs are smoothed over time by an [`OutputSmoother`]
+#[derive(Debug, Clone)]
+pub struct SmoothedSystem<InputType, OutputType> {
+    pub system: FuzzySystem<InputType, OutputType>,
+    pub smoother: OutputSmoother<OutputType>,
+}
+
+impl<InputType, OutputType> SmoothedSystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash,
+{
+    pub fn new(system: FuzzySystem<InputType, OutputType>, smoothing: Smoothing) -> Self {
+        Self {
+            system,
+            smoother: OutputSmoother::new(smoothing),
+        }
+    }
+
+    pub fn with_output(mut self, output: OutputType, smoothing: Smoothing) -> Self {
+        self.smoother = self.smoother.with_output(output, smoothing);
+        self
+    }
+
+    /// Evaluates the system for a single crisp input, `delta` after the previous update
+    pub fn update(&mut self, input_value: f32, delta: f32) {
+        self.smoother
+            .update(self.system.evaluate(input_value), delta);
+    }
+
+    /// Evaluates the system for several named inputs, `delta` after the previous update
+    pub fn update_inputs<K>(&mut self, inputs: &HashMap<K, f32>, delta: f32)
+    where
+        K: Borrow<str> + Eq + Hash,
+    {
+        self.smoother
+            .update(self.system.evaluate_inputs(inputs), delta);
+    }
+
+    /// The smoothed value of `output`
+    pub fn value(&self, output: &OutputType) -> Option<f32> {
+        self.smoother.value(output)
+    }
+
+    /// The value of `output` in the last evaluation, before smoothing
+    pub fn raw(&self, output: &OutputType) -> Option<f32> {
+        self.smoother.raw(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{FuzzyRule, FuzzySet};
+
+    fn smoother(smoothing: Smoothing) -> OutputSmoother<&'static str> {
+        let mut smoother = OutputSmoother::new(smoothing);
+        smoother.update(HashMap::from([("speed", 0.0)]), 0.1);
+        smoother
+    }
+
+    fn step(smoother: &mut OutputSmoother<&'static str>, target: f32, delta: f32) -> f32 {
+        smoother.update(HashMap::from([("speed", target)]), delta);
+        smoother.value(&"speed").unwrap()
+    }
+
+    #[test]
+    fn test_first_value_snaps() {
+        let mut smoother = OutputSmoother::new(Smoothing::exponential(1.0));
+        assert_eq!(smoother.value(&"speed"), None);
+        assert_eq!(step(&mut smoother, 4.0, 0.1), 4.0);
+    }
+
+    #[test]
+    fn test_instant() {
+        let mut smoother = smoother(Smoothing::Instant);
+        assert_eq!(step(&mut smoother, 10.0, 0.1), 10.0);
+    }
+
+    #[test]
+    fn test_exponential() {
+        let mut smoother = smoother(Smoothing::exponential(0.5));
+        assert!((step(&mut smoother, 10.0, 0.5) - 5.0).abs() < 1e-5);
+
+        // Independent of the frame rate
+        let mut smoother = self::smoother(Smoothing::exponential(0.5));
+        for _ in 0..10 {
+            step(&mut smoother, 10.0, 0.05);
+        }
+        assert!((smoother.value(&"speed").unwrap() - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_spring() {
+        let mut smoother = smoother(Smoothing::spring(0.5));
+        let mut previous = 0.0;
+        for _ in 0..150 {
+            let value = step(&mut smoother, 10.0, 0.02);
+            // Critically damped, so it never overshoots
+            assert!(value >= previous && value <= 10.0);
+            previous = value;
+        }
+        assert!((previous - 10.0).abs() < 0.01);
+
+        // The first step starts at rest
+        let mut smoother = self::smoother(Smoothing::spring(0.5));
+        assert!(step(&mut smoother, 10.0, 0.01) < 0.01);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let mut smoother = smoother(Smoothing::rate_limit(2.0));
+        assert!((step(&mut smoother, 10.0, 0.5) - 1.0).abs() < 1e-6);
+        assert!((step(&mut smoother, -10.0, 0.25) - 0.5).abs() < 1e-6);
+        assert_eq!(step(&mut smoother, 0.6, 1.0), 0.6);
+    }
+
+    #[test]
+    fn test_overrides_and_raw_values() {
+        let mut smoother = OutputSmoother::new(Smoothing::Instant)
+            .with_output("speed", Smoothing::rate_limit(1.0));
+        smoother.update(HashMap::from([("speed", 0.0), ("damage", 0.0)]), 0.1);
+        smoother.update(HashMap::from([("speed", 5.0), ("damage", 5.0)]), 1.0);
+
+        assert_eq!(smoother.value(&"speed"), Some(1.0));
+        assert_eq!(smoother.raw(&"speed"), Some(5.0));
+        assert_eq!(smoother.value(&"damage"), Some(5.0));
+        assert_eq!(smoother.values().count(), 2);
+
+        smoother.snap();
+        assert_eq!(smoother.value(&"speed"), Some(5.0));
+    }
+
+    #[test]
+    fn test_undefined_outputs_hold() {
+        let mut smoother = smoother(Smoothing::rate_limit(1.0));
+        smoother.update(HashMap::new(), 1.0);
+        assert_eq!(smoother.value(&"speed"), Some(0.0));
+        assert_eq!(smoother.raw(&"speed"), None);
+
+        smoother.reset();
+        assert_eq!(smoother.value(&"speed"), None);
+    }
+
+    #[test]
+    fn test_smoothed_system() {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("slow", vec![(0.0, 1.0), (1.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("fast", vec![(0.0, 0.0), (1.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("slow").with_consequence("speed", 1.0));
+        system.add_rule(FuzzyRule::new("fast").with_consequence("speed", 5.0));
+        let mut smoothed = SmoothedSystem::new(system, Smoothing::rate_limit(1.0));
+
+        smoothed.update(0.0, 0.1);
+        smoothed.update(1.0, 0.5);
+        assert_eq!(smoothed.raw(&"speed"), Some(5.0));
+        assert_eq!(smoothed.value(&"speed"), Some(1.5));
+    }
+}
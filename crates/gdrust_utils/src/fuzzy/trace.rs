@@ -0,0 +1,394 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use super::FuzzySystem;
+
+/// Everything [`FuzzySystem::evaluate_traced`] computed on the way to its outputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvaluationTrace<InputType, OutputType> {
+    /// Membership degrees of the terms read by rule conditions, in the order they were
+    /// first read
+    pub memberships: Vec<TermTrace<InputType>>,
+    /// Firing strengths of all enabled rules, in order
+    pub rules: Vec<RuleTrace<InputType, OutputType>>,
+    /// The outputs rules contributed to, declared ones first, like
+    /// [`CompiledSystem::outputs`](super::CompiledSystem::outputs)
+    pub outputs: Vec<OutputTrace<OutputType>>,
+}
+
+/// Membership degree of a single `input IS category` term
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermTrace<InputType> {
+    pub input: Option<String>,
+    pub category: InputType,
+    /// The crisp input after the out of range policy, if there was one
+    pub value: Option<f32>,
+    pub membership: f32,
+}
+
+/// Firing strength of a rule and what it contributed to the outputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleTrace<InputType, OutputType> {
+    /// Index into [`FuzzySystem::rules`]
+    pub rule: usize,
+    /// Truth degree of the condition, scaled by the rule weight
+    pub strength: f32,
+    /// Consequences used by the inference mode, weighted by `strength`, in the order of
+    /// [`EvaluationTrace::outputs`]. Empty if the rule didn't fire.
+    pub contributions: Vec<(OutputType, Contribution<InputType>)>,
+}
+
+/// What a fired rule contributes to an output
+#[derive(Debug, Clone, PartialEq)]
+pub enum Contribution<InputType> {
+    /// A constant or linear consequence, averaged with the rule strength as weight
+    Crisp(f32),
+    /// An output term, clipped or scaled by the rule strength
+    Term(InputType),
+}
+
+/// How the contributions to an output were combined
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregation {
+    WeightedAverage {
+        weighted_sum: f32,
+        total_weight: f32,
+    },
+    /// The aggregated output set as `(x, membership)` samples
+    Mamdani { samples: Vec<(f32, f32)> },
+}
+
+/// The crisp value of an output and how it was reached
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputTrace<OutputType> {
+    pub output: OutputType,
+    /// `None` if the output is left out of the result
+    pub value: Option<f32>,
+    pub aggregation: Aggregation,
+}
+
+impl<InputType, OutputType> Default for EvaluationTrace<InputType, OutputType> {
+    fn default() -> Self {
+        Self {
+            memberships: Vec::new(),
+            rules: Vec::new(),
+            outputs: Vec::new(),
+        }
+    }
+}
+
+impl<InputType, OutputType> EvaluationTrace<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash,
+{
+    /// The outputs as [`FuzzySystem::evaluate`] returns them
+    pub fn values(&self) -> HashMap<OutputType, f32> {
+        self.outputs
+            .iter()
+            .filter_map(|output| output.value.map(|value| (output.output.clone(), value)))
+            .collect()
+    }
+
+    pub fn output(&self, output: &OutputType) -> Option<&OutputTrace<OutputType>> {
+        self.outputs.iter().find(|trace| &trace.output == output)
+    }
+
+    /// The strongest rule contributing to `output`
+    pub fn dominant_rule(&self, output: &OutputType) -> Option<&RuleTrace<InputType, OutputType>> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.contributions.iter().any(|(o, _)| o == output))
+            .max_by(|a, b| a.strength.total_cmp(&b.strength))
+    }
+
+    /// Share of the total weight of `output` coming from `rule`, for weighted average
+    /// inference
+    pub fn share(&self, rule: usize, output: &OutputType) -> Option<f32> {
+        let rule = self.rules.iter().find(|trace| trace.rule == rule)?;
+        if !rule.contributions.iter().any(|(o, _)| o == output) {
+            return Some(0.0);
+        }
+        match self.output(output)?.aggregation {
+            Aggregation::WeightedAverage { total_weight, .. } if total_weight > 0.0 => {
+                Some(rule.strength / total_weight)
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn record_membership(
+        &mut self,
+        input: Option<&str>,
+        category: &InputType,
+        value: Option<f32>,
+        membership: f32,
+    ) {
+        let known = self
+            .memberships
+            .iter()
+            .any(|term| term.input.as_deref() == input && &term.category == category);
+        if !known {
+            self.memberships.push(TermTrace {
+                input: input.map(str::to_string),
+                category: category.clone(),
+                value,
+                membership,
+            });
+        }
+    }
+
+    pub(crate) fn record_rule(&mut self, rule: usize, strength: f32) {
+        self.rules.push(RuleTrace {
+            rule,
+            strength,
+            contributions: Vec::new(),
+        });
+    }
+
+    /// Adds a contribution to the last recorded rule
+    pub(crate) fn record_contribution(
+        &mut self,
+        output: &OutputType,
+        contribution: Contribution<InputType>,
+    ) {
+        if let Some(rule) = self.rules.last_mut() {
+            rule.contributions.push((output.clone(), contribution));
+        }
+    }
+
+    /// Puts the outputs and contributions in the order of `outputs`, since evaluation visits
+    /// them in hash order
+    fn sort_outputs(&mut self, outputs: &[OutputType]) {
+        let position = |output: &OutputType| outputs.iter().position(|o| o == output);
+        self.outputs.sort_by_key(|trace| position(&trace.output));
+        for rule in &mut self.rules {
+            rule.contributions
+                .sort_by_key(|(output, _)| position(output));
+        }
+    }
+
+    pub(crate) fn record_output(
+        &mut self,
+        output: &OutputType,
+        value: Option<f32>,
+        aggregation: Aggregation,
+    ) {
+        self.outputs.push(OutputTrace {
+            output: output.clone(),
+            value,
+            aggregation,
+        });
+    }
+}
+
+impl<InputType: Debug, OutputType: Debug> Display for EvaluationTrace<InputType, OutputType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for term in &self.memberships {
+            match &term.input {
+                Some(input) => write!(f, "{input} IS {:?}", term.category)?,
+                None => write!(f, "{:?}", term.category)?,
+            }
+            writeln!(f, ": {:.3}", term.membership)?;
+        }
+        for rule in &self.rules {
+            writeln!(f, "rule {}: {:.3}", rule.rule, rule.strength)?;
+            for (output, contribution) in &rule.contributions {
+                match contribution {
+                    Contribution::Crisp(value) => writeln!(f, "  {output:?} <- {value:.3}")?,
+                    Contribution::Term(category) => writeln!(f, "  {output:?} <- {category:?}")?,
+                }
+            }
+        }
+        for output in &self.outputs {
+            match output.value {
+                Some(value) => write!(f, "{:?} = {value:.3}", output.output)?,
+                None => write!(f, "{:?} undefined", output.output)?,
+            }
+            match output.aggregation {
+                Aggregation::WeightedAverage {
+                    weighted_sum,
+                    total_weight,
+                } => writeln!(f, " ({weighted_sum:.3} / {total_weight:.3})")?,
+                Aggregation::Mamdani { .. } => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    /// Like [`FuzzySystem::evaluate`], also recording term memberships, rule strengths and
+    /// how the outputs were aggregated
+    pub fn evaluate_traced(&self, input_value: f32) -> EvaluationTrace<InputType, OutputType> {
+        let mut trace = EvaluationTrace::default();
+        self.evaluate_with(&|_| Some(input_value), Some(&mut trace));
+        trace.sort_outputs(&self.outputs());
+        trace
+    }
+
+    /// Like [`FuzzySystem::evaluate_inputs`], also recording term memberships, rule
+    /// strengths and how the outputs were aggregated
+    pub fn evaluate_inputs_traced<K>(
+        &self,
+        inputs: &HashMap<K, f32>,
+    ) -> EvaluationTrace<InputType, OutputType>
+    where
+        K: Borrow<str> + Eq + Hash,
+    {
+        let mut trace = EvaluationTrace::default();
+        self.evaluate_with(
+            &|input| input.and_then(|name| inputs.get(name).copied()),
+            Some(&mut trace),
+        );
+        trace.sort_outputs(&self.outputs());
+        trace
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{
+        Antecedent, Defuzzifier, FuzzyRule, FuzzySet, FuzzyVariable, Inference, LinearConsequent,
+    };
+
+    fn boss() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("health", 0.0, 100.0)
+                .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (100.0, 0.0)]))
+                .with_term(FuzzySet::new("high", vec![(0.0, 0.0), (100.0, 1.0)])),
+        );
+        system.add_input_variable(
+            FuzzyVariable::new("distance", 0.0, 10.0)
+                .with_term(FuzzySet::new("near", vec![(0.0, 1.0), (10.0, 0.0)])),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").and(Antecedent::is("distance", "near")))
+                .with_consequence("aggression", 0.2),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high")).with_linear_consequence(
+                "aggression",
+                LinearConsequent::new(0.5).with_coefficient("distance", 0.1),
+            ),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("distance", "far")).with_consequence("flee", 1.0),
+        );
+        system
+    }
+
+    #[test]
+    fn test_trace_weighted_average() {
+        let system = boss();
+        let inputs = HashMap::from([("health", 25.0), ("distance", 5.0)]);
+        let trace = system.evaluate_inputs_traced(&inputs);
+
+        assert_eq!(trace.values(), system.evaluate_inputs(&inputs));
+        assert_eq!(
+            trace.memberships[0],
+            TermTrace {
+                input: Some("health".to_string()),
+                category: "low",
+                value: Some(25.0),
+                membership: 0.75
+            }
+        );
+        assert_eq!(trace.memberships.len(), 4);
+
+        assert_eq!(trace.rules.len(), 3);
+        assert_eq!(trace.rules[0].strength, 0.5);
+        assert_eq!(
+            trace.rules[0].contributions,
+            vec![("aggression", Contribution::Crisp(0.2))]
+        );
+        assert_eq!(trace.rules[1].strength, 0.25);
+        assert_eq!(
+            trace.rules[1].contributions,
+            vec![("aggression", Contribution::Crisp(1.0))]
+        );
+        assert_eq!(trace.rules[2].strength, 0.0);
+        assert!(trace.rules[2].contributions.is_empty());
+
+        let output = trace.output(&"aggression").unwrap();
+        assert_eq!(
+            output.aggregation,
+            Aggregation::WeightedAverage {
+                weighted_sum: 0.35,
+                total_weight: 0.75
+            }
+        );
+        assert_eq!(trace.dominant_rule(&"aggression").unwrap().rule, 0);
+        assert!((trace.share(0, &"aggression").unwrap() - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(trace.share(2, &"aggression"), Some(0.0));
+        assert!(trace.output(&"flee").is_none());
+    }
+
+    #[test]
+    fn test_trace_order_is_stable() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("hot", vec![(0.0, 0.0), (10.0, 1.0)]));
+        let mut rule = FuzzyRule::new("hot");
+        for output in ["f", "b", "e", "a", "d", "c"] {
+            rule = rule.with_consequence(output, 1.0);
+        }
+        system.add_rule(rule);
+
+        for _ in 0..20 {
+            let trace = system.evaluate_traced(3.0);
+            let outputs: Vec<&str> = trace.outputs.iter().map(|trace| trace.output).collect();
+            assert_eq!(outputs, ["a", "b", "c", "d", "e", "f"]);
+            let contributions: Vec<&str> = trace.rules[0]
+                .contributions
+                .iter()
+                .map(|(output, _)| *output)
+                .collect();
+            assert_eq!(contributions, outputs);
+        }
+    }
+
+    #[test]
+    fn test_trace_mamdani() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_input_set(FuzzySet::new("hot", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_output_set("fan", FuzzySet::triangle("fast", 0.0, 10.0, 10.0));
+        system.add_rule(FuzzyRule::new("hot").with_output_term("fan", "fast"));
+
+        let trace = system.evaluate_traced(5.0);
+
+        assert_eq!(trace.values(), system.evaluate(5.0));
+        assert_eq!(
+            trace.rules[0].contributions,
+            vec![("fan", Contribution::Term("fast"))]
+        );
+        match &trace.output(&"fan").unwrap().aggregation {
+            Aggregation::Mamdani { samples } => {
+                assert_eq!(samples.len(), Inference::DEFAULT_RESOLUTION);
+                assert_eq!(samples.last(), Some(&(10.0, 0.5)));
+            }
+            aggregation => panic!("unexpected {aggregation:?}"),
+        }
+        assert_eq!(trace.share(0, &"fan"), None);
+    }
+
+    #[test]
+    fn test_trace_display() {
+        let system = boss();
+        let trace =
+            system.evaluate_inputs_traced(&HashMap::from([("health", 0.0), ("distance", 0.0)]));
+        let text = trace.to_string();
+
+        assert!(text.contains("health IS \"low\": 1.000\n"));
+        assert!(text.contains("rule 0: 1.000\n  \"aggression\" <- 0.200\n"));
+        assert!(text.contains("\"aggression\" = 0.200 (0.200 / 1.000)\n"));
+    }
+}
@@ -0,0 +1,782 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{Consequent, FuzzySystem, Inference, Shape};
+
+/// A recorded input situation and the outputs the system should produce for it
+#[derive(Debug, Clone)]
+pub struct TrainingSample<OutputType> {
+    /// Named input values, read like [`FuzzySystem::evaluate_inputs`]
+    pub inputs: HashMap<String, f32>,
+    /// A value read by every term instead, like [`FuzzySystem::evaluate`]
+    pub value: Option<f32>,
+    pub targets: HashMap<OutputType, f32>,
+}
+
+impl<OutputType> Default for TrainingSample<OutputType> {
+    fn default() -> Self {
+        Self {
+            inputs: HashMap::new(),
+            value: None,
+            targets: HashMap::new(),
+        }
+    }
+}
+
+impl<OutputType: Eq + Hash> TrainingSample<OutputType> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_input(mut self, name: impl Into<String>, value: f32) -> Self {
+        self.inputs.insert(name.into(), value);
+        self
+    }
+
+    pub fn with_value(mut self, value: f32) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    pub fn with_target(mut self, output: OutputType, value: f32) -> Self {
+        self.targets.insert(output, value);
+        self
+    }
+
+    fn input(&self, name: Option<&str>) -> Option<f32> {
+        self.value
+            .or_else(|| name.and_then(|name| self.inputs.get(name).copied()))
+    }
+}
+
+/// Settings for [`FuzzySystem::train`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainingOptions {
+    /// Fit the constant and linear consequences by least squares
+    pub fit_consequences: bool,
+    /// Pulls fitted consequences towards their current values. Consequences of rules that
+    /// never fire on the samples keep their values.
+    pub regularization: f32,
+    /// Gradient descent steps on the shape parameters of the input terms, 0 to leave the
+    /// terms alone
+    pub membership_iterations: usize,
+    /// Initial step of the membership search, as a fraction of the input universe
+    pub membership_step: f32,
+}
+
+impl Default for TrainingOptions {
+    fn default() -> Self {
+        Self {
+            fit_consequences: true,
+            regularization: 1e-3,
+            membership_iterations: 0,
+            membership_step: 0.05,
+        }
+    }
+}
+
+impl TrainingOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_fit_consequences(mut self, fit_consequences: bool) -> Self {
+        self.fit_consequences = fit_consequences;
+        self
+    }
+
+    pub fn with_regularization(mut self, regularization: f32) -> Self {
+        self.regularization = regularization;
+        self
+    }
+
+    pub fn with_membership_iterations(mut self, iterations: usize) -> Self {
+        self.membership_iterations = iterations;
+        self
+    }
+
+    pub fn with_membership_step(mut self, step: f32) -> Self {
+        self.membership_step = step;
+        self
+    }
+}
+
+/// How well the system reproduces the targets of one output
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputFit<OutputType> {
+    pub output: OutputType,
+    /// Samples with a target for the output
+    pub samples: usize,
+    /// Samples for which the system leaves the output undefined
+    pub undefined: usize,
+    /// Root mean square error over the samples with a defined output
+    pub rmse: f32,
+    pub max_error: f32,
+}
+
+/// Result of [`FuzzySystem::fit_error`] and [`FuzzySystem::train`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FitReport<OutputType> {
+    /// Outputs in the order of the system, followed by outputs that only samples have
+    /// targets for
+    pub outputs: Vec<OutputFit<OutputType>>,
+}
+
+impl<OutputType> FitReport<OutputType> {
+    /// Root mean square error over all defined outputs of all samples
+    pub fn rmse(&self) -> f32 {
+        let (squares, count) = self.outputs.iter().fold((0.0, 0), |(squares, count), fit| {
+            let defined = fit.samples - fit.undefined;
+            (
+                squares + fit.rmse * fit.rmse * defined as f32,
+                count + defined,
+            )
+        });
+        if count == 0 {
+            0.0
+        } else {
+            (squares / count as f32).sqrt()
+        }
+    }
+
+    pub fn undefined(&self) -> usize {
+        self.outputs.iter().map(|fit| fit.undefined).sum()
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash + Ord,
+{
+    /// Measures how far the outputs of the system are from the targets of `samples`
+    pub fn fit_error(&self, samples: &[TrainingSample<OutputType>]) -> FitReport<OutputType> {
+        let mut fits: Vec<OutputFit<OutputType>> = Vec::new();
+        let mut squares: Vec<f32> = Vec::new();
+        for sample in samples {
+            let outputs = self.evaluate_with(&|name| sample.input(name), None);
+            for (output, target) in &sample.targets {
+                let index = match fits.iter().position(|fit| &fit.output == output) {
+                    Some(index) => index,
+                    None => {
+                        fits.push(OutputFit {
+                            output: output.clone(),
+                            samples: 0,
+                            undefined: 0,
+                            rmse: 0.0,
+                            max_error: 0.0,
+                        });
+                        squares.push(0.0);
+                        fits.len() - 1
+                    }
+                };
+                let fit = &mut fits[index];
+                fit.samples += 1;
+                match outputs.get(output) {
+                    Some(value) => {
+                        let error = (value - target).abs();
+                        squares[index] += error * error;
+                        fit.max_error = fit.max_error.max(error);
+                    }
+                    None => fit.undefined += 1,
+                }
+            }
+        }
+        for (fit, squares) in fits.iter_mut().zip(squares) {
+            let defined = fit.samples - fit.undefined;
+            if defined > 0 {
+                fit.rmse = (squares / defined as f32).sqrt();
+            }
+        }
+        let order = self.outputs();
+        let rank = |fit: &OutputFit<OutputType>| {
+            let position = order.iter().position(|output| output == &fit.output);
+            position.unwrap_or(order.len())
+        };
+        fits.sort_by(|a, b| rank(a).cmp(&rank(b)).then_with(|| a.output.cmp(&b.output)));
+        FitReport { outputs: fits }
+    }
+
+    /// Tunes the system to reproduce the targets of `samples` and reports the remaining
+    /// error.
+    ///
+    /// Under [`Inference::WeightedAverage`] the constant and linear consequences are fitted
+    /// by least squares, which finds the best values for the current terms in one step.
+    /// With [`TrainingOptions::membership_iterations`] the shape parameters of the input
+    /// terms are then improved by gradient descent, refitting the consequences after every
+    /// step. Custom shapes and output terms are left alone. Undefined outputs count as an
+    /// error as large as the range of the targets of their output, and at least 1.
+    pub fn train(
+        &mut self,
+        samples: &[TrainingSample<OutputType>],
+        options: &TrainingOptions,
+    ) -> FitReport<OutputType> {
+        if options.fit_consequences {
+            self.fit_consequences(samples, options.regularization);
+        }
+
+        let mut step = options.membership_step;
+        let mut error = self.training_error(samples);
+        for _ in 0..options.membership_iterations {
+            let parameters = self.shape_parameters();
+            if parameters.is_empty() || step < 1e-4 {
+                break;
+            }
+
+            // Central differences, scaled to the universe of each parameter
+            let gradient: Vec<f32> = (0..parameters.len())
+                .map(|i| {
+                    let (value, scale) = parameters[i];
+                    let h = 1e-3 * scale;
+                    let mut probe = self.clone();
+                    probe.set_shape_parameter(i, value + h);
+                    let above = probe.training_error(samples);
+                    probe.set_shape_parameter(i, value - h);
+                    let below = probe.training_error(samples);
+                    (above - below) / (2.0 * h) * scale
+                })
+                .collect();
+            let norm = gradient.iter().map(|g| g * g).sum::<f32>().sqrt();
+            if norm <= f32::EPSILON {
+                break;
+            }
+
+            // Backtracking along the gradient until the error drops
+            let mut improved = false;
+            while step >= 1e-4 {
+                let mut candidate = self.clone();
+                for (i, ((value, scale), g)) in parameters.iter().zip(&gradient).enumerate() {
+                    candidate.set_shape_parameter(i, value - step * scale * g / norm);
+                }
+                candidate.sanitize_shapes();
+                if options.fit_consequences {
+                    candidate.fit_consequences(samples, options.regularization);
+                }
+                let candidate_error = candidate.training_error(samples);
+                if candidate_error < error {
+                    *self = candidate;
+                    error = candidate_error;
+                    improved = true;
+                    break;
+                }
+                step /= 2.0;
+            }
+            if !improved {
+                break;
+            }
+        }
+
+        self.fit_error(samples)
+    }
+
+    /// Least squares fit of the constant and linear consequences, with ridge
+    /// regularization towards their current values
+    fn fit_consequences(&mut self, samples: &[TrainingSample<OutputType>], regularization: f32) {
+        if self.inference != Inference::WeightedAverage {
+            return;
+        }
+
+        let strengths: Vec<Vec<f32>> = samples
+            .iter()
+            .map(|sample| self.rule_strengths(&|name| sample.input(name)))
+            .collect();
+
+        for output in self.outputs() {
+            // The first parameter of each consequence and how many it has
+            let mut parameters: Vec<(usize, usize)> = Vec::new();
+            let mut count = 0;
+            for rule in &self.rules {
+                let size = match rule.consequences.get(&output) {
+                    Some(Consequent::Constant(_)) => 1,
+                    Some(Consequent::Linear(function)) => 1 + function.coefficients.len(),
+                    _ => 0,
+                };
+                parameters.push((count, size));
+                count += size;
+            }
+            if count == 0 {
+                continue;
+            }
+
+            let mut normal = vec![vec![0.0f64; count]; count];
+            let mut rhs = vec![0.0f64; count];
+            let mut row = vec![0.0f64; count];
+            for (sample, strengths) in samples.iter().zip(&strengths) {
+                let Some(target) = sample.targets.get(&output) else {
+                    continue;
+                };
+                row.fill(0.0);
+                let mut total_weight = 0.0;
+                for ((rule, strength), (first, _)) in
+                    self.rules.iter().zip(strengths).zip(&parameters)
+                {
+                    if *strength <= 0.0 {
+                        continue;
+                    }
+                    match rule.consequences.get(&output) {
+                        Some(Consequent::Constant(_)) => row[*first] = *strength as f64,
+                        Some(Consequent::Linear(function)) => {
+                            let inputs: Option<Vec<f32>> = function
+                                .coefficients
+                                .iter()
+                                .map(|(name, _)| {
+                                    let name = Some(name.as_str());
+                                    self.resolve_input(name, sample.input(name))
+                                })
+                                .collect();
+                            // Rules missing an input are left out, like in evaluation
+                            let Some(inputs) = inputs else {
+                                continue;
+                            };
+                            row[*first] = *strength as f64;
+                            for (k, input) in inputs.iter().enumerate() {
+                                row[first + 1 + k] = (*strength * input) as f64;
+                            }
+                        }
+                        _ => continue,
+                    }
+                    total_weight += *strength as f64;
+                }
+                if total_weight <= 0.0 {
+                    continue;
+                }
+
+                for (i, a) in row.iter().enumerate() {
+                    let a = a / total_weight;
+                    rhs[i] += a * *target as f64;
+                    for (j, b) in row.iter().enumerate() {
+                        normal[i][j] += a * b / total_weight;
+                    }
+                }
+            }
+
+            let current: Vec<f32> = self
+                .rules
+                .iter()
+                .flat_map(|rule| match rule.consequences.get(&output) {
+                    Some(Consequent::Constant(value)) => vec![*value],
+                    Some(Consequent::Linear(function)) => std::iter::once(function.constant)
+                        .chain(function.coefficients.iter().map(|(_, c)| *c))
+                        .collect(),
+                    _ => Vec::new(),
+                })
+                .collect();
+            // Parameters no sample informs, like those of rules that never fire, keep their
+            // values and are left out, which keeps the system solvable without regularization
+            let active: Vec<usize> = (0..count).filter(|i| normal[*i][*i] > 0.0).collect();
+            let reduced = active
+                .iter()
+                .map(|i| {
+                    active
+                        .iter()
+                        .map(|j| normal[*i][*j] + if i == j { regularization as f64 } else { 0.0 })
+                        .collect()
+                })
+                .collect();
+            let reduced_rhs = active
+                .iter()
+                .map(|i| rhs[*i] + (regularization * current[*i]) as f64)
+                .collect();
+            let Some(fitted) = solve(reduced, reduced_rhs) else {
+                continue;
+            };
+            let mut solution: Vec<f64> = current.iter().map(|value| *value as f64).collect();
+            for (i, value) in active.iter().zip(fitted) {
+                solution[*i] = value;
+            }
+
+            for (rule, (first, size)) in self.rules.iter_mut().zip(&parameters) {
+                if *size == 0 {
+                    continue;
+                }
+                let values = &solution[*first..first + size];
+                match rule.consequences.get_mut(&output) {
+                    Some(Consequent::Constant(value)) => *value = values[0] as f32,
+                    Some(Consequent::Linear(function)) => {
+                        function.constant = values[0] as f32;
+                        for ((_, coefficient), value) in
+                            function.coefficients.iter_mut().zip(&values[1..])
+                        {
+                            *coefficient = *value as f32;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    /// Firing strength of every rule
+    fn rule_strengths(&self, raw_input: &dyn Fn(Option<&str>) -> Option<f32>) -> Vec<f32> {
+        let input = |name: Option<&str>| self.resolve_input(name, raw_input(name));
+        self.rules
+            .iter()
+            .map(|rule| {
+                let truth = rule
+                    .condition
+                    .evaluate(self.rule_operators(rule), &mut |name, category| {
+                        self.term_membership(name, input(name), category)
+                    });
+                rule.strength(truth)
+            })
+            .collect()
+    }
+
+    /// Mean squared error over all targets, with undefined outputs counting as the range of
+    /// the targets of their output, and at least 1
+    fn training_error(&self, samples: &[TrainingSample<OutputType>]) -> f32 {
+        let mut ranges: HashMap<&OutputType, (f32, f32)> = HashMap::new();
+        for (output, target) in samples.iter().flat_map(|sample| &sample.targets) {
+            let range = ranges.entry(output).or_insert((*target, *target));
+            *range = (range.0.min(*target), range.1.max(*target));
+        }
+
+        let (mut squares, mut count) = (0.0, 0);
+        for sample in samples {
+            let outputs = self.evaluate_with(&|name| sample.input(name), None);
+            for (output, target) in &sample.targets {
+                let error = match outputs.get(output) {
+                    Some(value) => value - target,
+                    None => {
+                        let (min, max) = ranges[output];
+                        (max - min).max(1.0)
+                    }
+                };
+                squares += error * error;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            0.0
+        } else {
+            squares / count as f32
+        }
+    }
+
+    /// Tunable parameters of the input terms with the size of their universe, in the order
+    /// `set_shape_parameter` indexes them
+    fn shape_parameters(&self) -> Vec<(f32, f32)> {
+        let sets_universe = self
+            .input_sets
+            .iter()
+            .filter_map(|set| set.support())
+            .reduce(|(a, b), (c, d)| (a.min(c), b.max(d)))
+            .map_or(1.0, |(min, max)| max - min);
+        let shapes = self
+            .input_variables
+            .iter()
+            .flat_map(|variable| {
+                let universe = variable.max - variable.min;
+                variable.terms.iter().map(move |set| (&set.shape, universe))
+            })
+            .chain(
+                self.input_sets
+                    .iter()
+                    .map(|set| (&set.shape, sets_universe)),
+            );
+
+        let mut parameters = Vec::new();
+        for (shape, universe) in shapes {
+            let scale = universe.abs().max(f32::EPSILON);
+            parameters.extend(shape_values(shape).into_iter().map(|value| (value, scale)));
+        }
+        parameters
+    }
+
+    fn set_shape_parameter(&mut self, index: usize, value: f32) {
+        let shapes = self
+            .input_variables
+            .iter_mut()
+            .flat_map(|variable| variable.terms.iter_mut())
+            .chain(self.input_sets.iter_mut())
+            .map(|set| &mut set.shape);
+
+        let mut offset = 0;
+        for shape in shapes {
+            let mut values = shape_values(shape);
+            if index < offset + values.len() {
+                values[index - offset] = value;
+                set_shape_values(shape, &values);
+                return;
+            }
+            offset += values.len();
+        }
+    }
+
+    /// Restores the ordering and positivity the shapes need after a search step
+    fn sanitize_shapes(&mut self) {
+        let shapes = self
+            .input_variables
+            .iter_mut()
+            .flat_map(|variable| variable.terms.iter_mut())
+            .chain(self.input_sets.iter_mut())
+            .map(|set| &mut set.shape);
+        for shape in shapes {
+            match shape {
+                Shape::Points(points) => points.sort_by(|a, b| a.0.total_cmp(&b.0)),
+                Shape::Triangle { .. } | Shape::Trapezoid { .. } => {
+                    let mut values = shape_values(shape);
+                    values.sort_by(f32::total_cmp);
+                    set_shape_values(shape, &values);
+                }
+                Shape::Gaussian { sigma, .. } => *sigma = sigma.abs().max(1e-3),
+                Shape::Bell { width, slope, .. } => {
+                    *width = width.abs().max(1e-3);
+                    *slope = slope.max(1e-3);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// The tunable parameters of a shape. Polylines only expose their input positions.
+fn shape_values(shape: &Shape) -> Vec<f32> {
+    match shape {
+        Shape::Points(points) => points.iter().map(|(x, _)| *x).collect(),
+        Shape::Triangle { left, peak, right } => vec![*left, *peak, *right],
+        Shape::Trapezoid {
+            left,
+            left_top,
+            right_top,
+            right,
+        } => vec![*left, *left_top, *right_top, *right],
+        Shape::Gaussian { mean, sigma } => vec![*mean, *sigma],
+        Shape::Bell {
+            width,
+            slope,
+            center,
+        } => vec![*width, *slope, *center],
+        Shape::Sigmoid { slope, center } => vec![*slope, *center],
+        Shape::Singleton(value) => vec![*value],
+        Shape::Custom(_) => Vec::new(),
+    }
+}
+
+fn set_shape_values(shape: &mut Shape, values: &[f32]) {
+    match shape {
+        Shape::Points(points) => {
+            for ((x, _), value) in points.iter_mut().zip(values) {
+                *x = *value;
+            }
+        }
+        Shape::Triangle { left, peak, right } => {
+            [*left, *peak, *right] = [values[0], values[1], values[2]];
+        }
+        Shape::Trapezoid {
+            left,
+            left_top,
+            right_top,
+            right,
+        } => {
+            [*left, *left_top, *right_top, *right] = [values[0], values[1], values[2], values[3]];
+        }
+        Shape::Gaussian { mean, sigma } => [*mean, *sigma] = [values[0], values[1]],
+        Shape::Bell {
+            width,
+            slope,
+            center,
+        } => [*width, *slope, *center] = [values[0], values[1], values[2]],
+        Shape::Sigmoid { slope, center } => [*slope, *center] = [values[0], values[1]],
+        Shape::Singleton(value) => *value = values[0],
+        Shape::Custom(_) => {}
+    }
+}
+
+/// Solves `matrix * x = rhs` by Gaussian elimination with partial pivoting
+fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Option<Vec<f64>> {
+    let n = rhs.len();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        if matrix[pivot][column].abs() < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        rhs.swap(column, pivot);
+        let (pivot_rows, rows) = matrix.split_at_mut(column + 1);
+        let pivot_row = &pivot_rows[column];
+        for (offset, row) in rows.iter_mut().enumerate() {
+            let factor = row[column] / pivot_row[column];
+            for (value, pivot) in row[column..].iter_mut().zip(&pivot_row[column..]) {
+                *value -= factor * pivot;
+            }
+            rhs[column + 1 + offset] -= factor * rhs[column];
+        }
+    }
+
+    let mut solution = vec![0.0; n];
+    for row in (0..n).rev() {
+        let sum: f64 = (row + 1..n).map(|k| matrix[row][k] * solution[k]).sum();
+        solution[row] = (rhs[row] - sum) / matrix[row][row];
+    }
+    Some(solution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{Antecedent, FuzzyRule, FuzzySet, FuzzyVariable, LinearConsequent};
+
+    /// Complementary ramps, so the weighted average of two constants is linear in `x`
+    fn ramps() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("low", vec![(0.0, 1.0), (10.0, 0.0)]));
+        system.add_input_set(FuzzySet::new("high", vec![(0.0, 0.0), (10.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("low").with_consequence("speed", 0.5));
+        system.add_rule(FuzzyRule::new("high").with_consequence("speed", 0.5));
+        system
+    }
+
+    fn line(from: f32, to: f32) -> Vec<TrainingSample<&'static str>> {
+        (0..=10)
+            .map(|i| {
+                let x = i as f32;
+                TrainingSample::new()
+                    .with_value(x)
+                    .with_target("speed", from + (to - from) * x / 10.0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_constant_consequences() {
+        let mut system = ramps();
+        let samples = line(2.0, 5.0);
+        assert!(system.fit_error(&samples).rmse() > 1.0);
+
+        let report = system.train(&samples, &TrainingOptions::default());
+        assert!(report.rmse() < 1e-2, "{report:?}");
+        let constant = |rule: usize| system.rules[rule].consequences["speed"].constant().unwrap();
+        assert!((constant(0) - 2.0).abs() < 1e-2);
+        assert!((constant(1) - 5.0).abs() < 1e-2);
+        assert_eq!(report.outputs[0].samples, 11);
+    }
+
+    #[test]
+    fn test_fit_linear_consequences() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new("always", vec![(0.0, 1.0)]));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("a", "always")).with_linear_consequence(
+                "out",
+                LinearConsequent::new(0.0)
+                    .with_coefficient("a", 0.0)
+                    .with_coefficient("b", 0.0),
+            ),
+        );
+        let samples: Vec<TrainingSample<&str>> = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (2.0, 3.0)]
+            .into_iter()
+            .map(|(a, b)| {
+                TrainingSample::new()
+                    .with_input("a", a)
+                    .with_input("b", b)
+                    .with_target("out", 1.0 + 2.0 * a - b)
+            })
+            .collect();
+
+        let report = system.train(&samples, &TrainingOptions::new().with_regularization(0.0));
+        assert!(report.rmse() < 1e-4);
+        let Consequent::Linear(function) = &system.rules[0].consequences["out"] else {
+            panic!("expected a linear consequence");
+        };
+        assert!((function.constant - 1.0).abs() < 1e-4);
+        assert!((function.coefficients[0].1 - 2.0).abs() < 1e-4);
+        assert!((function.coefficients[1].1 + 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_rules_without_samples_keep_their_values() {
+        let mut system = ramps();
+        system.add_input_set(FuzzySet::new("beyond", vec![(20.0, 0.0), (30.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("beyond").with_consequence("speed", 7.0));
+
+        system.train(&line(2.0, 5.0), &TrainingOptions::default());
+        assert_eq!(system.rules[2].consequences["speed"].constant(), Some(7.0));
+
+        // Without regularization the rule that never fires would make the fit unsolvable
+        let mut system = ramps();
+        system.add_input_set(FuzzySet::new("beyond", vec![(20.0, 0.0), (30.0, 1.0)]));
+        system.add_rule(FuzzyRule::new("beyond").with_consequence("speed", 7.0));
+        let options = TrainingOptions::new().with_regularization(0.0);
+        let report = system.train(&line(2.0, 5.0), &options);
+        assert!(report.rmse() < 1e-4, "{report:?}");
+        let constant = |rule: usize| system.rules[rule].consequences["speed"].constant().unwrap();
+        assert!((constant(0) - 2.0).abs() < 1e-4);
+        assert!((constant(1) - 5.0).abs() < 1e-4);
+        assert_eq!(constant(2), 7.0);
+    }
+
+    #[test]
+    fn test_tune_memberships() {
+        // The target steps from 0 to 1 around 7, the terms cross at 5
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("x", 0.0, 10.0)
+                .with_term(FuzzySet::trapezoid("low", 0.0, 0.0, 4.0, 6.0))
+                .with_term(FuzzySet::trapezoid("high", 4.0, 6.0, 10.0, 10.0)),
+        );
+        system.add_rule(FuzzyRule::new(Antecedent::is("x", "low")).with_consequence("y", 0.0));
+        system.add_rule(FuzzyRule::new(Antecedent::is("x", "high")).with_consequence("y", 1.0));
+        let samples: Vec<TrainingSample<&str>> = (0..=20)
+            .map(|i| {
+                let x = i as f32 / 2.0;
+                let y = ((x - 6.0) / 2.0).clamp(0.0, 1.0);
+                TrainingSample::new().with_input("x", x).with_target("y", y)
+            })
+            .collect();
+
+        let before = system.fit_error(&samples).rmse();
+        let consequences_only = system
+            .clone()
+            .train(&samples, &TrainingOptions::default())
+            .rmse();
+        let tuned = system
+            .train(
+                &samples,
+                &TrainingOptions::new().with_membership_iterations(30),
+            )
+            .rmse();
+        assert!(consequences_only <= before);
+        assert!(
+            tuned < consequences_only * 0.5,
+            "{tuned} {consequences_only}"
+        );
+    }
+
+    #[test]
+    fn test_fit_error_counts_undefined_outputs() {
+        let system = ramps();
+        let samples = vec![
+            TrainingSample::new()
+                .with_value(5.0)
+                .with_target("speed", 1.0),
+            TrainingSample::new()
+                .with_value(5.0)
+                .with_target("jump", 1.0),
+        ];
+
+        let report = system.fit_error(&samples);
+        assert_eq!(report.undefined(), 1);
+        assert!((report.rmse() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_fit_error_output_order() {
+        let mut system = ramps();
+        system.add_rule(FuzzyRule::new("high").with_consequence("aim", 0.5));
+        let samples = vec![TrainingSample::new()
+            .with_value(5.0)
+            .with_target("jump", 1.0)
+            .with_target("aim", 1.0)
+            .with_target("crouch", 1.0)
+            .with_target("speed", 1.0)];
+
+        let report = system.fit_error(&samples);
+        let outputs: Vec<_> = report.outputs.iter().map(|fit| fit.output).collect();
+        assert_eq!(outputs, vec!["speed", "aim", "crouch", "jump"]);
+    }
+}
@@ -0,0 +1,623 @@
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{
+    inference, Antecedent, Consequent, Defuzzifier, FuzzySet, FuzzySystem, Inference,
+    LogicOperators, Shape,
+};
+
+/// An interval type-2 set, whose membership degree is itself uncertain and lies anywhere
+/// between a lower and an upper membership function. The area between the two is the
+/// footprint of uncertainty.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntervalSet<T> {
+    pub category: T,
+    pub upper: Shape,
+    pub lower: Shape,
+    /// Scales the lower membership function, which never exceeds the upper one
+    #[cfg_attr(feature = "serde", serde(default = "default_lower_height"))]
+    pub lower_height: f32,
+}
+
+#[cfg(feature = "serde")]
+fn default_lower_height() -> f32 {
+    1.0
+}
+
+impl<T> IntervalSet<T> {
+    pub fn new(category: T, upper: Shape, lower: Shape) -> Self {
+        Self {
+            category,
+            upper,
+            lower,
+            lower_height: 1.0,
+        }
+    }
+
+    /// A set without uncertainty, behaving like the type-1 `set`
+    pub fn from_set(set: FuzzySet<T>) -> Self {
+        Self::new(set.category, set.shape.clone(), set.shape)
+    }
+
+    /// The shape of `set` as upper membership function and the same shape scaled to
+    /// `lower_height` as lower one
+    pub fn blurred(set: FuzzySet<T>, lower_height: f32) -> Self {
+        Self::from_set(set).with_lower_height(lower_height)
+    }
+
+    /// Gaussian with a certain mean and a standard deviation between `sigma_lower` and
+    /// `sigma_upper`
+    pub fn gaussian(category: T, mean: f32, sigma_lower: f32, sigma_upper: f32) -> Self {
+        Self::new(
+            category,
+            Shape::Gaussian {
+                mean,
+                sigma: sigma_upper,
+            },
+            Shape::Gaussian {
+                mean,
+                sigma: sigma_lower,
+            },
+        )
+    }
+
+    pub fn with_lower_height(mut self, lower_height: f32) -> Self {
+        self.lower_height = lower_height.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Lower and upper membership degree of `input`
+    pub fn membership(&self, input: f32) -> (f32, f32) {
+        let upper = self.upper.membership(input);
+        let lower = (self.lower.membership(input) * self.lower_height).min(upper);
+        (lower, upper)
+    }
+}
+
+/// How [`IntervalSystem`] reduces the interval type-2 output to a type-1 interval
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TypeReduction {
+    /// Exact bounds of the centroid (Mamdani) or weighted average (Takagi-Sugeno) over all
+    /// embedded type-1 sets, as found by the Karnik-Mendel algorithm. Mamdani outputs always
+    /// use the centroid, regardless of the defuzzifier of the system.
+    #[default]
+    KarnikMendel,
+    /// Nie-Tan closed form, which averages the lower and upper firing strengths (or output
+    /// memberships) and defuzzifies the result. Much cheaper, but the output interval
+    /// collapses to a single value.
+    NieTan,
+}
+
+/// Evaluates the rules of a [`FuzzySystem`] with interval type-2 terms. Terms without an
+/// interval set keep their type-1 set, so only the terms of noisy inputs need one.
+///
+/// Rule weights, rule blocks, operators, input variables and the inference mode all come from
+/// `system`. Mamdani outputs still take their universe from the output variables of `system`.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        deserialize = "InputType: serde::Deserialize<'de>, OutputType: serde::Deserialize<'de> + Eq + Hash"
+    ))
+)]
+pub struct IntervalSystem<InputType, OutputType> {
+    pub system: FuzzySystem<InputType, OutputType>,
+    /// Interval sets of `input IS category` terms, with `None` for terms read from
+    /// [`FuzzySystem::input_sets`]
+    pub input_terms: Vec<(Option<String>, IntervalSet<InputType>)>,
+    pub output_terms: Vec<(OutputType, IntervalSet<InputType>)>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub type_reduction: TypeReduction,
+}
+
+impl<InputType, OutputType> IntervalSystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash,
+{
+    pub fn new(system: FuzzySystem<InputType, OutputType>) -> Self {
+        Self {
+            system,
+            input_terms: Vec::new(),
+            output_terms: Vec::new(),
+            type_reduction: TypeReduction::default(),
+        }
+    }
+
+    /// Replaces the term `input IS set.category`
+    pub fn with_input_term(
+        mut self,
+        input: impl Into<String>,
+        set: IntervalSet<InputType>,
+    ) -> Self {
+        self.input_terms.push((Some(input.into()), set));
+        self
+    }
+
+    /// Replaces the term `set.category` of [`FuzzySystem::input_sets`]
+    pub fn with_input_set(mut self, set: IntervalSet<InputType>) -> Self {
+        self.input_terms.push((None, set));
+        self
+    }
+
+    /// Replaces the output set `set.category` of `output`, for Mamdani inference
+    pub fn with_output_term(mut self, output: OutputType, set: IntervalSet<InputType>) -> Self {
+        self.output_terms.push((output, set));
+        self
+    }
+
+    pub fn with_type_reduction(mut self, type_reduction: TypeReduction) -> Self {
+        self.type_reduction = type_reduction;
+        self
+    }
+
+    /// Lower and upper membership degree of `input IS category`, from its interval set or
+    /// else from the type-1 set of the system
+    pub fn term_membership(
+        &self,
+        input: Option<&str>,
+        category: &InputType,
+        value: f32,
+    ) -> (f32, f32) {
+        // Named terms without an input variable read the input sets, like the system does
+        let key = input.filter(|name| self.system.input_variable(name).is_some());
+        let interval = self
+            .input_terms
+            .iter()
+            .find(|(name, set)| name.as_deref() == key && &set.category == category);
+        match interval {
+            Some((_, set)) => set.membership(value),
+            None => {
+                let membership = self
+                    .system
+                    .term(input, category)
+                    .map_or(0.0, |set| set.membership(value));
+                (membership, membership)
+            }
+        }
+    }
+
+    /// Evaluates the system for a single crisp input, which every term reads regardless of
+    /// its input name. Outputs are the middle of their type-reduced interval.
+    pub fn evaluate(&self, input_value: f32) -> HashMap<OutputType, f32> {
+        midpoints(self.evaluate_intervals_with(&|_| Some(input_value)))
+    }
+
+    /// Evaluates the system for several named inputs, see [`FuzzySystem::evaluate_inputs`].
+    /// Outputs are the middle of their type-reduced interval.
+    pub fn evaluate_inputs<K>(&self, inputs: &HashMap<K, f32>) -> HashMap<OutputType, f32>
+    where
+        K: Borrow<str> + Eq + Hash,
+    {
+        midpoints(self.evaluate_intervals(inputs))
+    }
+
+    /// The type-reduced `(min, max)` interval of every output, whose width reflects the
+    /// uncertainty of the terms
+    pub fn evaluate_intervals<K>(&self, inputs: &HashMap<K, f32>) -> HashMap<OutputType, (f32, f32)>
+    where
+        K: Borrow<str> + Eq + Hash,
+    {
+        self.evaluate_intervals_with(&|input| input.and_then(|name| inputs.get(name).copied()))
+    }
+
+    fn evaluate_intervals_with(
+        &self,
+        raw_input: &dyn Fn(Option<&str>) -> Option<f32>,
+    ) -> HashMap<OutputType, (f32, f32)> {
+        let system = &self.system;
+        let input = |name: Option<&str>| system.resolve_input(name, raw_input(name));
+        let mut weighted_outputs: HashMap<OutputType, Vec<(f32, f32, f32)>> = HashMap::new();
+        let mut fired_terms: HashMap<OutputType, Vec<FiredInterval<InputType>>> = HashMap::new();
+
+        for rule in system.rules.iter().filter(|rule| rule.enabled) {
+            let (lower, upper) = interval_truth(
+                &rule.condition,
+                system.rule_operators(rule),
+                &mut |name, category| match input(name) {
+                    Some(value) => self.term_membership(name, category, value),
+                    None => (0.0, 0.0),
+                },
+            );
+            let (lower, upper) = (rule.strength(lower), rule.strength(upper));
+            if upper <= 0.0 {
+                continue;
+            }
+
+            for (output, consequent) in &rule.consequences {
+                let value = match (consequent, &system.inference) {
+                    (Consequent::Constant(value), Inference::WeightedAverage) => Some(*value),
+                    (Consequent::Linear(function), Inference::WeightedAverage) => {
+                        function.value(|name| input(Some(name)))
+                    }
+                    (Consequent::Term(category), Inference::Mamdani { .. }) => {
+                        fired_terms
+                            .entry(output.clone())
+                            .or_default()
+                            .push(FiredInterval {
+                                category,
+                                lower,
+                                upper,
+                                block: rule.block,
+                            });
+                        None
+                    }
+                    _ => None,
+                };
+                if let Some(value) = value {
+                    weighted_outputs
+                        .entry(output.clone())
+                        .or_default()
+                        .push((value, lower, upper));
+                }
+            }
+        }
+
+        let mut result = HashMap::new();
+        if let Inference::Mamdani {
+            defuzzifier,
+            resolution,
+        } = system.inference
+        {
+            for (output, terms) in fired_terms {
+                let Some(samples) = self.mamdani_samples(&output, &terms, resolution) else {
+                    continue;
+                };
+                if let Some(interval) = self.reduce(&samples, Some(defuzzifier)) {
+                    result.insert(output, interval);
+                }
+            }
+            return result;
+        }
+
+        for (output, mut values) in weighted_outputs {
+            values.sort_by(|a, b| a.0.total_cmp(&b.0));
+            if let Some(interval) = self.reduce(&values, None) {
+                result.insert(output, interval);
+            }
+        }
+        result
+    }
+
+    /// Lower and upper aggregated output set of `output` as `(x, lower, upper)` samples
+    fn mamdani_samples(
+        &self,
+        output: &OutputType,
+        fired_terms: &[FiredInterval<InputType>],
+        resolution: usize,
+    ) -> Option<Vec<(f32, f32, f32)>> {
+        let variable = self.system.output_variable(output)?;
+        let implied: Vec<(&FiredInterval<InputType>, OutputSet<InputType>)> = fired_terms
+            .iter()
+            .filter_map(|fired| {
+                let interval = self
+                    .output_terms
+                    .iter()
+                    .find(|(o, set)| o == output && &set.category == fired.category)
+                    .map(|(_, set)| OutputSet::Interval(set));
+                let set =
+                    interval.or_else(|| variable.term(fired.category).map(OutputSet::Type1))?;
+                Some((fired, set))
+            })
+            .collect();
+
+        let mut blocks = vec![0.0; self.system.rule_blocks.len()];
+        let samples = inference::sample_positions(variable.min, variable.max, resolution)
+            .map(|x| {
+                let memberships: Vec<(f32, f32)> =
+                    implied.iter().map(|(_, set)| set.membership(x)).collect();
+                let lower = implied
+                    .iter()
+                    .zip(&memberships)
+                    .map(|((fired, _), mu)| (fired.block, fired.lower, mu.0));
+                let lower = self.system.aggregate(lower, &mut blocks);
+                let upper = implied
+                    .iter()
+                    .zip(&memberships)
+                    .map(|((fired, _), mu)| (fired.block, fired.upper, mu.1));
+                let upper = self.system.aggregate(upper, &mut blocks);
+                (x, lower.min(upper), upper)
+            })
+            .collect();
+        Some(samples)
+    }
+
+    /// Reduces `(x, lower, upper)` weights sorted by `x` to an output interval. `defuzzifier`
+    /// is the one of Mamdani systems, used by the Nie-Tan reduction.
+    fn reduce(
+        &self,
+        weights: &[(f32, f32, f32)],
+        defuzzifier: Option<Defuzzifier>,
+    ) -> Option<(f32, f32)> {
+        match self.type_reduction {
+            TypeReduction::KarnikMendel => karnik_mendel(weights),
+            TypeReduction::NieTan => {
+                let averaged: Vec<(f32, f32)> = weights
+                    .iter()
+                    .map(|(x, lower, upper)| (*x, (lower + upper) / 2.0))
+                    .collect();
+                let value = match defuzzifier {
+                    Some(defuzzifier) => defuzzifier.defuzzify(&averaged)?,
+                    None => {
+                        let total: f32 = averaged.iter().map(|(_, w)| w).sum();
+                        if total <= 0.0 {
+                            return None;
+                        }
+                        averaged.iter().map(|(x, w)| x * w).sum::<f32>() / total
+                    }
+                };
+                Some((value, value))
+            }
+        }
+    }
+}
+
+/// An output term consequence of a fired rule, with its lower and upper strength
+struct FiredInterval<'a, InputType> {
+    category: &'a InputType,
+    lower: f32,
+    upper: f32,
+    block: Option<usize>,
+}
+
+enum OutputSet<'a, T> {
+    Type1(&'a FuzzySet<T>),
+    Interval(&'a IntervalSet<T>),
+}
+
+impl<T> OutputSet<'_, T> {
+    fn membership(&self, x: f32) -> (f32, f32) {
+        match self {
+            OutputSet::Type1(set) => {
+                let membership = set.membership(x);
+                (membership, membership)
+            }
+            OutputSet::Interval(set) => set.membership(x),
+        }
+    }
+}
+
+/// Lower and upper truth degree of `antecedent`. The operators are monotonic in each
+/// argument, so the bounds of the operands give the bounds of the result.
+fn interval_truth<InputType, F>(
+    antecedent: &Antecedent<InputType>,
+    operators: &LogicOperators,
+    term: &mut F,
+) -> (f32, f32)
+where
+    F: FnMut(Option<&str>, &InputType) -> (f32, f32),
+{
+    // Negations and some hedges are decreasing, so sort the mapped bounds again
+    let ordered = |(a, b): (f32, f32)| (a.min(b), a.max(b));
+    match antecedent {
+        Antecedent::Is { input, category } => term(input.as_deref(), category),
+        Antecedent::And(terms) => terms.iter().fold((1.0, 1.0), |acc, t| {
+            let (lower, upper) = interval_truth(t, operators, term);
+            (
+                operators.and.apply(acc.0, lower),
+                operators.and.apply(acc.1, upper),
+            )
+        }),
+        Antecedent::Or(terms) => terms.iter().fold((0.0, 0.0), |acc, t| {
+            let (lower, upper) = interval_truth(t, operators, term);
+            (
+                operators.or.apply(acc.0, lower),
+                operators.or.apply(acc.1, upper),
+            )
+        }),
+        Antecedent::Not(inner) => {
+            let (lower, upper) = interval_truth(inner, operators, term);
+            ordered((operators.not.apply(lower), operators.not.apply(upper)))
+        }
+        Antecedent::Hedged(hedge, inner) => {
+            let (lower, upper) = interval_truth(inner, operators, term);
+            ordered((hedge.apply(lower), hedge.apply(upper)))
+        }
+    }
+}
+
+/// Smallest and largest weighted average of the `x` positions over all weights between
+/// `lower` and `upper`. The extremes switch from upper to lower weights (or the other way
+/// around) at a single position, so trying every switch point finds them exactly.
+fn karnik_mendel(weights: &[(f32, f32, f32)]) -> Option<(f32, f32)> {
+    // Sums of x * weight and weight over the positions before each switch point, in f64 to
+    // keep fine sampled universes accurate
+    let prefix = |weight: fn(&(f32, f32, f32)) -> f32| {
+        let mut sums = vec![(0.0f64, 0.0f64)];
+        for w in weights {
+            let (moment, total) = sums[sums.len() - 1];
+            sums.push((moment + (w.0 * weight(w)) as f64, total + weight(w) as f64));
+        }
+        sums
+    };
+    let lower = prefix(|w| w.1);
+    let upper = prefix(|w| w.2);
+    let n = weights.len();
+
+    let mut min = f64::INFINITY;
+    let mut max = f64::NEG_INFINITY;
+    for k in 0..=n {
+        // Upper weights left of the switch point pull the average down, right of it up
+        let left = (
+            upper[k].0 + lower[n].0 - lower[k].0,
+            upper[k].1 + lower[n].1 - lower[k].1,
+        );
+        if left.1 > 0.0 {
+            min = min.min(left.0 / left.1);
+        }
+        let right = (
+            lower[k].0 + upper[n].0 - upper[k].0,
+            lower[k].1 + upper[n].1 - upper[k].1,
+        );
+        if right.1 > 0.0 {
+            max = max.max(right.0 / right.1);
+        }
+    }
+    (min <= max).then_some((min as f32, max as f32))
+}
+
+fn midpoints<OutputType: Eq + Hash>(
+    intervals: HashMap<OutputType, (f32, f32)>,
+) -> HashMap<OutputType, f32> {
+    intervals
+        .into_iter()
+        .map(|(output, (min, max))| (output, (min + max) / 2.0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{FuzzyRule, FuzzyVariable};
+
+    fn seen() -> FuzzySet<&'static str> {
+        FuzzySet::new("seen", vec![(0.0, 0.0), (10.0, 1.0)])
+    }
+
+    fn guard() -> FuzzySystem<&'static str, &'static str> {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(
+            FuzzyVariable::new("visibility", 0.0, 10.0)
+                .with_term(FuzzySet::new("hidden", vec![(0.0, 1.0), (10.0, 0.0)]))
+                .with_term(seen()),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("visibility", "hidden")).with_consequence("alert", 0.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("visibility", "seen")).with_consequence("alert", 10.0),
+        );
+        system
+    }
+
+    fn noisy_guard() -> IntervalSystem<&'static str, &'static str> {
+        IntervalSystem::new(guard())
+            .with_input_term("visibility", IntervalSet::blurred(seen(), 0.5))
+    }
+
+    #[test]
+    fn test_interval_set_membership() {
+        let set = IntervalSet::blurred(FuzzySet::triangle("near", 0.0, 5.0, 10.0), 0.5);
+        assert_eq!(set.membership(5.0), (0.5, 1.0));
+        assert_eq!(set.membership(2.5), (0.25, 0.5));
+        assert_eq!(set.membership(20.0), (0.0, 0.0));
+
+        let set = IntervalSet::gaussian("near", 0.0, 1.0, 2.0);
+        let (lower, upper) = set.membership(1.0);
+        assert!(lower < upper);
+        assert_eq!(set.membership(0.0), (1.0, 1.0));
+
+        // The lower function is cut at the upper one
+        let set = IntervalSet::new(
+            "near",
+            Shape::Singleton(0.0),
+            Shape::Triangle {
+                left: -1.0,
+                peak: 0.0,
+                right: 1.0,
+            },
+        );
+        assert_eq!(set.membership(0.5), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_without_uncertainty_matches_type1() {
+        let system = IntervalSystem::new(guard());
+        for x in [0.0, 2.5, 7.0] {
+            let inputs = HashMap::from([("visibility", x)]);
+            let intervals = system.evaluate_intervals(&inputs);
+            let (min, max) = intervals["alert"];
+            assert!((max - min).abs() < 1e-5);
+            assert!((min - guard().evaluate_inputs(&inputs)["alert"]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_karnik_mendel_weighted_average() {
+        // hidden fires 0.5, seen fires between 0.25 and 0.5
+        let inputs = HashMap::from([("visibility", 5.0)]);
+        let (min, max) = noisy_guard().evaluate_intervals(&inputs)["alert"];
+        assert!((min - 2.5 / 0.75).abs() < 1e-5);
+        assert!((max - 5.0).abs() < 1e-5);
+        let value = noisy_guard().evaluate_inputs(&inputs)["alert"];
+        assert!((value - (min + max) / 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_nie_tan_weighted_average() {
+        let system = noisy_guard().with_type_reduction(TypeReduction::NieTan);
+        let inputs = HashMap::from([("visibility", 5.0)]);
+        let (min, max) = system.evaluate_intervals(&inputs)["alert"];
+        assert_eq!(min, max);
+        assert!((min - 3.75 / 0.875).abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_negation_swaps_bounds() {
+        let mut system = guard();
+        system.rules.clear();
+        system.add_rule(
+            FuzzyRule::new(!Antecedent::is("visibility", "seen")).with_consequence("calm", 1.0),
+        );
+        let system = IntervalSystem::new(system)
+            .with_input_term("visibility", IntervalSet::blurred(seen(), 0.5));
+
+        assert_eq!(
+            system.term_membership(Some("visibility"), &"seen", 5.0),
+            (0.25, 0.5)
+        );
+        let rule = &system.system.rules[0];
+        let negated = interval_truth(
+            &rule.condition,
+            &system.system.operators,
+            &mut |input, category| system.term_membership(input, category, 5.0),
+        );
+        assert_eq!(negated, (0.5, 0.75));
+    }
+
+    #[test]
+    fn test_mamdani_interval_output() {
+        let mut system = guard();
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_output_set("alert", FuzzySet::triangle("low", 0.0, 2.0, 5.0));
+        system.add_output_set("alert", FuzzySet::triangle("high", 5.0, 8.0, 10.0));
+        system.rules.clear();
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("visibility", "hidden")).with_output_term("alert", "low"),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("visibility", "seen")).with_output_term("alert", "high"),
+        );
+        let inputs = HashMap::from([("visibility", 5.0)]);
+        let type1 = system.evaluate_inputs(&inputs)["alert"];
+
+        let system = IntervalSystem::new(system)
+            .with_input_term("visibility", IntervalSet::blurred(seen(), 0.5))
+            .with_output_term(
+                "alert",
+                IntervalSet::blurred(FuzzySet::triangle("high", 5.0, 8.0, 10.0), 0.5),
+            );
+        let (min, max) = system.evaluate_intervals(&inputs)["alert"];
+        assert!(min < max);
+        assert!(min <= type1 && type1 <= max + 1e-4);
+
+        let nie_tan = system
+            .with_type_reduction(TypeReduction::NieTan)
+            .evaluate_inputs(&inputs)["alert"];
+        assert!(min <= nie_tan && nie_tan <= max);
+    }
+
+    #[test]
+    fn test_karnik_mendel() {
+        assert_eq!(karnik_mendel(&[]), None);
+        assert_eq!(karnik_mendel(&[(0.0, 0.0, 0.0)]), None);
+        assert_eq!(
+            karnik_mendel(&[(0.0, 1.0, 1.0), (10.0, 0.0, 1.0)]),
+            Some((0.0, 5.0))
+        );
+    }
+}
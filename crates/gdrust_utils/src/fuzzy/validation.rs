@@ -0,0 +1,761 @@
+use std::fmt::{self, Debug, Display};
+use std::hash::Hash;
+
+use super::{
+    Consequent, FuzzyRule, FuzzySet, FuzzySystem, FuzzyVariable, Inference, LogicOperators,
+    RuleBlock, Shape,
+};
+
+/// Where a [`FuzzySet`] lives in a [`FuzzySystem`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum SetOwner<OutputType> {
+    /// [`FuzzySystem::input_sets`]
+    InputSets,
+    /// The input variable with this name
+    Input(String),
+    /// The output variable of this output
+    Output(OutputType),
+}
+
+/// How bad a [`Diagnostic`] is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The system doesn't work as written
+    Error,
+    /// The system works, but probably not as intended
+    Warning,
+}
+
+/// A problem found by [`FuzzySystem::validate`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic<InputType, OutputType> {
+    /// A term in the condition of the rule at index `rule` has no matching set, so it never
+    /// fires
+    UnknownCondition {
+        rule: usize,
+        input: Option<String>,
+        category: InputType,
+    },
+    /// The weight of the rule at index `rule` is outside of 0..=1
+    InvalidWeight { rule: usize, weight: f32 },
+    /// The rule at index `rule` points to a missing rule block, so it uses the operators of
+    /// the system
+    UnknownRuleBlock { rule: usize, block: usize },
+    /// A Mamdani consequence of the rule at index `rule` points to a missing output set
+    UnknownOutputTerm {
+        rule: usize,
+        output: OutputType,
+        category: InputType,
+    },
+    /// The point at `index` of a polyline lies left of the point before it
+    UnsortedPoints {
+        owner: SetOwner<OutputType>,
+        category: InputType,
+        index: usize,
+    },
+    /// Two consecutive points of a polyline share this input value. Vertical steps are
+    /// allowed, so this is only a warning.
+    DuplicatePoint {
+        owner: SetOwner<OutputType>,
+        category: InputType,
+        x: f32,
+    },
+    /// A polyline point has a membership degree outside of 0..=1
+    MembershipOutOfRange {
+        owner: SetOwner<OutputType>,
+        category: InputType,
+        degree: f32,
+    },
+    /// The point at `index` of a polyline has a non-finite input or a NaN membership
+    NonFinitePoint {
+        owner: SetOwner<OutputType>,
+        category: InputType,
+        index: usize,
+    },
+    /// A polyline without points
+    EmptySet {
+        owner: SetOwner<OutputType>,
+        category: InputType,
+    },
+    /// Shape parameters that are out of order, or a width or sigma that isn't positive
+    InvalidShape {
+        owner: SetOwner<OutputType>,
+        category: InputType,
+    },
+    /// The universe of an input or output variable is inverted or not finite
+    InvalidUniverse {
+        owner: SetOwner<OutputType>,
+        min: f32,
+        max: f32,
+    },
+    /// No rule reads this set
+    UnusedSet {
+        owner: SetOwner<OutputType>,
+        category: InputType,
+    },
+    /// No rule has a consequence for this output that the inference mode uses
+    OutputNeverProduced { output: OutputType },
+}
+
+impl<InputType, OutputType> Diagnostic<InputType, OutputType> {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Diagnostic::DuplicatePoint { .. }
+            | Diagnostic::UnusedSet { .. }
+            | Diagnostic::OutputNeverProduced { .. } => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity() == Severity::Error
+    }
+}
+
+impl<OutputType: Debug> Display for SetOwner<OutputType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetOwner::InputSets => write!(f, "input sets"),
+            SetOwner::Input(name) => write!(f, "input `{name}`"),
+            SetOwner::Output(output) => write!(f, "output {output:?}"),
+        }
+    }
+}
+
+impl<InputType: Debug, OutputType: Debug> Display for Diagnostic<InputType, OutputType> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::UnknownCondition {
+                rule,
+                input: Some(input),
+                category,
+            } => write!(f, "rule {rule}: no set for `{input}` IS {category:?}"),
+            Diagnostic::UnknownCondition {
+                rule,
+                input: None,
+                category,
+            } => write!(f, "rule {rule}: no input set {category:?}"),
+            Diagnostic::InvalidWeight { rule, weight } => {
+                write!(f, "rule {rule}: weight {weight} is outside of 0..=1")
+            }
+            Diagnostic::UnknownRuleBlock { rule, block } => {
+                write!(f, "rule {rule}: no rule block {block}")
+            }
+            Diagnostic::UnknownOutputTerm {
+                rule,
+                output,
+                category,
+            } => write!(f, "rule {rule}: no set {category:?} for output {output:?}"),
+            Diagnostic::UnsortedPoints {
+                owner,
+                category,
+                index,
+            } => write!(
+                f,
+                "{owner}, set {category:?}: point {index} is out of order"
+            ),
+            Diagnostic::DuplicatePoint { owner, category, x } => {
+                write!(f, "{owner}, set {category:?}: duplicate point at {x}")
+            }
+            Diagnostic::MembershipOutOfRange {
+                owner,
+                category,
+                degree,
+            } => write!(
+                f,
+                "{owner}, set {category:?}: membership {degree} is outside of 0..=1"
+            ),
+            Diagnostic::NonFinitePoint {
+                owner,
+                category,
+                index,
+            } => write!(f, "{owner}, set {category:?}: point {index} is not finite"),
+            Diagnostic::EmptySet { owner, category } => {
+                write!(f, "{owner}, set {category:?}: no points")
+            }
+            Diagnostic::InvalidShape { owner, category } => {
+                write!(f, "{owner}, set {category:?}: invalid shape parameters")
+            }
+            Diagnostic::InvalidUniverse { owner, min, max } => {
+                write!(f, "{owner}: invalid universe {min}..{max}")
+            }
+            Diagnostic::UnusedSet { owner, category } => {
+                write!(f, "{owner}, set {category:?}: not used by any rule")
+            }
+            Diagnostic::OutputNeverProduced { output } => {
+                write!(f, "output {output:?}: never produced by any rule")
+            }
+        }
+    }
+}
+
+impl<InputType, OutputType> FuzzySystem<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash,
+{
+    /// Checks the sets and rules of the system, returning every problem found
+    pub fn validate(&self) -> Vec<Diagnostic<InputType, OutputType>> {
+        let mut diagnostics = Vec::new();
+
+        // Sets read by rule conditions and by Mamdani consequences, by owner and index
+        let mut used_sets: Vec<(SetOwner<OutputType>, usize)> = Vec::new();
+
+        for (index, rule) in self.rules.iter().enumerate() {
+            if !(0.0..=1.0).contains(&rule.weight) {
+                diagnostics.push(Diagnostic::InvalidWeight {
+                    rule: index,
+                    weight: rule.weight,
+                });
+            }
+            if let Some(block) = rule.block.filter(|block| *block >= self.rule_blocks.len()) {
+                diagnostics.push(Diagnostic::UnknownRuleBlock { rule: index, block });
+            }
+            for (input, category) in rule.condition.terms() {
+                match self.term_location(input, category) {
+                    Some(location) => used_sets.push(location),
+                    None => diagnostics.push(Diagnostic::UnknownCondition {
+                        rule: index,
+                        input: input.map(str::to_string),
+                        category: category.clone(),
+                    }),
+                }
+            }
+            for (output, consequent) in &rule.consequences {
+                if let Consequent::Term(category) = consequent {
+                    let term = self
+                        .output_variable(output)
+                        .and_then(|v| v.terms.iter().position(|set| &set.category == category));
+                    match term {
+                        Some(term) => used_sets.push((SetOwner::Output(output.clone()), term)),
+                        None => diagnostics.push(Diagnostic::UnknownOutputTerm {
+                            rule: index,
+                            output: output.clone(),
+                            category: category.clone(),
+                        }),
+                    }
+                }
+            }
+        }
+
+        let variables = self
+            .input_variables
+            .iter()
+            .map(|variable| (SetOwner::Input(variable.name.clone()), variable))
+            .chain(
+                self.output_variables
+                    .iter()
+                    .map(|(output, variable)| (SetOwner::Output(output.clone()), variable)),
+            );
+        let mut owned_sets: Vec<(SetOwner<OutputType>, &[FuzzySet<InputType>])> =
+            vec![(SetOwner::InputSets, &self.input_sets)];
+        for (owner, variable) in variables {
+            let (min, max) = (variable.min, variable.max);
+            if !(min.is_finite() && max.is_finite()) || min > max {
+                diagnostics.push(Diagnostic::InvalidUniverse {
+                    owner: owner.clone(),
+                    min,
+                    max,
+                });
+            }
+            owned_sets.push((owner, &variable.terms));
+        }
+        for (owner, sets) in owned_sets {
+            for (index, set) in sets.iter().enumerate() {
+                check_shape(&owner, set, &mut diagnostics);
+                let key = (owner.clone(), index);
+                if !used_sets.contains(&key) {
+                    diagnostics.push(Diagnostic::UnusedSet {
+                        owner: owner.clone(),
+                        category: set.category.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut outputs: Vec<&OutputType> = self
+            .output_variables
+            .iter()
+            .map(|(output, _)| output)
+            .collect();
+        for output in self.rules.iter().flat_map(|rule| rule.consequences.keys()) {
+            if !outputs.contains(&output) {
+                outputs.push(output);
+            }
+        }
+        for output in outputs {
+            let produced = self.rules.iter().any(|rule| {
+                matches!(
+                    (rule.consequences.get(output), &self.inference),
+                    (
+                        Some(Consequent::Constant(_) | Consequent::Linear(_)),
+                        Inference::WeightedAverage
+                    ) | (Some(Consequent::Term(_)), Inference::Mamdani { .. })
+                )
+            });
+            if !produced {
+                diagnostics.push(Diagnostic::OutputNeverProduced {
+                    output: output.clone(),
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Owner and index of the set of `input IS category`, mirroring [`FuzzySystem::term`]
+    fn term_location(
+        &self,
+        input: Option<&str>,
+        category: &InputType,
+    ) -> Option<(SetOwner<OutputType>, usize)> {
+        let position =
+            |sets: &[FuzzySet<InputType>]| sets.iter().position(|set| &set.category == category);
+        match input.and_then(|name| self.input_variable(name)) {
+            Some(variable) => position(&variable.terms)
+                .map(|index| (SetOwner::Input(variable.name.clone()), index)),
+            None => position(&self.input_sets).map(|index| (SetOwner::InputSets, index)),
+        }
+    }
+
+    /// Starts a [`FuzzySystemBuilder`], which refuses to build systems with errors
+    pub fn builder() -> FuzzySystemBuilder<InputType, OutputType> {
+        FuzzySystemBuilder {
+            system: Self::new(),
+        }
+    }
+}
+
+fn check_shape<InputType: Clone, OutputType: Clone>(
+    owner: &SetOwner<OutputType>,
+    set: &FuzzySet<InputType>,
+    diagnostics: &mut Vec<Diagnostic<InputType, OutputType>>,
+) {
+    let owner = || owner.clone();
+    let category = || set.category.clone();
+    let ordered = |values: &[f32]| values.windows(2).all(|pair| pair[0] <= pair[1]);
+
+    match &set.shape {
+        Shape::Points(points) => {
+            if points.is_empty() {
+                diagnostics.push(Diagnostic::EmptySet {
+                    owner: owner(),
+                    category: category(),
+                });
+            }
+            for (index, pair) in points.windows(2).enumerate() {
+                let ((x1, _), (x2, _)) = (pair[0], pair[1]);
+                if x2 < x1 {
+                    diagnostics.push(Diagnostic::UnsortedPoints {
+                        owner: owner(),
+                        category: category(),
+                        index: index + 1,
+                    });
+                } else if x2 == x1 {
+                    diagnostics.push(Diagnostic::DuplicatePoint {
+                        owner: owner(),
+                        category: category(),
+                        x: x1,
+                    });
+                }
+            }
+            for (index, (x, degree)) in points.iter().enumerate() {
+                if !x.is_finite() || degree.is_nan() {
+                    diagnostics.push(Diagnostic::NonFinitePoint {
+                        owner: owner(),
+                        category: category(),
+                        index,
+                    });
+                } else if !(0.0..=1.0).contains(degree) {
+                    diagnostics.push(Diagnostic::MembershipOutOfRange {
+                        owner: owner(),
+                        category: category(),
+                        degree: *degree,
+                    });
+                }
+            }
+        }
+        shape => {
+            let valid = match *shape {
+                Shape::Triangle { left, peak, right } => ordered(&[left, peak, right]),
+                Shape::Trapezoid {
+                    left,
+                    left_top,
+                    right_top,
+                    right,
+                } => ordered(&[left, left_top, right_top, right]),
+                Shape::Gaussian { sigma, .. } => sigma > 0.0,
+                Shape::Bell { width, slope, .. } => width > 0.0 && slope > 0.0,
+                Shape::Sigmoid { slope, center } => slope.is_finite() && center.is_finite(),
+                Shape::Singleton(value) => value.is_finite(),
+                Shape::Points(_) | Shape::Custom(_) => true,
+            };
+            if !valid {
+                diagnostics.push(Diagnostic::InvalidShape {
+                    owner: owner(),
+                    category: category(),
+                });
+            }
+        }
+    }
+}
+
+/// Builds a [`FuzzySystem`] and [validates](FuzzySystem::validate) it before handing it out
+#[derive(Debug, Clone)]
+pub struct FuzzySystemBuilder<InputType, OutputType> {
+    system: FuzzySystem<InputType, OutputType>,
+}
+
+impl<InputType, OutputType> FuzzySystemBuilder<InputType, OutputType>
+where
+    InputType: Clone + PartialEq,
+    OutputType: Clone + Eq + Hash,
+{
+    pub fn with_input_set(mut self, set: FuzzySet<InputType>) -> Self {
+        self.system.add_input_set(set);
+        self
+    }
+
+    pub fn with_input_variable(mut self, variable: FuzzyVariable<InputType>) -> Self {
+        self.system.add_input_variable(variable);
+        self
+    }
+
+    pub fn with_output_variable(
+        mut self,
+        output: OutputType,
+        variable: FuzzyVariable<InputType>,
+    ) -> Self {
+        self.system.add_output_variable(output, variable);
+        self
+    }
+
+    pub fn with_output_set(mut self, output: OutputType, set: FuzzySet<InputType>) -> Self {
+        self.system.add_output_set(output, set);
+        self
+    }
+
+    pub fn with_rule(mut self, rule: FuzzyRule<InputType, OutputType>) -> Self {
+        self.system.add_rule(rule);
+        self
+    }
+
+    pub fn with_operators(mut self, operators: LogicOperators) -> Self {
+        self.system.operators = operators;
+        self
+    }
+
+    /// Adds a rule block, which rules refer to by its position among the added blocks
+    pub fn with_rule_block(mut self, block: RuleBlock) -> Self {
+        self.system.add_rule_block(block);
+        self
+    }
+
+    pub fn with_inference(mut self, inference: Inference) -> Self {
+        self.system.inference = inference;
+        self
+    }
+
+    /// Returns the system, or every [`Severity::Error`] diagnostic if there are any.
+    /// Warnings don't stop the build.
+    pub fn build(
+        self,
+    ) -> Result<FuzzySystem<InputType, OutputType>, Vec<Diagnostic<InputType, OutputType>>> {
+        let errors: Vec<_> = self
+            .system
+            .validate()
+            .into_iter()
+            .filter(Diagnostic::is_error)
+            .collect();
+        if errors.is_empty() {
+            Ok(self.system)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fuzzy::{Antecedent, Defuzzifier, PartitionShape};
+
+    fn health() -> FuzzyVariable<&'static str> {
+        FuzzyVariable::new("health", 0.0, 100.0)
+            .with_term(FuzzySet::new("low", vec![(0.0, 1.0), (50.0, 0.0)]))
+            .with_term(FuzzySet::new("high", vec![(50.0, 0.0), (100.0, 1.0)]))
+    }
+
+    #[test]
+    fn test_valid_system() {
+        let system = FuzzySystem::builder()
+            .with_input_variable(health())
+            .with_rule(
+                FuzzyRule::new(Antecedent::is("health", "low")).with_consequence("flee", 1.0),
+            )
+            .with_rule(
+                FuzzyRule::new(Antecedent::is("health", "high")).with_consequence("flee", 0.0),
+            )
+            .build()
+            .unwrap();
+        assert!(system.validate().is_empty());
+    }
+
+    #[test]
+    fn test_unknown_condition() {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(health());
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low").and(Antecedent::is("health", "dead")))
+                .with_consequence("flee", 1.0),
+        );
+        system.add_rule(FuzzyRule::new("hot").with_consequence("flee", 0.0));
+
+        let diagnostics = system.validate();
+        assert!(diagnostics.contains(&Diagnostic::UnknownCondition {
+            rule: 0,
+            input: Some("health".to_string()),
+            category: "dead"
+        }));
+        assert!(diagnostics.contains(&Diagnostic::UnknownCondition {
+            rule: 1,
+            input: None,
+            category: "hot"
+        }));
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "rule 0: no set for `health` IS \"dead\""
+        );
+    }
+
+    #[test]
+    fn test_points_problems() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_set(FuzzySet::new(
+            "unsorted",
+            vec![(0.0, 0.0), (5.0, 1.0), (3.0, 0.0)],
+        ));
+        system.add_input_set(FuzzySet::new("step", vec![(0.0, 0.0), (0.0, 1.0)]));
+        system.add_input_set(FuzzySet::new("loud", vec![(0.0, 0.0), (1.0, 1.5)]));
+        system.add_input_set(FuzzySet::new("empty", vec![]));
+        for category in ["unsorted", "step", "loud", "empty"] {
+            system.add_rule(FuzzyRule::new(category).with_consequence("out", 1.0));
+        }
+
+        let owner = SetOwner::InputSets;
+        assert_eq!(
+            system.validate(),
+            vec![
+                Diagnostic::UnsortedPoints {
+                    owner: owner.clone(),
+                    category: "unsorted",
+                    index: 2
+                },
+                Diagnostic::DuplicatePoint {
+                    owner: owner.clone(),
+                    category: "step",
+                    x: 0.0
+                },
+                Diagnostic::MembershipOutOfRange {
+                    owner: owner.clone(),
+                    category: "loud",
+                    degree: 1.5
+                },
+                Diagnostic::EmptySet {
+                    owner,
+                    category: "empty"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_shapes() {
+        let mut system: FuzzySystem<&str, &str> = FuzzySystem::new();
+        system.add_input_set(FuzzySet::triangle("a", 5.0, 0.0, 10.0));
+        system.add_input_set(FuzzySet::gaussian("b", 0.0, 0.0));
+        system.add_input_set(FuzzySet::trapezoid("c", 0.0, 1.0, 2.0, 3.0));
+        for category in ["a", "b", "c"] {
+            system.add_rule(FuzzyRule::new(category).with_consequence("out", 1.0));
+        }
+
+        let invalid: Vec<_> = system
+            .validate()
+            .into_iter()
+            .filter_map(|d| match d {
+                Diagnostic::InvalidShape { category, .. } => Some(category),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(invalid, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_warnings() {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(health());
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_output_set("speed", FuzzySet::triangle("slow", 0.0, 0.0, 5.0));
+        system.add_output_set("speed", FuzzySet::triangle("fast", 5.0, 10.0, 10.0));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low"))
+                .with_output_term("speed", "slow")
+                .with_consequence("flee", 1.0),
+        );
+
+        let diagnostics = system.validate();
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic::UnusedSet {
+                    owner: SetOwner::Input("health".to_string()),
+                    category: "high"
+                },
+                Diagnostic::UnusedSet {
+                    owner: SetOwner::Output("speed"),
+                    category: "fast"
+                },
+                Diagnostic::OutputNeverProduced { output: "flee" },
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.severity() == Severity::Warning));
+    }
+
+    #[test]
+    fn test_unknown_output_term() {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(health());
+        system.inference = Inference::mamdani(Defuzzifier::Centroid);
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low")).with_output_term("speed", "slow"),
+        );
+
+        assert!(system.validate().contains(&Diagnostic::UnknownOutputTerm {
+            rule: 0,
+            output: "speed",
+            category: "slow"
+        }));
+    }
+
+    #[test]
+    fn test_builder_refuses_errors() {
+        let errors = FuzzySystem::builder()
+            .with_input_set(FuzzySet::new("low", vec![(10.0, 1.0), (0.0, 0.0)]))
+            .with_input_set(FuzzySet::new("unused", vec![(0.0, 1.0)]))
+            .with_rule(FuzzyRule::new("low").with_consequence("out", 1.0))
+            .with_rule(FuzzyRule::new("missing").with_consequence("out", 0.0))
+            .build()
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().all(Diagnostic::is_error));
+    }
+
+    #[test]
+    fn test_builder_accepts_vertical_steps() {
+        let variable = FuzzyVariable::new("health", 0.0, 10.0)
+            .with_partition(["low", "high"], PartitionShape::Trapezoid { plateau: 1.0 });
+        let system = FuzzySystem::builder()
+            .with_input_variable(variable)
+            .with_rule(FuzzyRule::new(Antecedent::is("health", "low")).with_consequence("out", 0.0))
+            .with_rule(
+                FuzzyRule::new(Antecedent::is("health", "high")).with_consequence("out", 1.0),
+            )
+            .build()
+            .unwrap();
+        let diagnostics = system.validate();
+        assert!(!diagnostics.is_empty());
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| matches!(diagnostic, Diagnostic::DuplicatePoint { .. })));
+    }
+
+    #[test]
+    fn test_invalid_weight() {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(health());
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low")).with_consequence("flee", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high")).with_consequence("flee", 0.0),
+        );
+        system.rules[1].weight = 1.5;
+
+        assert_eq!(
+            system.validate(),
+            vec![Diagnostic::InvalidWeight {
+                rule: 1,
+                weight: 1.5
+            }]
+        );
+    }
+
+    #[test]
+    fn test_unknown_rule_block() {
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(health());
+        let block = system.add_rule_block(RuleBlock::new("careful", LogicOperators::default()));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low"))
+                .with_consequence("flee", 1.0)
+                .in_block(block),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high"))
+                .with_consequence("flee", 0.0)
+                .in_block(3),
+        );
+
+        assert_eq!(
+            system.validate(),
+            vec![Diagnostic::UnknownRuleBlock { rule: 1, block: 3 }]
+        );
+    }
+
+    #[test]
+    fn test_invalid_universes_and_nan_points() {
+        let mut inverted = health();
+        (inverted.min, inverted.max) = (100.0, 0.0);
+        let mut system = FuzzySystem::new();
+        system.add_input_variable(inverted);
+        system.add_output_variable("flee", FuzzyVariable::new("flee", 0.0, f32::INFINITY));
+        system.add_input_set(FuzzySet::new(
+            "nan",
+            vec![(0.0, 0.0), (f32::NAN, 1.0), (2.0, f32::NAN)],
+        ));
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "low")).with_consequence("flee", 1.0),
+        );
+        system.add_rule(
+            FuzzyRule::new(Antecedent::is("health", "high")).with_consequence("flee", 0.0),
+        );
+        system.add_rule(FuzzyRule::new("nan").with_consequence("flee", 0.5));
+
+        let diagnostics = system.validate();
+        assert!(diagnostics.contains(&Diagnostic::InvalidUniverse {
+            owner: SetOwner::Input("health".to_string()),
+            min: 100.0,
+            max: 0.0
+        }));
+        assert!(diagnostics.contains(&Diagnostic::InvalidUniverse {
+            owner: SetOwner::Output("flee"),
+            min: 0.0,
+            max: f32::INFINITY
+        }));
+        for index in [1, 2] {
+            assert!(diagnostics.contains(&Diagnostic::NonFinitePoint {
+                owner: SetOwner::InputSets,
+                category: "nan",
+                index
+            }));
+        }
+        assert!(diagnostics.iter().all(Diagnostic::is_error));
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "input `health`: invalid universe 100..0"
+        );
+    }
+}
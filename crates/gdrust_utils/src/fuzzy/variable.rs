@@ -0,0 +1,217 @@
+use super::FuzzySet;
+
+/// What a [`FuzzyVariable`] does with crisp values outside of its universe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OutOfRange {
+    /// Clamps the value into the universe
+    #[default]
+    Clamp,
+    /// Treats the value as missing, so none of the terms fire
+    Ignore,
+    /// Uses the value as is
+    Extrapolate,
+}
+
+/// A named linguistic variable like "temperature", owning its universe of discourse and its
+/// terms like "cold", "warm" and "hot"
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(deserialize = "T: serde::Deserialize<'de>"))
+)]
+pub struct FuzzyVariable<T> {
+    pub name: String,
+    pub min: f32,
+    pub max: f32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub terms: Vec<FuzzySet<T>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub out_of_range: OutOfRange,
+}
+
+impl<T> FuzzyVariable<T> {
+    /// A variable without terms over `min..=max`. Inverted bounds are swapped.
+    pub fn new(name: impl Into<String>, min: f32, max: f32) -> Self {
+        let (min, max) = if min > max { (max, min) } else { (min, max) };
+        Self {
+            name: name.into(),
+            min,
+            max,
+            terms: Vec::new(),
+            out_of_range: OutOfRange::default(),
+        }
+    }
+
+    pub fn with_term(mut self, set: FuzzySet<T>) -> Self {
+        self.terms.push(set);
+        self
+    }
+
+    pub fn with_out_of_range(mut self, out_of_range: OutOfRange) -> Self {
+        self.out_of_range = out_of_range;
+        self
+    }
+
+    pub fn add_term(&mut self, set: FuzzySet<T>) {
+        self.terms.push(set);
+    }
+
+    /// Whether `value` lies in the universe, with inverted bounds read in either order
+    pub fn contains(&self, value: f32) -> bool {
+        let (low, high) = self.bounds();
+        (low..=high).contains(&value)
+    }
+
+    /// Applies the out of range policy to a crisp value. Never panics, even if the public
+    /// bounds were inverted or set to NaN after construction.
+    pub fn resolve(&self, value: f32) -> Option<f32> {
+        if self.contains(value) {
+            return Some(value);
+        }
+        match self.out_of_range {
+            OutOfRange::Clamp => {
+                let (low, high) = self.bounds();
+                Some(if value < low {
+                    low
+                } else if value > high {
+                    high
+                } else {
+                    value
+                })
+            }
+            OutOfRange::Ignore => None,
+            OutOfRange::Extrapolate => Some(value),
+        }
+    }
+
+    fn bounds(&self) -> (f32, f32) {
+        if self.min > self.max {
+            (self.max, self.min)
+        } else {
+            (self.min, self.max)
+        }
+    }
+}
+
+impl<T: PartialEq> FuzzyVariable<T> {
+    pub fn term(&self, category: &T) -> Option<&FuzzySet<T>> {
+        self.terms.iter().find(|set| &set.category == category)
+    }
+
+    /// Membership degree of `value` in the term with this category, after applying the out
+    /// of range policy. Unknown terms have a membership of 0.
+    pub fn membership(&self, category: &T, value: f32) -> f32 {
+        match (self.term(category), self.resolve(value)) {
+            (Some(set), Some(value)) => set.membership(value),
+            _ => 0.0,
+        }
+    }
+
+    /// Membership degrees of `value` in all terms, in order
+    pub fn memberships(&self, value: f32) -> Vec<(&T, f32)> {
+        let value = self.resolve(value);
+        self.terms
+            .iter()
+            .map(|set| (&set.category, value.map_or(0.0, |v| set.membership(v))))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temperature() -> FuzzyVariable<&'static str> {
+        FuzzyVariable::new("temperature", 0.0, 50.0)
+            .with_term(FuzzySet::new("cold", vec![(0.0, 1.0), (20.0, 0.0)]))
+            .with_term(FuzzySet::new(
+                "warm",
+                vec![(15.0, 0.0), (25.0, 1.0), (35.0, 0.0)],
+            ))
+            .with_term(FuzzySet::new("hot", vec![(30.0, 0.0), (50.0, 1.0)]))
+    }
+
+    #[test]
+    fn test_variable_creation() {
+        let variable = temperature();
+        assert_eq!(variable.name, "temperature");
+        assert_eq!((variable.min, variable.max), (0.0, 50.0));
+        assert_eq!(variable.terms.len(), 3);
+        assert_eq!(variable.out_of_range, OutOfRange::Clamp);
+    }
+
+    #[test]
+    fn test_term_lookup() {
+        let variable = temperature();
+        assert_eq!(variable.term(&"warm").unwrap().category, "warm");
+        assert!(variable.term(&"freezing").is_none());
+    }
+
+    #[test]
+    fn test_membership() {
+        let variable = temperature();
+        assert_eq!(variable.membership(&"warm", 25.0), 1.0);
+        assert_eq!(variable.membership(&"cold", 10.0), 0.5);
+        assert_eq!(variable.membership(&"freezing", 10.0), 0.0);
+    }
+
+    #[test]
+    fn test_memberships() {
+        let variable = temperature();
+        assert_eq!(
+            variable.memberships(17.5),
+            vec![(&"cold", 0.125), (&"warm", 0.25), (&"hot", 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_clamp() {
+        let variable = FuzzyVariable::new("x", 0.0, 10.0).with_term(FuzzySet::new(
+            "peak",
+            vec![(0.0, 0.0), (10.0, 1.0), (20.0, 0.0)],
+        ));
+
+        assert_eq!(variable.resolve(15.0), Some(10.0));
+        assert_eq!(variable.resolve(-5.0), Some(0.0));
+        assert_eq!(variable.membership(&"peak", 15.0), 1.0);
+    }
+
+    #[test]
+    fn test_out_of_range_ignore() {
+        let variable = temperature().with_out_of_range(OutOfRange::Ignore);
+
+        assert_eq!(variable.resolve(60.0), None);
+        assert_eq!(variable.membership(&"hot", 60.0), 0.0);
+        assert_eq!(variable.membership(&"hot", 50.0), 1.0);
+    }
+
+    #[test]
+    fn test_out_of_range_extrapolate() {
+        let variable = FuzzyVariable::new("x", 0.0, 10.0)
+            .with_out_of_range(OutOfRange::Extrapolate)
+            .with_term(FuzzySet::new(
+                "peak",
+                vec![(0.0, 0.0), (10.0, 1.0), (20.0, 0.0)],
+            ));
+
+        assert_eq!(variable.resolve(15.0), Some(15.0));
+        assert_eq!(variable.membership(&"peak", 15.0), 0.5);
+    }
+
+    #[test]
+    fn test_inverted_and_nan_bounds() {
+        let variable = FuzzyVariable::<&str>::new("x", 10.0, 0.0);
+        assert_eq!((variable.min, variable.max), (0.0, 10.0));
+        assert_eq!(variable.resolve(15.0), Some(10.0));
+
+        // Bounds changed after construction must not panic either
+        let mut variable = FuzzyVariable::<&str>::new("x", 0.0, 10.0);
+        (variable.min, variable.max) = (10.0, 0.0);
+        assert_eq!(variable.resolve(-5.0), Some(0.0));
+        variable.max = f32::NAN;
+        assert_eq!(variable.resolve(-5.0), Some(10.0));
+        assert_eq!(variable.resolve(f32::NAN).map(f32::is_nan), Some(true));
+    }
+}